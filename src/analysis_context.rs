@@ -0,0 +1,120 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use inkwell::module::{Module as InkwellModule};
+use inkwell::values::{AsValueRef, FunctionValue};
+
+use crate::codegen::codegen_global::{get_vtables, Vtable};
+use crate::control_flow_graph::FunctionGraph;
+use crate::harness::SymbolicInput;
+use crate::parameter_domain::ValueRanges;
+use crate::soundness::SoundnessLedger;
+use crate::symbol_table::SymbolTable;
+use crate::symbolic_execution::AnalysisOptions;
+
+
+/// Call through a vtable, split over the implementations of the trait in the module
+pub struct VirtualCall {
+    /// Z3 name of the vtable pointer of the trait object
+    pub vtable_var_name: String,
+    /// Implementations that may be called (ex: `<Square as Shape>`), with the Z3 name of their vtable address
+    pub implementations: Vec<(String, String)>,
+}
+
+
+/// Havocked call to a function outside of the module
+pub struct ExternalCall {
+    /// Name of the called function without its hash (ex: `std::process::id`)
+    pub function_name: String,
+    /// Namespace of the calling function
+    pub namespace: String,
+    /// Z3 names of the arguments, with whether they are booleans
+    pub arguments: Vec<(String, bool)>,
+    /// Z3 name of the return value with whether it is a boolean, None if the call returns nothing
+    pub return_value: Option<(String, bool)>,
+    /// Z3 name of the variable deciding whether the call panics, None if the external call policy rules out panics
+    pub may_panic_var_name: Option<String>,
+}
+
+
+/// Call to a function that always panics, or undefined behaviour reported like a panic
+pub struct PanicSite {
+    /// Path of the called function (ex: `core::option::expect_failed`), or of the function with the undefined behaviour
+    pub function_path: String,
+    /// Message of the panic, if it is a constant string (ex: the message given to `expect`), or the undefined behaviour
+    pub message: Option<String>,
+    /// Whether the site is undefined behaviour (ex: a null pointer dereference) rather than a panic
+    pub is_undefined_behaviour: bool,
+}
+
+
+pub struct AnalysisContext {
+    /// Options the analysis was started with
+    pub options: AnalysisOptions,
+    /// Constructs that were skipped or approximated while encoding the program
+    pub ledger: SoundnessLedger,
+    /// Z3 names of the LLVM values, with the reverse map used for diagnostics
+    pub symbols: SymbolTable,
+    /// Paths of stubbed functions mapped to the names of the stubs replacing them
+    pub stubs: HashMap<String, String>,
+    /// Ranges of the values the parameters of functions may take (ex: the discriminants of an enum), read before converting to DSA
+    pub parameter_ranges: HashMap<String, Vec<Option<ValueRanges>>>,
+    /// Names of the `&str` parameters of the analysed function, passed as a data pointer and a length (ex: `s` for `%s.0` and `%s.1`)
+    pub str_parameters: Vec<String>,
+    /// Values read from the environment of the program (ex: command line arguments, stdin)
+    pub inputs: RefCell<Vec<SymbolicInput>>,
+    /// Calls on trait objects, used to report the safety of each implementation
+    pub virtual_calls: RefCell<Vec<VirtualCall>>,
+    /// Havocked external calls, whose results only depend on their arguments when comparing functions
+    pub external_calls: RefCell<Vec<ExternalCall>>,
+    /// Calls to panic functions, indexed by the value of the panic site variable
+    pub panic_sites: RefCell<Vec<PanicSite>>,
+    /// Instructions whose successors are not encoded, which leave a proof of safety unknown
+    pub unsupported_instructions: RefCell<Vec<String>>,
+    /// Vtables of the analysed modules, keyed by the LLVM handle of the module
+    vtables: RefCell<HashMap<usize, Rc<Vec<Vtable>>>>,
+    /// Control flow graphs of the analysed functions, keyed by the LLVM handle of the function
+    function_graphs: RefCell<HashMap<usize, Rc<FunctionGraph>>>,
+    /// Counter used to create unique names for encoded constructs that have no LLVM name
+    fresh_name_counter: Cell<usize>,
+}
+
+impl AnalysisContext {
+    pub fn new(options: &AnalysisOptions) -> AnalysisContext {
+        return AnalysisContext {
+            options: options.clone(),
+            ledger: SoundnessLedger::new(),
+            symbols: SymbolTable::new(),
+            stubs: HashMap::new(),
+            parameter_ranges: HashMap::new(),
+            str_parameters: Vec::new(),
+            inputs: RefCell::new(Vec::new()),
+            virtual_calls: RefCell::new(Vec::new()),
+            external_calls: RefCell::new(Vec::new()),
+            panic_sites: RefCell::new(Vec::new()),
+            unsupported_instructions: RefCell::new(Vec::new()),
+            vtables: RefCell::new(HashMap::new()),
+            function_graphs: RefCell::new(HashMap::new()),
+            fresh_name_counter: Cell::new(0),
+        };
+    }
+
+    pub fn get_fresh_name(&self, prefix: &str) -> String {
+        let id = self.fresh_name_counter.get();
+        self.fresh_name_counter.set(id + 1);
+        return format!("{}.{}", prefix, id);
+    }
+
+    pub fn get_vtables(&self, module: &InkwellModule) -> Rc<Vec<Vtable>> {
+        //! Vtables of the module, which are looked for on the first call only
+        let module_key = module.as_mut_ptr() as usize;
+        return self.vtables.borrow_mut().entry(module_key).or_insert_with(|| Rc::new(get_vtables(module))).clone();
+    }
+
+    pub fn get_function_graph(&self, function: &FunctionValue) -> Rc<FunctionGraph> {
+        //! Control flow graph of the function, which is built on the first call only
+        let function_key = function.as_value_ref() as usize;
+        return self.function_graphs.borrow_mut().entry(function_key).or_insert_with(|| Rc::new(FunctionGraph::new(function))).clone();
+    }
+}
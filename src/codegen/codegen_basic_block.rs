@@ -4,14 +4,18 @@ use tracing::{warn};
 
 use inkwell::module::{Module as InkwellModule};
 use inkwell::basic_block::BasicBlock;
-use inkwell::values::{FunctionValue, InstructionOpcode};
+use inkwell::values::{AnyValue, FunctionValue, InstructionOpcode};
 
 use z3::Solver;
 use z3::ast::{Ast, Bool, Int};
 
+use crate::analysis_context::AnalysisContext;
 use crate::codegen::codegen_instruction::codegen_instruction;
+use crate::codegen::codegen_undefined_behaviour::codegen_undefined_behaviour_checks;
+use crate::control_flow_graph::get_invoke_destinations;
+use crate::soundness::SoundnessImpact;
 use crate::utils::var_utils::get_var_name;
-use crate::symbolic_execution::{PANIC_VAR_NAME, COMMON_END_NODE};
+use crate::symbolic_execution::{get_panic_var_name, MAIN_FUNCTION_NAMESPACE, ENTRY_PROBE_PREFIX, EXIT_PROBE_PREFIX};
 
 
 pub type EdgeSet = HashMap<String, HashSet<String>>;
@@ -34,6 +38,18 @@ fn get_basic_block_by_name<'a>(function: &'a FunctionValue, name: &String, names
 }
 
 
+pub fn get_entry_probe_name(node: &str) -> String {
+    //! Name of the probe that is falsified when the given node is entered
+    return format!("{}{}", ENTRY_PROBE_PREFIX, node);
+}
+
+
+pub fn get_exit_probe_name(node: &str) -> String {
+    //! Name of the probe that is falsified when all instructions of the given node were executed
+    return format!("{}{}", EXIT_PROBE_PREFIX, node);
+}
+
+
 pub fn is_panic_block(bb: &BasicBlock) -> Option<bool> {
     return if let Some(terminator) = bb.get_terminator() {
         let opcode = terminator.get_opcode();
@@ -48,20 +64,18 @@ pub fn is_panic_block(bb: &BasicBlock) -> Option<bool> {
                 Some(false)
             }
             InstructionOpcode::IndirectBr => {
-                warn!("Unsure if opcode {:?} implies a panicking block.", opcode);
-                None
+                Some(false)
             }
             InstructionOpcode::Invoke => {
-                warn!("Unsure if opcode {:?} implies a panicking block.", opcode);
-                None
+                Some(false)
             }
             InstructionOpcode::CallBr => {
                 warn!("Unsure if opcode {:?} implies a panicking block.", opcode);
                 None
             }
             InstructionOpcode::Resume => {
-                warn!("Unsure if opcode {:?} implies a panicking block.", opcode);
-                None
+                // Propagates the panic that started the unwinding
+                Some(true)
             }
             InstructionOpcode::CatchSwitch => {
                 warn!("Unsure if opcode {:?} implies a panicking block.", opcode);
@@ -92,6 +106,7 @@ pub fn is_panic_block(bb: &BasicBlock) -> Option<bool> {
 
 pub fn get_entry_condition<'a>(
     solver: &'a Solver<'_>,
+    analysis: &AnalysisContext,
     function: &'a FunctionValue,
     predecessor: &str,
     node: &str,
@@ -117,7 +132,7 @@ pub fn get_entry_condition<'a>(
                         Bool::from_bool(solver.get_context(), target_val);
                     let switch_var = Bool::new_const(
                         solver.get_context(),
-                        get_var_name(&discriminant, &solver, namespace),
+                        get_var_name(&discriminant, &solver, analysis, namespace),
                     );
 
                     entry_condition = switch_var._eq(&target_val_var);
@@ -141,7 +156,7 @@ pub fn get_entry_condition<'a>(
                 }
                 let switch_var = Int::new_const(
                     solver.get_context(),
-                    get_var_name(&discriminant, &solver, namespace),
+                    get_var_name(&discriminant, &solver, analysis, namespace),
                 );
 
                 if target_val == terminator.get_operand(0).unwrap().left().unwrap() {
@@ -149,18 +164,24 @@ pub fn get_entry_condition<'a>(
                     for j in 2..num_operands {
                         if j % 2 == 0 { 
                             let temp_target_val = terminator.get_operand(j).unwrap().left().unwrap();
-                            let temp_target_val_var = Int::new_const(solver.get_context(), get_var_name(&temp_target_val, &solver, namespace));
+                            let temp_target_val_var = Int::new_const(solver.get_context(), get_var_name(&temp_target_val, &solver, analysis, namespace));
                             entry_condition = Bool::and(solver.get_context(), &[&(switch_var._eq(&temp_target_val_var)).not(), &entry_condition]);
                         }
                     }
                 } else {
-                    let target_val_var = Int::new_const(solver.get_context(), get_var_name(&target_val, &solver, namespace));
+                    let target_val_var = Int::new_const(solver.get_context(), get_var_name(&target_val, &solver, analysis, namespace));
                     entry_condition = switch_var._eq(&target_val_var);
                 }
             }
             InstructionOpcode::Return => {
                 // Unconditionally go to node
             }
+            InstructionOpcode::Invoke => {
+                // Unconditionally go to node (the unwind destination is entered through the panics of the invoked function)
+            }
+            InstructionOpcode::IndirectBr => {
+                // Any destination may be branched to
+            }
             InstructionOpcode::Unreachable => {
                 // Unconditionally go to node
             }
@@ -182,13 +203,29 @@ pub fn codegen_basic_block(
     backward_edges: &EdgeSet,
     function: &FunctionValue,
     solver: &Solver,
+    analysis: &AnalysisContext,
     namespace: &str,
     call_stack: &str,
+    panic_target_node: &str,
     return_register: &str
 ) -> () {
+    let basic_block = get_basic_block_by_name(&function, &node, namespace).unwrap();
+    if is_panic_block(&basic_block).is_none() {
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("Successors of basic block {} with an unsupported terminator are not analyzed", node));
+        let terminator_string = basic_block.get_terminator().map(|terminator| terminator.print_to_string().to_string()).unwrap_or(node.clone());
+        analysis.unsupported_instructions.borrow_mut().push(terminator_string.trim().to_string());
+    }
+
+    // The unwind destination of an invoke is the panic target of the invoked function rather than a successor
+    let terminator = get_basic_block_by_name(&function, &node, namespace).unwrap().get_terminator();
+    let unwind_destination = terminator.and_then(|terminator| get_invoke_destinations(&terminator, namespace)).map(|(_, unwind_destination)| unwind_destination);
+
     let mut successor_conditions = Bool::from_bool(solver.get_context(), true);
     if let Some(successors) = forward_edges.get(&node) {
         for successor in successors {
+            if Some(successor) == unwind_destination.as_ref() {
+                continue;
+            }
             let successor_var =
                 Bool::new_const(solver.get_context(), String::from(successor));
             successor_conditions =
@@ -197,30 +234,45 @@ pub fn codegen_basic_block(
     }
     let mut node_var = successor_conditions;
 
-    if forward_edges.get(&node).is_some() && forward_edges.get(&node).unwrap().contains(COMMON_END_NODE) {
+    if forward_edges.get(&node).is_some() && forward_edges.get(&node).unwrap().contains(panic_target_node) {
         // assign panic_var
-        let lvalue_var = Bool::new_const(solver.get_context(), PANIC_VAR_NAME);
+        let lvalue_var = Bool::new_const(solver.get_context(), get_panic_var_name(panic_target_node));
         let is_panic = is_panic_block(&get_basic_block_by_name(&function, &node, namespace).unwrap()).unwrap_or(true);
         let rvalue_var = Bool::from_bool(solver.get_context(), is_panic);
         let assignment = lvalue_var._eq(&rvalue_var);
         node_var = assignment.implies(&node_var);
     }
 
+    // Reachability probes are only placed in the analyzed function (and asserted true unless probed)
+    let is_probed = namespace == MAIN_FUNCTION_NAMESPACE;
+    if is_probed {
+        let exit_probe_var = Bool::new_const(solver.get_context(), get_exit_probe_name(&node));
+        node_var = Bool::and(solver.get_context(), &[&exit_probe_var, &node_var]);
+    }
+
     // Parse statements in the basic block
     let mut prev_instruction = get_basic_block_by_name(&function, &node, namespace).unwrap().get_last_instruction();
 
     while let Some(current_instruction) = prev_instruction {
         // Process current instruction
-        node_var = codegen_instruction(&module, &node, node_var, current_instruction, function, solver, namespace, call_stack, return_register);
+        node_var = codegen_instruction(&module, &node, node_var, current_instruction, function, solver, analysis, namespace, call_stack, panic_target_node, return_register);
+        if analysis.options.check_undefined_behaviour {
+            node_var = codegen_undefined_behaviour_checks(&module, node_var, current_instruction, function, solver, analysis, namespace, panic_target_node, return_register);
+        }
         prev_instruction = current_instruction.get_previous_instruction();
     }
 
+    if is_probed {
+        let entry_probe_var = Bool::new_const(solver.get_context(), get_entry_probe_name(&node));
+        node_var = Bool::and(solver.get_context(), &[&entry_probe_var, &node_var]);
+    }
+
     let mut entry_conditions = Bool::from_bool(solver.get_context(), true);
     if let Some(predecessors) = backward_edges.get(&node) {
         if predecessors.len() > 0 {
             for predecessor in predecessors {
                 // get conditions
-                let entry_condition = get_entry_condition(&solver, &function, &predecessor, &node, namespace);
+                let entry_condition = get_entry_condition(&solver, analysis, &function, &predecessor, &node, namespace);
                 entry_conditions = Bool::and(solver.get_context(), &[&entry_conditions, &entry_condition]);
             }
         }
@@ -1,46 +1,71 @@
-use tracing::{warn};
-
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::module::{Module as InkwellModule};
-use inkwell::values::{InstructionValue};
+use inkwell::types::{AnyType, AnyTypeEnum};
+use inkwell::values::{AnyValue, BasicValue, FunctionValue, GlobalValue, InstructionValue, PointerValue};
 
 use z3::Solver;
 use z3::ast::{Ast, Bool, Int};
 
+use crate::analysis_context::{AnalysisContext, ExternalCall, PanicSite, VirtualCall};
 use crate::codegen::codegen_function::codegen_function;
+use crate::codegen::codegen_global::{get_constant_string, get_vtable_slot, Vtable};
 use crate::control_flow_graph::forward_topological_sort;
-use crate::utils::function_utils::{get_function_name, get_function_by_name};
-use crate::utils::var_utils::{get_min_max_signed_int, get_var_name};
-use crate::symbolic_execution::get_module_name_from_file_name;
+use crate::companion::{is_companion_call, COMPANION_ANY_PATH, COMPANION_ASSUME_PATH};
+use crate::harness::{is_input_call, is_output_call, SymbolicInput};
+use crate::panics::{get_default_panic_message, is_panic_sink, is_panicking_method, PANIC_SITE_VAR_NAME};
+use crate::llvm_constant::{get_base_global, get_cast_global};
+use crate::codegen::codegen_heap::{codegen_heap_call, codegen_vec_call, get_heap_function, get_vec_method};
+use crate::codegen::codegen_str::{codegen_str_call, get_byte_pointer_assignment, get_str_method, is_byte_sequence_type};
+use crate::codegen::codegen_intrinsic::{codegen_intrinsic, get_intrinsic_model, get_intrinsic_name};
+use crate::codegen::codegen_undefined_behaviour::{get_address_var_name, get_pointer_address};
+use crate::utils::function_utils::{get_call_argument_count, get_called_function, get_function_name, get_function_path, strip_function_hash};
+use crate::utils::var_utils::{get_domain_constraint, get_var_name, is_function_pointer_type};
+use crate::soundness::SoundnessImpact;
+use crate::stubs::get_stub_function;
+use crate::symbolic_execution::{get_module_name_from_file_name, get_panic_var_name, ExternalCallPolicy};
+
+
+/// Name given to indirect calls whose targets are all outside of the module
+const INDIRECT_CALL_NAME: &str = "indirect call";
+
 
 fn codegen_general_call<'a>(
     module: &InkwellModule,
     mut node_var: Bool<'a>,
     instruction: InstructionValue,
+    function: FunctionValue,
     solver: &'a Solver,
+    analysis: &AnalysisContext,
     namespace: &str,
-    call_stack: &str
+    call_stack: &str,
+    panic_target_node: &str,
+    post_node_suffix: &str
 ) -> Bool<'a> {
-    // Create named POST_NODE from node_var
-    let new_return_register_string = get_var_name(&instruction, solver, namespace);
+    // A stub whose arity does not match the replaced function cannot be called with its arguments
+    let argument_count = get_call_argument_count(&instruction);
+    if u32::try_from(function.get_params().len()).ok() != Some(argument_count) {
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("Call to {} passes {} arguments to a function with {} parameters, the call is skipped", function.get_name().to_str().unwrap(), argument_count, function.get_params().len()));
+        return node_var;
+    }
+
+    // Create named POST_NODE from node_var (the suffix tells apart the candidate targets of an indirect call)
+    let new_return_register_string = get_var_name(&instruction, solver, analysis, namespace);
     let new_return_register_str = new_return_register_string.as_str();
-    let post_node_name_string = format!("{}_{}", namespace, new_return_register_str);
+    let post_node_name_string = format!("{}_{}{}", namespace, new_return_register_str, post_node_suffix);
     let post_node_name_str = post_node_name_string.as_str();
     let post_node = Bool::new_const(solver.get_context(), post_node_name_str);
     solver.assert(&post_node._eq(&node_var));
     let new_namespace = format!("{}.", post_node);
 
     // Code gen function with return to POST_NODE and request to assign return value to new return register
-    let call_operand = instruction.get_operand(instruction.get_num_operands()-1)
-        .unwrap().left().unwrap().into_pointer_value();
-    let call_operation_name_string = get_function_name(&call_operand);
-    let function = get_function_by_name(module, &call_operation_name_string).unwrap();
-    let new_return_register_string = get_var_name(&instruction, solver, namespace);
+    let call_operation_name_string = get_function_name(&function.as_global_value().as_pointer_value());
+    let new_return_register_string = get_var_name(&instruction, solver, analysis, namespace);
     let new_return_register_str = new_return_register_string.as_str();
     let new_call_stack_string = format!("{},{}", call_stack, function.get_name().to_str().unwrap());
-    codegen_function(module, &function, solver, new_namespace.as_str(), new_call_stack_string.as_str(), &post_node_name_str, new_return_register_str);
+    codegen_function(module, &function, solver, analysis, new_namespace.as_str(), new_call_stack_string.as_str(), &post_node_name_str, panic_target_node, new_return_register_str);
     
     // CALL_NODE: Start node of function
-    let called_function_forward_sorted_nodes = forward_topological_sort(&function, &new_namespace, post_node_name_str);
+    let called_function_forward_sorted_nodes = forward_topological_sort(&function, &new_namespace, post_node_name_str, panic_target_node);
     if called_function_forward_sorted_nodes.len() > 0 {
         let call_node_name = called_function_forward_sorted_nodes.first().unwrap();
         node_var = Bool::new_const(solver.get_context(), call_node_name.as_str());
@@ -50,24 +75,35 @@ fn codegen_general_call<'a>(
     }
 
     // PRE_NODE with CALL_NODE as successor: Assign call arguments
-    // TODO: Handle try_from fail
-    assert!(u32::try_from(function.get_params().len()).unwrap() == instruction.get_num_operands() - 1);
     for i in 0..function.get_params().len() {
         // TODO: Support other input types
         let params = function.get_params();
         let input = params.get(i).unwrap();
         if input.get_type().to_string().eq("\"i1\"") {
-            let lvalue = Bool::new_const(solver.get_context(), get_var_name(input, &solver, &new_namespace));
-            let rvalue = Bool::new_const(solver.get_context(), get_var_name(&instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap(), &solver, namespace));
+            let lvalue = Bool::new_const(solver.get_context(), get_var_name(input, &solver, analysis, &new_namespace));
+            let rvalue = Bool::new_const(solver.get_context(), get_var_name(&instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap(), &solver, analysis, namespace));
             let assignment = lvalue._eq(&rvalue);
             node_var = assignment.implies(&node_var);
-        } else if input.get_type().is_int_type() {
-            let lvalue = Int::new_const(solver.get_context(), get_var_name(input, &solver, &new_namespace));
-            let rvalue = Int::new_const(solver.get_context(), get_var_name(&instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap(), &solver, namespace));
+        } else if input.get_type().is_int_type() || is_function_pointer_type(&input.get_type().as_any_type_enum()) || is_vtable_argument(module, analysis, &instruction, i) {
+            let lvalue = Int::new_const(solver.get_context(), get_var_name(input, &solver, analysis, &new_namespace));
+            let rvalue = Int::new_const(solver.get_context(), get_var_name(&instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap(), &solver, analysis, namespace));
             let assignment = lvalue._eq(&rvalue);
             node_var = assignment.implies(&node_var);
+        } else if is_byte_sequence_type(&input.get_type().as_any_type_enum()) {
+            let lvalue_name = get_var_name(input, &solver, analysis, &new_namespace);
+            let rvalue_name = get_var_name(&instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let assignment = get_byte_pointer_assignment(solver, &lvalue_name, &rvalue_name, &Int::from_u64(solver.get_context(), 0));
+            node_var = assignment.implies(&node_var);
         }  else {
-            warn!("Currently unsupported type {:?} for input parameter to {}", input.get_type().to_string(), call_operation_name_string);
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for input parameter to {}", input.get_type().to_string(), call_operation_name_string));
+        }
+        // The UB checker passes the addresses of pointer arguments, which the pointers of the called function are derived from
+        if analysis.options.check_undefined_behaviour && input.get_type().is_pointer_type() {
+            let argument = instruction.get_operand(u32::try_from(i).unwrap()).unwrap().left().unwrap();
+            if let Some(argument_address) = get_pointer_address(module, &argument, solver, analysis, namespace) {
+                let parameter_address = Int::new_const(solver.get_context(), get_address_var_name(&get_var_name(input, &solver, analysis, &new_namespace)));
+                node_var = parameter_address._eq(&argument_address).implies(&node_var);
+            }
         }
     }
 
@@ -75,152 +111,467 @@ fn codegen_general_call<'a>(
     return node_var;
 }
 
-pub fn codegen_call<'a>(
+fn codegen_external_call<'a>(
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    call_operation_name_str: &str
+) -> Bool<'a> {
+    //! Encodes a call to a function outside of the module according to the external call policy
+    let return_type = instruction.get_type();
+    let mut return_value = None;
+    if !return_type.is_void_type() {
+        // Havoc the return value within its type's domain
+        let return_register_name = get_var_name(&instruction, &solver, analysis, namespace);
+        if let Some(domain_constraint) = get_domain_constraint(solver, &return_register_name, &return_type) {
+            node_var = domain_constraint.implies(&node_var);
+            return_value = Some((return_register_name, return_type.to_string().eq("\"i1\"")));
+        } else {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Return value of type {} of external call {:?} is unconstrained", return_type.to_string(), call_operation_name_str));
+        }
+    }
+
+    let policy = analysis.options.external_calls;
+    let mut may_panic_var_name = None;
+    match policy {
+        ExternalCallPolicy::Havoc => {
+            analysis.ledger.record(SoundnessImpact::Unsound, format!("External call {:?} is havocked and its panics are ignored (policy: {})", call_operation_name_str, policy));
+        }
+        ExternalCallPolicy::AssumeNoPanic => {
+            analysis.ledger.record(SoundnessImpact::Assumption, format!("External call {:?} is havocked and assumed not to panic (policy: {})", call_operation_name_str, policy));
+        }
+        ExternalCallPolicy::AssumeMayPanic => {
+            // Either the call panics (reaching the panic target node) or it returns
+            let may_panic_name = analysis.get_fresh_name(&format!("{}{}.may_panic", namespace, call_operation_name_str));
+            let may_panic_var = Bool::new_const(solver.get_context(), may_panic_name.as_str());
+            may_panic_var_name = Some(may_panic_name);
+            let panic_var = Bool::new_const(solver.get_context(), get_panic_var_name(panic_target_node));
+            let panic_target_node_var = Bool::new_const(solver.get_context(), panic_target_node);
+            let panic_path = panic_var._eq(&Bool::from_bool(solver.get_context(), true)).implies(&panic_target_node_var);
+            node_var = may_panic_var.ite(&panic_path, &node_var);
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("External call {:?} is havocked and may panic (policy: {})", call_operation_name_str, policy));
+        }
+    }
+
+    // Targets of indirect calls are unknown, so their results are not tied to those of other calls
+    if call_operation_name_str == INDIRECT_CALL_NAME {
+        return node_var;
+    }
+    let mut arguments = Vec::<(String, bool)>::new();
+    for i in 0..get_call_argument_count(&instruction) {
+        let argument = instruction.get_operand(i).unwrap().left().unwrap();
+        if argument.get_type().to_string().eq("\"i1\"") {
+            arguments.push((get_var_name(&argument, solver, analysis, namespace), true));
+        } else if argument.get_type().is_int_type() {
+            arguments.push((get_var_name(&argument, solver, analysis, namespace), false));
+        } else {
+            // Calls with other arguments (ex: pointers) may differ even when their encoded arguments are equal
+            return node_var;
+        }
+    }
+    analysis.external_calls.borrow_mut().push(ExternalCall {
+        function_name: String::from(strip_function_hash(call_operation_name_str)),
+        namespace: String::from(namespace),
+        arguments,
+        return_value,
+        may_panic_var_name,
+    });
+    return node_var;
+}
+
+
+fn codegen_companion_call<'a>(
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    function_path: &str
+) -> Bool<'a> {
+    //! Encodes the calls to the companion crate used by verification harnesses
+    match function_path {
+        COMPANION_ANY_PATH => {
+            let return_type = instruction.get_type();
+            let return_register_name = get_var_name(&instruction, &solver, analysis, namespace);
+            if let Some(domain_constraint) = get_domain_constraint(solver, &return_register_name, &return_type) {
+                node_var = domain_constraint.implies(&node_var);
+            } else {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Symbolic value of type {} is unconstrained", return_type.to_string()));
+            }
+        }
+        COMPANION_ASSUME_PATH => {
+            let condition_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            node_var = Bool::new_const(solver.get_context(), condition_name).implies(&node_var);
+        }
+        _ => {
+            // A failed assertion reaches the panic target node
+            let condition_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let panic_var = Bool::new_const(solver.get_context(), get_panic_var_name(panic_target_node));
+            let panic_target_node_var = Bool::new_const(solver.get_context(), panic_target_node);
+            let panic_path = panic_var._eq(&Bool::from_bool(solver.get_context(), true)).implies(&panic_target_node_var);
+            node_var = Bool::new_const(solver.get_context(), condition_name).ite(&node_var, &panic_path);
+        }
+    }
+    return node_var;
+}
+
+
+fn has_writable_pointer_argument(module: &InkwellModule, instruction: &InstructionValue) -> bool {
+    //! Pointer arguments the called function may write through (ex: the `&mut String` of `read_line`), which rustc does not mark `readonly`
+    let function = match get_called_function(module, instruction) {
+        Some(function) => function,
+        None => return true,
+    };
+    let readonly_kind_id = Attribute::get_named_enum_kind_id("readonly");
+    return (0..get_call_argument_count(instruction)).any(|index| {
+        let is_pointer = instruction.get_operand(index).and_then(|operand| operand.left()).map_or(false, |operand| operand.is_pointer_value());
+        is_pointer && function.get_enum_attribute(AttributeLoc::Param(index), readonly_kind_id).is_none()
+    });
+}
+
+
+fn codegen_input_call<'a>(
     module: &InkwellModule,
     mut node_var: Bool<'a>,
     instruction: InstructionValue,
     solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    function_path: &str
+) -> Bool<'a> {
+    //! Inputs of the program (ex: command line arguments, stdin) take arbitrary values within their type's domain
+    //! Inputs returned as a pair (ex: `io::Result<usize>` of `read_line`) are read field by field
+    let return_type = instruction.get_type();
+    if return_type.is_void_type() {
+        // Inputs returned through an out pointer (ex: `String`) are arbitrary values for the loads from it
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Input read by {:?} through an out pointer is unconstrained", function_path));
+        return node_var;
+    }
+    if has_writable_pointer_argument(module, &instruction) {
+        // Only the returned value is symbolic, the memory written by the call (ex: the line read into a `String`) keeps its previous value
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("Input written by {:?} through a pointer argument is not symbolic", function_path));
+    }
+    let return_register_name = get_var_name(&instruction, &solver, analysis, namespace);
+    let fields: Vec<(String, AnyTypeEnum, Option<u32>)> = if return_type.is_struct_type() {
+        return_type.into_struct_type().get_field_types().iter().enumerate()
+            .map(|(index, field_type)| (format!("{}.{}", return_register_name, index), field_type.as_any_type_enum(), Some(index as u32)))
+            .collect()
+    } else {
+        vec![(return_register_name, return_type, None)]
+    };
+    for (var_name, var_type, field) in fields {
+        if let Some(domain_constraint) = get_domain_constraint(solver, &var_name, &var_type) {
+            node_var = domain_constraint.implies(&node_var);
+            analysis.inputs.borrow_mut().push(SymbolicInput {
+                function_path: String::from(function_path),
+                field,
+                var_name,
+                is_bool: var_type.into_int_type().get_bit_width() == 1,
+            });
+        } else {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Input of type {} read by {:?} is unconstrained", var_type.to_string(), function_path));
+        }
+    }
+    return node_var;
+}
+
+
+fn codegen_panic_call<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
     namespace: &str,
-    call_stack: &str
+    function_path: &str
+) -> Bool<'a> {
+    //! Panic sinks are followed by `unreachable`, which makes the block panic, the call records the panic site with its message
+    //! The message is read from the `&str` arguments (ex: `expect("msg")`), if they are constant
+    let message = match get_default_panic_message(function_path) {
+        Some(message) => Some(String::from(message)),
+        None if get_call_argument_count(&instruction) >= 2 => {
+            let message_pointer = instruction.get_operand(0).unwrap().left().unwrap();
+            let message_length = instruction.get_operand(1).unwrap().left().unwrap();
+            get_constant_string(module, &message_pointer, &message_length, solver, analysis, namespace)
+        }
+        None => None,
+    };
+
+    // The site is only assigned on the paths that panic there, so the model of a panicking run tells which site it reached
+    let mut panic_sites = analysis.panic_sites.borrow_mut();
+    let panic_site_var = Int::new_const(solver.get_context(), PANIC_SITE_VAR_NAME);
+    let assignment = panic_site_var._eq(&Int::from_u64(solver.get_context(), panic_sites.len() as u64));
+    panic_sites.push(PanicSite {
+        function_path: String::from(function_path),
+        message,
+        is_undefined_behaviour: false,
+    });
+    return assignment.implies(&node_var);
+}
+
+
+fn get_global_address(module: &InkwellModule, global: &GlobalValue) -> u64 {
+    //! Distinct non-null address of a function or global variable (ex: a vtable), given by its position in the module
+    //! Functions are numbered before global variables, so their addresses never overlap
+    let mut address = 1;
+    let mut next_function = module.get_first_function();
+    while let Some(current_function) = next_function {
+        if current_function.as_global_value() == *global {
+            return address;
+        }
+        address += 1;
+        next_function = current_function.get_next_function();
+    }
+    let mut next_global = module.get_first_global();
+    while let Some(current_global) = next_global {
+        if current_global == *global {
+            return address;
+        }
+        address += 1;
+        next_global = current_global.get_next_global();
+    }
+    return address;
+}
+
+
+fn is_vtable_argument(module: &InkwellModule, analysis: &AnalysisContext, instruction: &InstructionValue, argument_index: usize) -> bool {
+    //! Whether a call argument is a (cast) vtable, passed along with the data pointer of a trait object
+    let argument = instruction.get_operand(u32::try_from(argument_index).unwrap()).unwrap().left().unwrap();
+    if !argument.is_pointer_value() || !argument.into_pointer_value().is_const() {
+        return false;
+    }
+    return get_cast_global(&argument).map_or(false, |name| analysis.get_vtables(module).iter().any(|vtable| vtable.global_name == name));
+}
+
+
+fn get_indirect_call_targets<'ctx>(module: &InkwellModule<'ctx>, call_operand: &PointerValue) -> (Vec<FunctionValue<'ctx>>, usize) {
+    //! Functions of the module whose signature matches the called function pointer, and the number of matching external functions
+    let called_type = call_operand.get_type().get_element_type();
+    let mut targets = Vec::<FunctionValue>::new();
+    let mut external_target_count = 0;
+    let mut next_function = module.get_first_function();
+    while let Some(current_function) = next_function {
+        if AnyTypeEnum::from(current_function.get_type()) == called_type {
+            if current_function.count_basic_blocks() > 0 {
+                targets.push(current_function);
+            } else {
+                external_target_count += 1;
+            }
+        }
+        next_function = current_function.get_next_function();
+    }
+    return (targets, external_target_count);
+}
+
+
+fn get_cast_function<'ctx>(module: &InkwellModule<'ctx>, call_operand: &PointerValue) -> Option<FunctionValue<'ctx>> {
+    //! Function called through a constant cast (ex: `bitcast (void (i32*)* @f to void (i8*)*)`)
+    if !call_operand.is_const() || !call_operand.get_name().to_bytes().is_empty() {
+        return None;
+    }
+    return module.get_function(&get_base_global(&call_operand.as_basic_value_enum())?);
+}
+
+
+fn codegen_indirect_call<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    call_stack: &str,
+    panic_target_node: &str,
+    call_operand: &PointerValue
+) -> Bool<'a> {
+    //! Calls through function pointers are split over the functions of the module with a matching signature
+    let (targets, external_target_count) = get_indirect_call_targets(module, call_operand);
+    if external_target_count > 0 {
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("Indirect call {:?} may target {} external functions, which are not analyzed", instruction.print_to_string().to_string(), external_target_count));
+    }
+    if targets.is_empty() {
+        return codegen_external_call(node_var, instruction, solver, analysis, namespace, panic_target_node, INDIRECT_CALL_NAME);
+    }
+
+    let call_operand_var = Int::new_const(solver.get_context(), get_var_name(call_operand, solver, analysis, namespace));
+    let mut is_target_conditions = Vec::<Bool>::new();
+    let mut target_node_vars = Vec::<Bool>::new();
+    for (target_index, target) in targets.iter().enumerate() {
+        let target_var = Int::new_const(solver.get_context(), get_var_name(&target.as_global_value().as_pointer_value(), solver, analysis, namespace));
+        solver.assert(&target_var._eq(&Int::from_u64(solver.get_context(), get_global_address(module, &target.as_global_value()))));
+        let is_target = call_operand_var._eq(&target_var);
+        let target_node_var = codegen_general_call(module, node_var.clone(), instruction, *target, solver, analysis, namespace, call_stack, panic_target_node, &format!(".target{}", target_index));
+        target_node_vars.push(is_target.implies(&target_node_var));
+        is_target_conditions.push(is_target);
+    }
+
+    // The called function pointer is one of the candidate targets, whichever it may be
+    let is_any_target = Bool::or(solver.get_context(), &is_target_conditions.iter().collect::<Vec<&Bool>>());
+    let all_target_node_vars = Bool::and(solver.get_context(), &target_node_vars.iter().collect::<Vec<&Bool>>());
+
+    // Otherwise (ex: an unconstrained function pointer parameter) an unknown function is called, as if it was external
+    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Indirect call {:?} to none of its candidate targets is encoded as an external call", instruction.print_to_string().to_string()));
+    let unknown_target_node_var = codegen_external_call(node_var, instruction, solver, analysis, namespace, panic_target_node, INDIRECT_CALL_NAME);
+    return is_any_target.ite(&all_target_node_vars, &unknown_target_node_var);
+}
+
+
+fn codegen_virtual_call<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    call_stack: &str,
+    panic_target_node: &str,
+    call_operand: &PointerValue
+) -> Option<Bool<'a>> {
+    //! Calls through a vtable slot are split over the vtables of the module, calling the function of each vtable at that slot
+    //! None if the called function pointer is not loaded from a vtable
+    let (vtable_pointer, slot_offset) = get_vtable_slot(module, call_operand, solver)?;
+    let argument_count = get_call_argument_count(&instruction);
+    let vtables = analysis.get_vtables(module);
+    let implementations: Vec<(&Vtable, FunctionValue)> = vtables.iter()
+        .filter_map(|vtable| {
+            let slot_function = vtable.slots.iter().find(|(offset, _)| *offset == slot_offset).and_then(|(_, name)| module.get_function(name))?;
+            // Methods take the receiver as `&Self` rather than the erased `{}*`, so only their arity is compared
+            if slot_function.count_params() != argument_count || slot_function.count_basic_blocks() == 0 {
+                return None;
+            }
+            Some((vtable, slot_function))
+        })
+        .collect();
+    if implementations.is_empty() {
+        return None;
+    }
+
+    let vtable_var_name = get_var_name(&vtable_pointer, solver, analysis, namespace);
+    let vtable_var = Int::new_const(solver.get_context(), vtable_var_name.as_str());
+    let mut is_implementation_conditions = Vec::<Bool>::new();
+    let mut implementation_node_vars = Vec::<Bool>::new();
+    let mut implementation_vtables = Vec::<(String, String)>::new();
+    for (implementation_index, (vtable, slot_function)) in implementations.iter().enumerate() {
+        let vtable_global = module.get_global(&vtable.global_name).unwrap();
+        let vtable_address_var_name = get_var_name(&vtable_global.as_pointer_value(), solver, analysis, namespace);
+        let vtable_address_var = Int::new_const(solver.get_context(), vtable_address_var_name.as_str());
+        solver.assert(&vtable_address_var._eq(&Int::from_u64(solver.get_context(), get_global_address(module, &vtable_global))));
+        let is_implementation = vtable_var._eq(&vtable_address_var);
+        let implementation_node_var = codegen_general_call(module, node_var.clone(), instruction, *slot_function, solver, analysis, namespace, call_stack, panic_target_node, &format!(".impl{}", implementation_index));
+        implementation_node_vars.push(is_implementation.implies(&implementation_node_var));
+        is_implementation_conditions.push(is_implementation);
+        implementation_vtables.push((vtable.implementation.clone(), vtable_address_var_name));
+    }
+    analysis.virtual_calls.borrow_mut().push(VirtualCall {
+        vtable_var_name,
+        implementations: implementation_vtables,
+    });
+
+    // The trait object was created by one of the implementations of the module, whichever it may be
+    let is_any_implementation = Bool::or(solver.get_context(), &is_implementation_conditions.iter().collect::<Vec<&Bool>>());
+    let all_implementation_node_vars = Bool::and(solver.get_context(), &implementation_node_vars.iter().collect::<Vec<&Bool>>());
+    return Some(is_any_implementation.implies(&all_implementation_node_vars));
+}
+
+
+pub fn codegen_call<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    call_stack: &str,
+    panic_target_node: &str
 ) -> Bool<'a> {
     let call_operand = instruction.get_operand(instruction.get_num_operands()-1)
         .unwrap().left().unwrap().into_pointer_value();
-    let call_operation_name_string = get_function_name(&call_operand);
+    if !call_operand.is_const() {
+        if let Some(node_var) = codegen_virtual_call(module, node_var.clone(), instruction, solver, analysis, namespace, call_stack, panic_target_node, &call_operand) {
+            return node_var;
+        }
+        return codegen_indirect_call(module, node_var, instruction, solver, analysis, namespace, call_stack, panic_target_node, &call_operand);
+    }
+    // Functions called through a constant cast are called directly
+    if let Some(cast_function) = get_cast_function(module, &call_operand).filter(|function| function.count_params() == get_call_argument_count(&instruction)) {
+        return codegen_direct_call(module, node_var, instruction, solver, analysis, namespace, call_stack, panic_target_node, &cast_function.as_global_value().as_pointer_value());
+    }
+    return codegen_direct_call(module, node_var, instruction, solver, analysis, namespace, call_stack, panic_target_node, &call_operand);
+}
+
+
+fn codegen_direct_call<'a>(
+    module: &InkwellModule,
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    call_stack: &str,
+    panic_target_node: &str,
+    call_operand: &PointerValue
+) -> Bool<'a> {
+    let call_operation_name_string = get_function_name(call_operand);
     let call_operation_name_str = call_operation_name_string.as_str();
 
-    let module_name = get_module_name_from_file_name(&String::from(module.get_name().to_str().unwrap()));
-    if call_operation_name_str.find(&module_name).is_some() {
-        return codegen_general_call(module, node_var, instruction, solver, namespace, call_stack);
+    // User-provided stubs replace the called function, wherever it is defined
+    if let Some(stub_function) = get_stub_function(module, analysis, call_operand) {
+        analysis.ledger.record(SoundnessImpact::Assumption, format!("Call {:?} is replaced by stub {:?}", call_operation_name_str, stub_function.get_name().to_str().unwrap()));
+        return codegen_general_call(module, node_var, instruction, stub_function, solver, analysis, namespace, call_stack, panic_target_node, "");
+    }
+
+    // Instances of generic methods that may panic (ex: `Option::<i32>::unwrap`) are defined in the module
+    if is_panicking_method(&get_function_path(call_operand)) {
+        if let Some(function) = module.get_function(call_operand.get_name().to_str().unwrap()).filter(|function| function.count_basic_blocks() > 0) {
+            return codegen_general_call(module, node_var, instruction, function, solver, analysis, namespace, call_stack, panic_target_node, "");
+        }
+    }
+
+    // Functions of the crate (ex: `module::foo` or `<module::Foo as Trait>::bar`) are inlined when the module defines them
+    let function_path = get_function_path(call_operand);
+    let module_prefix = format!("{}::", get_module_name_from_file_name(&String::from(module.get_name().to_str().unwrap())));
+    if function_path.starts_with(&module_prefix) || function_path.starts_with(&format!("<{}", module_prefix)) {
+        if let Some(function) = module.get_function(call_operand.get_name().to_str().unwrap()).filter(|function| function.count_basic_blocks() > 0) {
+            return codegen_general_call(module, node_var, instruction, function, solver, analysis, namespace, call_stack, panic_target_node, "");
+        }
     }
 
     match call_operation_name_str {
-        s if s.starts_with("llvm.sadd.with.overflow.i") => {
-            // Translate the intrinsic integer size to an i64 representing the min/max representable numbers
-            let s_size = &s[25..];
-            let (min_int_val, max_int_val) = get_min_max_signed_int(s_size);
-
-            let operand1_name = get_var_name(
-                &instruction.get_operand(0).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-            let operand2_name = get_var_name(
-                &instruction.get_operand(1).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-
-            let lvalue_var_name_1 = format!("{}.0", get_var_name(&instruction, &solver, namespace));
-            let lvalue_var_1 = Int::new_const(solver.get_context(), lvalue_var_name_1);
-            let rvalue_var_1 = Int::add(solver.get_context(), &[
-                &Int::new_const(solver.get_context(), operand1_name),
-                &Int::new_const(solver.get_context(), operand2_name)
-            ]);
-            
-            let assignment_1 = lvalue_var_1._eq(&rvalue_var_1);
-
-            let lvalue_var_name_2 = format!("{}.1", get_var_name(&instruction, &solver, namespace));
-            let min_int = Int::from_i64(solver.get_context(), min_int_val);
-            let max_int = Int::from_i64(solver.get_context(), max_int_val);
-            let rvalue_var_2 = Bool::or(solver.get_context(), &[&rvalue_var_1.gt(&max_int), &rvalue_var_1.lt(&min_int)]);
-            
-            let assignment_2 = Bool::new_const(solver.get_context(), lvalue_var_name_2)._eq(&rvalue_var_2);
-            let assignment = Bool::and(solver.get_context(), &[&assignment_1, &assignment_2]);
-            node_var = assignment.implies(&node_var);
+        _ if is_panic_sink(&function_path) => {
+            node_var = codegen_panic_call(module, node_var, instruction, solver, analysis, namespace, &function_path);
         }
-        s if s.starts_with("llvm.ssub.with.overflow.i") => {
-            // Translate the intrinsic integer size to an i64 representing the min/max representable numbers
-            let s_size = &s[25..];
-            let (min_int_val, max_int_val) = get_min_max_signed_int(s_size);
-
-            let operand1_name = get_var_name(
-                &instruction.get_operand(0).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-            let operand2_name = get_var_name(
-                &instruction.get_operand(1).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-
-            let lvalue_var_name_1 = format!("{}.0", get_var_name(&instruction, &solver, namespace));
-            let lvalue_var_1 = Int::new_const(solver.get_context(), lvalue_var_name_1);
-            let rvalue_var_1 = Int::sub(solver.get_context(), &[
-                &Int::new_const(solver.get_context(), operand1_name),
-                &Int::new_const(solver.get_context(), operand2_name)
-            ]);
-            
-            let assignment_1 = lvalue_var_1._eq(&rvalue_var_1);
-
-            let lvalue_var_name_2 = format!("{}.1", get_var_name(&instruction, &solver, namespace));
-            let min_int = Int::from_i64(solver.get_context(), min_int_val);
-            let max_int = Int::from_i64(solver.get_context(), max_int_val);
-            let rvalue_var_2 = Bool::or(solver.get_context(), &[&rvalue_var_1.gt(&max_int), &rvalue_var_1.lt(&min_int)]);
-            
-            let assignment_2 = Bool::new_const(solver.get_context(), lvalue_var_name_2)._eq(&rvalue_var_2);
-            let assignment = Bool::and(solver.get_context(), &[&assignment_1, &assignment_2]);
-            node_var = assignment.implies(&node_var);
+        _ if get_heap_function(&function_path).is_some() => {
+            node_var = codegen_heap_call(module, node_var, instruction, solver, analysis, namespace, panic_target_node, get_heap_function(&function_path).unwrap(), &function_path);
         }
-        s if s.starts_with("llvm.smul.with.overflow.i") => {
-            // Translate the intrinsic integer size to an i64 representing the min/max representable numbers
-            let s_size = &s[25..];
-            let (min_int_val, max_int_val) = get_min_max_signed_int(s_size);
-
-            let operand1_name = get_var_name(
-                &instruction.get_operand(0).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-            let operand2_name = get_var_name(
-                &instruction.get_operand(1).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-
-            let lvalue_var_name_1 = format!("{}.0", get_var_name(&instruction, &solver, namespace));
-            let lvalue_var_1 = Int::new_const(solver.get_context(), lvalue_var_name_1);
-            let rvalue_var_1 = Int::mul(solver.get_context(), &[
-                &Int::new_const(solver.get_context(), operand1_name),
-                &Int::new_const(solver.get_context(), operand2_name)
-            ]);
-            
-            let assignment_1 = lvalue_var_1._eq(&rvalue_var_1);
-
-            let lvalue_var_name_2 = format!("{}.1", get_var_name(&instruction, &solver, namespace));
-            let min_int = Int::from_i64(solver.get_context(), min_int_val);
-            let max_int = Int::from_i64(solver.get_context(), max_int_val);
-            let rvalue_var_2 = Bool::or(solver.get_context(), &[&rvalue_var_1.gt(&max_int), &rvalue_var_1.lt(&min_int)]);
-            
-            let assignment_2 = Bool::new_const(solver.get_context(), lvalue_var_name_2)._eq(&rvalue_var_2);
-            let assignment = Bool::and(solver.get_context(), &[&assignment_1, &assignment_2]);
-            node_var = assignment.implies(&node_var);
+        _ if get_vec_method(&function_path, &instruction).is_some() => {
+            node_var = codegen_vec_call(module, node_var, instruction, solver, analysis, namespace, panic_target_node, get_vec_method(&function_path, &instruction).unwrap());
         }
-        "llvm.expect.i1" => {
-            let lvalue_var_name = get_var_name(
-                &instruction,
-                &solver,
-                namespace
-            );
-            let operand1_name = get_var_name(
-                &instruction.get_operand(0).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-            let operand2_name = get_var_name(
-                &instruction.get_operand(1).unwrap().left().unwrap(),
-                &solver,
-                namespace
-            );
-            let rvalue_var = Bool::new_const(solver.get_context(), operand1_name)._eq(&Bool::new_const(solver.get_context(), operand2_name));
-            let assignment = Bool::new_const(solver.get_context(), lvalue_var_name)._eq(&rvalue_var);
-            node_var = assignment.implies(&node_var);
+        _ if get_str_method(&function_path, &instruction).is_some() => {
+            node_var = codegen_str_call(node_var, instruction, solver, analysis, namespace, panic_target_node, get_str_method(&function_path, &instruction).unwrap());
+        }
+        s if s.starts_with("llvm.dbg.") => {
+            // NO-OP: debug info intrinsics do not affect the program semantics
+        }
+        _ if is_companion_call(&function_path) => {
+            node_var = codegen_companion_call(node_var, instruction, solver, analysis, namespace, panic_target_node, &function_path);
+        }
+        _ if is_input_call(&function_path) => {
+            node_var = codegen_input_call(module, node_var, instruction, solver, analysis, namespace, &function_path);
         }
-        s if s.starts_with("core::panicking::panic") => {
-            // NO-OP
+        _ if is_output_call(&function_path) => {
+            // NO-OP: writing to stdout or stderr only panics if the stream is closed
+            analysis.ledger.record(SoundnessImpact::Assumption, format!("Output call {:?} is assumed not to panic", function_path));
         }
         _ => {
-            warn!("Unsupported Call function {:?}", call_operation_name_str);
+            // Calls are modelled by the name of the intrinsic or helper, regardless of the types it is instantiated with
+            if let Some(model) = get_intrinsic_model(&get_intrinsic_name(&function_path)) {
+                return codegen_intrinsic(node_var, instruction, solver, analysis, namespace, model, &function_path);
+            }
+            node_var = codegen_external_call(node_var, instruction, solver, analysis, namespace, panic_target_node, call_operation_name_str);
         }
     }
     return node_var;
@@ -1,16 +1,16 @@
-use tracing::{warn};
-
 use inkwell::module::{Module as InkwellModule};
 use inkwell::values::{FunctionValue};
 
 use z3::Solver;
 
+use crate::analysis_context::AnalysisContext;
 use crate::codegen::codegen_basic_block::codegen_basic_block;
 use crate::control_flow_graph::{get_forward_edges, get_backward_edges, backward_topological_sort};
+use crate::soundness::SoundnessImpact;
 use crate::utils::pretty_print::pretty_print_function;
 
 
-pub fn codegen_function(module: &InkwellModule, function: &FunctionValue, solver: &Solver, namespace: &str, call_stack: &str, return_target_node: &str, return_register: &str) -> () {
+pub fn codegen_function(module: &InkwellModule, function: &FunctionValue, solver: &Solver, analysis: &AnalysisContext, namespace: &str, call_stack: &str, return_target_node: &str, panic_target_node: &str, return_register: &str) -> () {
     //! Perform backward symbolic execution on a function given the llvm-ir function object
     
     let call_stack_vec = std::vec::Vec::from_iter(call_stack.split(","));
@@ -18,7 +18,8 @@ pub fn codegen_function(module: &InkwellModule, function: &FunctionValue, solver
         let current_call = *call_stack_vec.last().unwrap();
         for i in 0..call_stack_vec.len()-1 {
             if call_stack_vec.get(i).unwrap().eq(&current_call) {
-                warn!("Recursive call to {} in call stack {:?} cannot be analyzed!", current_call, call_stack);
+                // The entry of the recursive call is left unconstrained, so the call may lead anywhere
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Recursive call to {} in call stack {:?} cannot be analyzed!", current_call, call_stack));
                 return;
             }
         }
@@ -26,11 +27,15 @@ pub fn codegen_function(module: &InkwellModule, function: &FunctionValue, solver
 
     pretty_print_function(&function, namespace);
 
-    let forward_edges = get_forward_edges(&function, namespace, return_target_node);
-    let backward_edges = get_backward_edges(&function, namespace, return_target_node);
-    let backward_sorted_nodes = backward_topological_sort(&function, namespace, return_target_node);
+    let forward_edges = get_forward_edges(&function, namespace, return_target_node, panic_target_node);
+    let backward_edges = get_backward_edges(&function, namespace, return_target_node, panic_target_node);
+    let backward_sorted_nodes = backward_topological_sort(&function, namespace, return_target_node, panic_target_node);
+    if backward_sorted_nodes.len() < function.get_basic_blocks().len() {
+        // The blocks of the cycle are left unconstrained, so entering the cycle may lead anywhere
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Basic blocks in a cycle of {} are not analyzed", function.get_name().to_str().unwrap()));
+    }
 
     for node in backward_sorted_nodes {
-        codegen_basic_block(&module, node, &forward_edges, &backward_edges, function, solver, namespace, call_stack, return_register);
+        codegen_basic_block(&module, node, &forward_edges, &backward_edges, function, solver, analysis, namespace, call_stack, panic_target_node, return_register);
     }
 }
\ No newline at end of file
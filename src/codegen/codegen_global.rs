@@ -0,0 +1,445 @@
+use inkwell::module::{Module as InkwellModule};
+use inkwell::targets::{ByteOrdering, TargetData};
+use inkwell::types::{AnyType, AnyTypeEnum, BasicTypeEnum};
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, GlobalValue, InstructionOpcode, InstructionValue, PointerValue};
+
+use z3::Solver;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::analysis_context::AnalysisContext;
+use crate::llvm_constant::{get_aggregate_elements, get_base_global, get_constant_expression, get_global_name, is_undefined_constant, is_zero_constant, ConstantExpression};
+use crate::soundness::SoundnessImpact;
+use crate::utils::function_utils::get_function_path;
+use crate::utils::var_utils::get_var_name;
+
+
+/// Upper bound on the offsets enumerated for a load at a symbolic offset into a global
+const MAX_SYMBOLIC_OFFSETS: usize = 4096;
+
+
+/// Initializer of a global laid out in memory
+pub struct GlobalInitializer {
+    /// Bytes of the initializer, None for padding, `undef` and pointers
+    pub bytes: Vec<Option<u8>>,
+    /// Byte offsets of the pointers to globals, with the name of the global pointed to
+    pub pointers: Vec<(usize, String)>,
+}
+
+
+/// Vtable of a trait object (ex: `@vtable.0` for `&Square as &dyn Shape`)
+pub struct Vtable {
+    /// Name of the global holding the vtable (ex: `vtable.0`)
+    pub global_name: String,
+    /// Implementation the vtable was created for (ex: `<Square as Shape>`)
+    pub implementation: String,
+    /// Byte offsets of the slots holding functions of the module, with the name of the function
+    pub slots: Vec<(usize, String)>,
+}
+
+
+pub fn get_target_data(module: &InkwellModule) -> TargetData {
+    return TargetData::create(module.get_data_layout().as_str().to_str().unwrap());
+}
+
+
+pub fn get_pointer_width(module: &InkwellModule) -> u32 {
+    //! Width in bits of pointers, `usize` and `isize` on the target the module was compiled for
+    return get_target_data(module).get_pointer_byte_size(None) * 8;
+}
+
+
+pub fn get_element_offset<'a>(solver: &'a Solver, target_data: &TargetData, source_type: &BasicTypeEnum, indices: &[Int<'a>]) -> Option<Int<'a>> {
+    //! Byte offset addressed by the indices of a `getelementptr` over the source type
+    //! Struct fields must be selected by constant indices, array and vector elements may be selected by symbolic ones
+    let (first_index, field_indices) = indices.split_first()?;
+    let mut offset = first_index * &Int::from_u64(solver.get_context(), target_data.get_abi_size(source_type));
+    let mut current_type = *source_type;
+    for index in field_indices {
+        match current_type {
+            BasicTypeEnum::StructType(struct_type) => {
+                let field_index = u32::try_from(index.simplify().as_u64()?).ok()?;
+                offset = offset + Int::from_u64(solver.get_context(), target_data.offset_of_element(&struct_type, field_index)?);
+                current_type = struct_type.get_field_type_at_index(field_index)?;
+            }
+            BasicTypeEnum::ArrayType(array_type) => {
+                current_type = array_type.get_element_type();
+                offset = offset + index * &Int::from_u64(solver.get_context(), target_data.get_abi_size(&current_type));
+            }
+            BasicTypeEnum::VectorType(vector_type) => {
+                current_type = vector_type.get_element_type();
+                offset = offset + index * &Int::from_u64(solver.get_context(), target_data.get_abi_size(&current_type));
+            }
+            _ => return None,
+        }
+    }
+    return Some(offset);
+}
+
+
+fn get_constant_pointer<'a>(solver: &'a Solver, target_data: &TargetData, constant: &BasicValueEnum) -> Option<(String, Int<'a>)> {
+    //! Global and byte offset a constant pointer expression points to (ex: `getelementptr inbounds ([4 x i32], [4 x i32]* @TABLE, i64 0, i64 2)` -> `TABLE`, 8)
+    if let Some(global_name) = get_global_name(constant) {
+        return Some((global_name, Int::from_i64(solver.get_context(), 0)));
+    }
+    return match get_constant_expression(constant)? {
+        ConstantExpression::PointerCast(operand) => get_constant_pointer(solver, target_data, &operand),
+        ConstantExpression::GetElementPtr(base, indices) => {
+            let (global_name, base_offset) = get_constant_pointer(solver, target_data, &base)?;
+            let source_type = BasicTypeEnum::try_from(base.into_pointer_value().get_type().get_element_type()).ok()?;
+            let indices = indices.iter()
+                .map(|index| Some(Int::from_i64(solver.get_context(), index.into_int_value().get_sign_extended_constant()?)))
+                .collect::<Option<Vec<Int>>>()?;
+            let offset = get_element_offset(solver, target_data, &source_type, &indices)?;
+            Some((global_name, base_offset + offset))
+        }
+        _ => None,
+    };
+}
+
+
+pub fn get_global_instruction_pointer<'a, 'ctx>(
+    module: &InkwellModule<'ctx>,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<(GlobalValue<'ctx>, Int<'a>)> {
+    //! Global and byte offset an address computation points to, None if it is not based on a global
+    return match instruction.get_opcode() {
+        InstructionOpcode::BitCast => get_global_pointer(module, &instruction.get_operand(0)?.left()?, solver, analysis, namespace),
+        InstructionOpcode::GetElementPtr => {
+            let base = instruction.get_operand(0)?.left()?;
+            let (global, base_offset) = get_global_pointer(module, &base, solver, analysis, namespace)?;
+            let source_type = BasicTypeEnum::try_from(base.into_pointer_value().get_type().get_element_type()).ok()?;
+            let mut indices = Vec::<Int>::new();
+            for operand_index in 1..instruction.get_num_operands() {
+                let index = instruction.get_operand(operand_index)?.left()?;
+                indices.push(Int::new_const(solver.get_context(), get_var_name(&index, solver, analysis, namespace)));
+            }
+            let offset = get_element_offset(solver, &get_target_data(module), &source_type, &indices)?;
+            Some((global, base_offset + offset))
+        }
+        _ => None,
+    };
+}
+
+
+pub fn get_global_pointer<'a, 'ctx>(
+    module: &InkwellModule<'ctx>,
+    pointer: &BasicValueEnum,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<(GlobalValue<'ctx>, Int<'a>)> {
+    //! Global and byte offset a pointer points to, following casts and address computations back to the global
+    if !pointer.is_pointer_value() {
+        return None;
+    }
+    let pointer = pointer.into_pointer_value();
+    if !pointer.is_const() {
+        return get_global_instruction_pointer(module, &pointer.as_instruction()?, solver, analysis, namespace);
+    }
+    let name = pointer.get_name().to_str().unwrap().to_string();
+    if !name.is_empty() {
+        return Some((module.get_global(&name)?, Int::from_i64(solver.get_context(), 0)));
+    }
+    let (global_name, offset) = get_constant_pointer(solver, &get_target_data(module), &pointer.as_basic_value_enum())?;
+    return Some((module.get_global(&global_name)?, offset));
+}
+
+
+fn write_constant_bytes(target_data: &TargetData, constant_type: &BasicTypeEnum, constant: &BasicValueEnum, initializer: &mut GlobalInitializer, offset: usize) -> Option<()> {
+    //! Writes the bytes of a constant at the offset, leaving padding, `undef` and pointers as unknown bytes
+    //! Pointers to globals are recorded with their offset
+    if is_undefined_constant(constant) {
+        return Some(());
+    }
+    if is_zero_constant(constant) {
+        let abi_size = usize::try_from(target_data.get_abi_size(constant_type)).ok()?;
+        for byte in initializer.bytes.get_mut(offset..offset + abi_size)? {
+            *byte = Some(0);
+        }
+        return Some(());
+    }
+    match constant {
+        BasicValueEnum::IntValue(int_value) => {
+            // Booleans are stored as 1, wider than 64 bit integers are left unknown
+            let value = if int_value.get_type().get_bit_width() == 1 {
+                int_value.get_zero_extended_constant().map(|value| i128::from(value))
+            } else if int_value.get_type().get_bit_width() <= 64 {
+                int_value.get_sign_extended_constant().map(|value| i128::from(value))
+            } else {
+                None
+            };
+            if let Some(value) = value {
+                let size = usize::try_from(target_data.get_store_size(constant_type)).ok()?;
+                let is_big_endian = target_data.get_byte_ordering() == ByteOrdering::BigEndian;
+                for byte_index in 0..size.min(16) {
+                    let position = if is_big_endian { offset + size - 1 - byte_index } else { offset + byte_index };
+                    *initializer.bytes.get_mut(position)? = Some(((value >> (8 * byte_index)) & 0xff) as u8);
+                }
+            }
+        }
+        BasicValueEnum::PointerValue(..) => {
+            if let Some(global_name) = get_base_global(constant) {
+                initializer.pointers.push((offset, global_name));
+            }
+        }
+        BasicValueEnum::ArrayValue(..) | BasicValueEnum::StructValue(..) | BasicValueEnum::VectorValue(..) => {
+            for (element_index, element) in get_aggregate_elements(constant)?.iter().enumerate() {
+                let element_index = u32::try_from(element_index).ok()?;
+                let (element_type, element_offset) = match constant_type {
+                    BasicTypeEnum::StructType(struct_type) => (struct_type.get_field_type_at_index(element_index)?, target_data.offset_of_element(struct_type, element_index)?),
+                    BasicTypeEnum::ArrayType(array_type) => (array_type.get_element_type(), u64::from(element_index) * target_data.get_abi_size(&array_type.get_element_type())),
+                    BasicTypeEnum::VectorType(vector_type) => (vector_type.get_element_type(), u64::from(element_index) * target_data.get_abi_size(&vector_type.get_element_type())),
+                    _ => return None,
+                };
+                write_constant_bytes(target_data, &element_type, element, initializer, offset + usize::try_from(element_offset).ok()?)?;
+            }
+        }
+        _ => {
+            // Unsupported constants (ex: floats, `ptrtoint` expressions)
+        }
+    }
+    return Some(());
+}
+
+
+fn get_global_initializer(target_data: &TargetData, global: &GlobalValue) -> Option<GlobalInitializer> {
+    //! Layout of the initializer of a global, None if it is not supported
+    let global_type = BasicTypeEnum::try_from(global.as_pointer_value().get_type().get_element_type()).ok()?;
+    let constant = global.get_initializer()?;
+    let mut initializer = GlobalInitializer {
+        bytes: vec![None; usize::try_from(target_data.get_abi_size(&global_type)).ok()?],
+        pointers: Vec::new(),
+    };
+    write_constant_bytes(target_data, &global_type, &constant, &mut initializer, 0)?;
+    return Some(initializer);
+}
+
+
+fn get_loaded_value(bytes: &[Option<u8>], is_big_endian: bool, bit_width: u32) -> Option<i64> {
+    //! Signed value of the loaded bytes, None if one of them is unknown
+    let mut value: u128 = 0;
+    for byte_index in 0..bytes.len() {
+        let byte = bytes[if is_big_endian { byte_index } else { bytes.len() - 1 - byte_index }]?;
+        value = (value << 8) | u128::from(byte);
+    }
+    let value = value & ((1u128 << bit_width) - 1);
+    if (value >> (bit_width - 1)) & 1 == 1 {
+        return i64::try_from(value as i128 - (1i128 << bit_width)).ok();
+    }
+    return i64::try_from(value).ok();
+}
+
+
+fn get_preceding_global_store<'a, 'ctx>(
+    module: &InkwellModule<'ctx>,
+    instruction: &InstructionValue<'ctx>,
+    global: &GlobalValue,
+    offset: &Int<'a>,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<BasicValueEnum<'ctx>> {
+    //! Value stored at the same offset into the global earlier in the block of the load, None if it may have been overwritten since
+    //! Calls and stores through pointers which are not known to point into another global may write to the global
+    let global_name = global.get_name().to_str().unwrap();
+    let concrete_offset = offset.simplify().as_i64()?;
+    let mut previous_instruction = instruction.get_previous_instruction();
+    while let Some(current_instruction) = previous_instruction {
+        previous_instruction = current_instruction.get_previous_instruction();
+        match current_instruction.get_opcode() {
+            InstructionOpcode::Call | InstructionOpcode::Invoke | InstructionOpcode::CallBr => return None,
+            InstructionOpcode::Store => {
+                let stored_value = current_instruction.get_operand(0)?.left()?;
+                let (stored_global, stored_offset) = get_global_pointer(module, &current_instruction.get_operand(1)?.left()?, solver, analysis, namespace)?;
+                if stored_global.get_name().to_str().unwrap().ne(global_name) {
+                    continue;
+                }
+                if stored_offset.simplify().as_i64()? != concrete_offset || stored_value.get_type().as_any_type_enum() != instruction.get_type() {
+                    return None;
+                }
+                return Some(stored_value);
+            }
+            _ => {}
+        }
+    }
+    return None;
+}
+
+
+pub fn codegen_global_load<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: InstructionValue,
+    global: GlobalValue,
+    offset: Int<'a>,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Bool<'a> {
+    //! Loads from immutable globals read their initializer, loads from `static mut` read the value stored earlier in the block if any
+    //! Other loads from `static mut` or external globals are unconstrained
+    let global_name = global.get_name().to_str().unwrap().to_string();
+    let load_type = instruction.get_type();
+    if !global.is_constant() && load_type.is_int_type() {
+        if let Some(stored_value) = get_preceding_global_store(module, &instruction, &global, &offset, solver, analysis, namespace) {
+            let lvalue_var_name = get_var_name(&instruction, solver, analysis, namespace);
+            let rvalue_var_name = get_var_name(&stored_value, solver, analysis, namespace);
+            let assignment = if load_type.into_int_type().get_bit_width() == 1 {
+                Bool::new_const(solver.get_context(), lvalue_var_name)._eq(&Bool::new_const(solver.get_context(), rvalue_var_name))
+            } else {
+                Int::new_const(solver.get_context(), lvalue_var_name)._eq(&Int::new_const(solver.get_context(), rvalue_var_name))
+            };
+            return assignment.implies(&node_var);
+        }
+    }
+    if !global.is_constant() || global.get_initializer().is_none() {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Load from mutable or external global @{} is unconstrained", global_name));
+        return node_var;
+    }
+    if !load_type.is_int_type() || load_type.into_int_type().get_bit_width() > 64 {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for load from global @{}", load_type.to_string(), global_name));
+        return node_var;
+    }
+    let target_data = get_target_data(module);
+    let bytes = match get_global_initializer(&target_data, &global) {
+        Some(initializer) => initializer.bytes,
+        None => {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Initializer of global @{} is not supported", global_name));
+            return node_var;
+        }
+    };
+
+    let bit_width = load_type.into_int_type().get_bit_width();
+    let size = usize::try_from(target_data.get_store_size(&AnyTypeEnum::from(load_type.into_int_type()))).unwrap();
+    let candidate_offsets: Vec<usize> = match offset.simplify().as_i64() {
+        Some(concrete_offset) => usize::try_from(concrete_offset).into_iter().collect(),
+        None if bytes.len() < size => Vec::new(),
+        None if bytes.len() - size + 1 > MAX_SYMBOLIC_OFFSETS => {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Load at a symbolic offset into global @{} is unconstrained", global_name));
+            return node_var;
+        }
+        None => (0..=bytes.len() - size).collect(),
+    };
+
+    // Offsets out of bounds or over unknown bytes (ex: pointers) leave the loaded value unconstrained
+    let is_big_endian = target_data.get_byte_ordering() == ByteOrdering::BigEndian;
+    let lvalue_var_name = get_var_name(&instruction, solver, analysis, namespace);
+    let mut assignments = Vec::<Bool>::new();
+    for candidate_offset in candidate_offsets {
+        let value = match bytes.get(candidate_offset..candidate_offset + size) {
+            Some(loaded_bytes) => get_loaded_value(loaded_bytes, is_big_endian, bit_width),
+            None => None,
+        };
+        if value.is_none() {
+            continue;
+        }
+        let assignment = if bit_width == 1 {
+            Bool::new_const(solver.get_context(), lvalue_var_name.as_str())._eq(&Bool::from_bool(solver.get_context(), value.unwrap() != 0))
+        } else {
+            Int::new_const(solver.get_context(), lvalue_var_name.as_str())._eq(&Int::from_i64(solver.get_context(), value.unwrap()))
+        };
+        assignments.push(offset._eq(&Int::from_u64(solver.get_context(), candidate_offset as u64)).implies(&assignment));
+    }
+    if assignments.is_empty() {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Load of unknown bytes from global @{} is unconstrained", global_name));
+        return node_var;
+    }
+    let assignment = Bool::and(solver.get_context(), &assignments.iter().collect::<Vec<&Bool>>());
+    return assignment.implies(&node_var);
+}
+
+
+
+pub fn get_vtables(module: &InkwellModule) -> Vec<Vtable> {
+    //! Immutable globals holding pointers to functions of the module, as emitted for trait objects
+    //! Use `AnalysisContext::get_vtables`, which only looks for the vtables of a module once
+    let target_data = get_target_data(module);
+    let mut vtables = Vec::new();
+    let mut next_global = module.get_first_global();
+    while let Some(global) = next_global {
+        next_global = global.get_next_global();
+        if !global.is_constant() || global.get_initializer().is_none() {
+            continue;
+        }
+        let initializer = match get_global_initializer(&target_data, &global) {
+            Some(initializer) => initializer,
+            None => continue,
+        };
+        let slots: Vec<(usize, FunctionValue)> = initializer.pointers.iter()
+            .filter_map(|(offset, name)| module.get_function(name).map(|function| (*offset, function)))
+            .collect();
+        if slots.is_empty() {
+            continue;
+        }
+        // Methods are named after their implementation (ex: `<Square as Shape>::sides`), unlike `drop_in_place`
+        let global_name = global.get_name().to_str().unwrap().to_string();
+        let implementation = slots.iter()
+            .map(|(_, function)| get_function_path(&function.as_global_value().as_pointer_value()))
+            .find(|path| path.starts_with('<') && path.contains(" as "))
+            .and_then(|path| path.rfind("::").map(|position| path[..position].to_string()))
+            .unwrap_or_else(|| format!("@{}", global_name));
+        let slots = slots.iter().map(|(offset, function)| (*offset, function.get_name().to_str().unwrap().to_string())).collect();
+        vtables.push(Vtable { global_name, implementation, slots });
+    }
+    return vtables;
+}
+
+
+pub fn get_vtable_slot<'ctx>(module: &InkwellModule, function_pointer: &PointerValue<'ctx>, solver: &Solver) -> Option<(BasicValueEnum<'ctx>, usize)> {
+    //! Vtable pointer and byte offset of the slot a function pointer is loaded from, None if it is not loaded at a constant offset
+    //! ex: `%f = load i32 ({}*)*, i32 ({}*)** %slot` with `%slot = getelementptr inbounds i32 ({}*)*, i32 ({}*)** %vtable, i64 3` -> `%vtable`, 24
+    let load = function_pointer.as_instruction()?;
+    if load.get_opcode() != InstructionOpcode::Load {
+        return None;
+    }
+    let target_data = get_target_data(module);
+    let mut pointer = load.get_operand(0)?.left()?;
+    let mut offset: i64 = 0;
+    while let Some(instruction) = pointer.into_pointer_value().as_instruction() {
+        match instruction.get_opcode() {
+            InstructionOpcode::BitCast => {
+                pointer = instruction.get_operand(0)?.left()?;
+            }
+            InstructionOpcode::GetElementPtr => {
+                let base = instruction.get_operand(0)?.left()?;
+                let source_type = BasicTypeEnum::try_from(base.into_pointer_value().get_type().get_element_type()).ok()?;
+                let mut indices = Vec::<Int>::new();
+                for operand_index in 1..instruction.get_num_operands() {
+                    let index = instruction.get_operand(operand_index)?.left()?.into_int_value().get_sign_extended_constant()?;
+                    indices.push(Int::from_i64(solver.get_context(), index));
+                }
+                offset += get_element_offset(solver, &target_data, &source_type, &indices)?.simplify().as_i64()?;
+                pointer = base;
+            }
+            _ => break,
+        }
+    }
+    return Some((pointer, usize::try_from(offset).ok()?));
+}
+
+
+pub fn get_constant_string(
+    module: &InkwellModule,
+    pointer: &BasicValueEnum,
+    length: &BasicValueEnum,
+    solver: &Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<String> {
+    //! String a constant `&str` (pointer and length) points to in an immutable global (ex: the message of `expect`)
+    if !length.is_int_value() {
+        return None;
+    }
+    let length = usize::try_from(length.into_int_value().get_zero_extended_constant()?).ok()?;
+    let (global, offset) = get_global_pointer(module, pointer, solver, analysis, namespace)?;
+    if !global.is_constant() {
+        return None;
+    }
+    let offset = usize::try_from(offset.simplify().as_u64()?).ok()?;
+    let initializer = get_global_initializer(&get_target_data(module), &global)?;
+    let bytes = initializer.bytes.get(offset..offset + length)?.iter().copied().collect::<Option<Vec<u8>>>()?;
+    return String::from_utf8(bytes).ok();
+}
@@ -0,0 +1,442 @@
+use inkwell::module::{Module as InkwellModule};
+use inkwell::values::{BasicValueEnum, FunctionValue, InstructionOpcode, InstructionValue};
+
+use z3::Solver;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::analysis_context::{AnalysisContext, PanicSite};
+use crate::codegen::codegen_global::get_pointer_width;
+use crate::codegen::codegen_undefined_behaviour::{codegen_panic_site, codegen_undefined_behaviour, get_address_var_name, get_max_address, get_pointer_address};
+use crate::control_flow_graph::FunctionGraph;
+use crate::panics::{get_default_panic_message, get_path_without_generics};
+use crate::soundness::SoundnessImpact;
+use crate::utils::function_utils::{get_call_argument_count, get_function_path};
+use crate::utils::var_utils::{get_min_max_signed_int_of_width, get_unsigned_value, get_var_name};
+
+
+/// Functions returning a fresh allocation, with a `(size, align)` layout as their first arguments
+const ALLOCATION_PATHS: &[&str] = &["__rust_alloc", "__rust_alloc_zeroed", "alloc::alloc::alloc", "alloc::alloc::alloc_zeroed", "alloc::alloc::exchange_malloc"];
+
+/// Functions freeing the allocation given as their first argument, followed by its `(size, align)` layout
+const DEALLOCATION_PATHS: &[&str] = &["__rust_dealloc", "alloc::alloc::dealloc"];
+
+/// Functions moving an allocation to a fresh one, with the `(ptr, size, align, new_size)` arguments
+const REALLOCATION_PATHS: &[&str] = &["__rust_realloc", "alloc::alloc::realloc"];
+
+/// Upper bound on the length of the vectors whose length is not known (ex: parameters)
+pub const MAX_VEC_LENGTH: u64 = 16;
+
+/// Function reached by an out of bounds index, which gives the panic message
+const BOUNDS_CHECK_PANIC_PATH: &str = "core::panicking::panic_bounds_check";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapFunction {
+    Allocation,
+    Deallocation,
+    Reallocation,
+}
+
+
+/// Methods of `Vec<T>` encoded over the length and capacity of the vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VecMethod {
+    New,
+    WithCapacity,
+    Push,
+    Clear,
+    Len,
+    Capacity,
+    IsEmpty,
+    /// `v[i]` with a `usize` index (ranges are not modelled)
+    Index,
+}
+
+impl VecMethod {
+    fn is_mutation(&self) -> bool {
+        return matches!(self, VecMethod::New | VecMethod::WithCapacity | VecMethod::Push | VecMethod::Clear);
+    }
+}
+
+
+pub fn get_heap_function(function_path: &str) -> Option<HeapFunction> {
+    if ALLOCATION_PATHS.contains(&function_path) {
+        return Some(HeapFunction::Allocation);
+    }
+    if DEALLOCATION_PATHS.contains(&function_path) {
+        return Some(HeapFunction::Deallocation);
+    }
+    if REALLOCATION_PATHS.contains(&function_path) {
+        return Some(HeapFunction::Reallocation);
+    }
+    return None;
+}
+
+
+fn get_called_path(instruction: &InstructionValue) -> Option<String> {
+    if !matches!(instruction.get_opcode(), InstructionOpcode::Call | InstructionOpcode::Invoke) {
+        return None;
+    }
+    let called_value = instruction.get_operand(instruction.get_num_operands() - 1)?.left()?;
+    if !called_value.is_pointer_value() || !called_value.into_pointer_value().is_const() {
+        return None;
+    }
+    return Some(get_function_path(&called_value.into_pointer_value()));
+}
+
+
+pub fn get_allocation_size<'a>(instruction: &InstructionValue, solver: &'a Solver, analysis: &AnalysisContext, namespace: &str) -> Option<Int<'a>> {
+    //! Size of the allocation returned by the instruction, None if it is not a call to an allocation function
+    let size_operand_index = match get_called_path(instruction).and_then(|function_path| get_heap_function(&function_path)) {
+        Some(HeapFunction::Allocation) => 0,
+        Some(HeapFunction::Reallocation) => 3,
+        _ => return None,
+    };
+    let size = instruction.get_operand(size_operand_index)?.left()?;
+    return get_unsigned(&size, solver, analysis, namespace);
+}
+
+
+pub fn get_unsigned<'a>(operand: &BasicValueEnum, solver: &'a Solver, analysis: &AnalysisContext, namespace: &str) -> Option<Int<'a>> {
+    //! Unsigned view of an integer operand (ex: a `usize`, as wide as the pointers of the target), which is encoded as a signed integer, None for other operands
+    if !operand.is_int_value() {
+        return None;
+    }
+    let var = Int::new_const(solver.get_context(), get_var_name(operand, solver, analysis, namespace));
+    return Some(get_unsigned_value(solver.get_context(), &var, operand.into_int_value().get_type().get_bit_width()));
+}
+
+
+fn get_freed_pointers<'ctx>(function: &FunctionValue<'ctx>, function_graph: &FunctionGraph, instruction: &InstructionValue) -> Vec<(InstructionValue<'ctx>, BasicValueEnum<'ctx>)> {
+    //! Frees (and reallocations) executed on every path before the instruction, with the pointer they free
+    //! Frees on some of the paths only (ex: in a branch) are not tracked, as the encoding has no path dependent state
+    let mut freed_pointers = Vec::new();
+    for bb in function.get_basic_blocks() {
+        let mut next_instruction = bb.get_first_instruction();
+        while let Some(current_instruction) = next_instruction {
+            next_instruction = current_instruction.get_next_instruction();
+            let is_free = matches!(get_called_path(&current_instruction).and_then(|function_path| get_heap_function(&function_path)), Some(HeapFunction::Deallocation | HeapFunction::Reallocation));
+            if is_free && current_instruction != *instruction && function_graph.dominates(&current_instruction, instruction) {
+                freed_pointers.push((current_instruction, current_instruction.get_operand(0).unwrap().left().unwrap()));
+            }
+        }
+    }
+    return freed_pointers;
+}
+
+
+pub fn get_freed_regions<'a>(
+    module: &InkwellModule,
+    instruction: &InstructionValue,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Vec<(Int<'a>, Int<'a>)> {
+    //! Address and size of the allocations freed before the instruction
+    let mut freed_regions = Vec::new();
+    for (free_instruction, freed_pointer) in get_freed_pointers(function, &analysis.get_function_graph(function), instruction) {
+        let size = free_instruction.get_operand(1).unwrap().left().unwrap();
+        if let (Some(address), Some(size)) = (get_pointer_address(module, &freed_pointer, solver, analysis, namespace), get_unsigned(&size, solver, analysis, namespace)) {
+            freed_regions.push((address, size));
+        }
+    }
+    return freed_regions;
+}
+
+
+fn codegen_allocation<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    function_path: &str,
+    alignment_operand_index: u32
+) -> Bool<'a> {
+    //! The allocation returns a fresh address, aligned and followed by the requested number of bytes
+    let address = Int::new_const(solver.get_context(), get_address_var_name(&get_var_name(instruction, solver, analysis, namespace)));
+    let size = get_allocation_size(instruction, solver, analysis, namespace).unwrap();
+    let alignment_operand = instruction.get_operand(alignment_operand_index).unwrap().left().unwrap();
+    let alignment = Int::new_const(solver.get_context(), get_var_name(&alignment_operand, solver, analysis, namespace));
+    let assignment = Bool::and(solver.get_context(), &[
+        &address.gt(&Int::from_u64(solver.get_context(), 0)),
+        &(&address + &size).le(&get_max_address(module, solver)),
+        &alignment.le(&Int::from_u64(solver.get_context(), 0)).not().implies(&address.modulo(&alignment)._eq(&Int::from_u64(solver.get_context(), 0))),
+    ]);
+    // Distinct allocations are not constrained to be disjoint, which only matters to code comparing their addresses
+    analysis.ledger.record(SoundnessImpact::Assumption, format!("Allocation {:?} is assumed to succeed", function_path));
+    return assignment.implies(&node_var);
+}
+
+
+fn codegen_double_free_check<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! Freeing an allocation that a preceding free already freed is undefined behaviour
+    let pointer = instruction.get_operand(0).unwrap().left().unwrap();
+    let address = match get_pointer_address(module, &pointer, solver, analysis, namespace) {
+        Some(address) => address,
+        None => return node_var,
+    };
+    let mut is_freed = Vec::<Bool>::new();
+    for (_, freed_pointer) in get_freed_pointers(function, &analysis.get_function_graph(function), instruction) {
+        if let Some(freed_address) = get_pointer_address(module, &freed_pointer, solver, analysis, namespace) {
+            is_freed.push(address._eq(&freed_address));
+        }
+    }
+    let is_double_free = Bool::or(solver.get_context(), &is_freed.iter().collect::<Vec<&Bool>>());
+    return codegen_undefined_behaviour(node_var, &is_double_free, instruction, String::from("double free"), solver, analysis, panic_target_node);
+}
+
+
+pub fn codegen_heap_call<'a>(
+    module: &InkwellModule,
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    heap_function: HeapFunction,
+    function_path: &str
+) -> Bool<'a> {
+    //! Allocator calls do not panic, the UB checker gives allocations their addresses and checks frees
+    if !analysis.options.check_undefined_behaviour {
+        return node_var;
+    }
+    let function = instruction.get_parent().and_then(|bb| bb.get_parent()).unwrap();
+    match heap_function {
+        HeapFunction::Allocation => {
+            node_var = codegen_allocation(module, node_var, &instruction, solver, analysis, namespace, function_path, 1);
+        }
+        HeapFunction::Deallocation => {
+            node_var = codegen_double_free_check(module, node_var, &instruction, &function, solver, analysis, namespace, panic_target_node);
+        }
+        HeapFunction::Reallocation => {
+            // The old allocation is freed, its contents are moved to a fresh allocation
+            node_var = codegen_allocation(module, node_var, &instruction, solver, analysis, namespace, function_path, 2);
+            node_var = codegen_double_free_check(module, node_var, &instruction, &function, solver, analysis, namespace, panic_target_node);
+        }
+    }
+    return node_var;
+}
+
+
+pub fn get_vec_method(function_path: &str, instruction: &InstructionValue) -> Option<VecMethod> {
+    //! Modelled method of `Vec<T>` called by the instruction (ex: `alloc::vec::Vec<T,A>::push`)
+    let method = match get_path_without_generics(function_path).as_str() {
+        "alloc::vec::Vec::new" => VecMethod::New,
+        "alloc::vec::Vec::with_capacity" => VecMethod::WithCapacity,
+        "alloc::vec::Vec::push" => VecMethod::Push,
+        "alloc::vec::Vec::clear" => VecMethod::Clear,
+        "alloc::vec::Vec::len" => VecMethod::Len,
+        "alloc::vec::Vec::capacity" => VecMethod::Capacity,
+        "alloc::vec::Vec::is_empty" => VecMethod::IsEmpty,
+        _ if function_path.starts_with("<alloc::vec::Vec<") && (function_path.ends_with(">::index") || function_path.ends_with(">::index_mut")) => {
+            // A `usize` index is passed alone (with the location of the call) and returns a reference to the element
+            let index = instruction.get_operand(1)?.left()?;
+            if get_call_argument_count(instruction) != 3 || !index.is_int_value() || !instruction.get_type().is_pointer_type() {
+                return None;
+            }
+            VecMethod::Index
+        }
+        _ => return None,
+    };
+    return Some(method);
+}
+
+
+fn get_vec_object<'ctx>(pointer: BasicValueEnum<'ctx>) -> BasicValueEnum<'ctx> {
+    //! Vector a pointer points to, identified by the pointer with its casts removed (ex: the stack slot of a local vector)
+    if pointer.is_pointer_value() {
+        if let Some(instruction) = pointer.into_pointer_value().as_instruction() {
+            if instruction.get_opcode() == InstructionOpcode::BitCast {
+                return get_vec_object(instruction.get_operand(0).unwrap().left().unwrap());
+            }
+        }
+    }
+    return pointer;
+}
+
+
+fn get_instruction_index(function: &FunctionValue, instruction: &InstructionValue) -> usize {
+    //! Position of the instruction in the function, which names the states of the vectors it changes
+    let mut index = 0;
+    for bb in function.get_basic_blocks() {
+        let mut next_instruction = bb.get_first_instruction();
+        while let Some(current_instruction) = next_instruction {
+            if current_instruction == *instruction {
+                return index;
+            }
+            index += 1;
+            next_instruction = current_instruction.get_next_instruction();
+        }
+    }
+    return index;
+}
+
+
+fn get_vec_mutations<'ctx>(function: &FunctionValue<'ctx>, object: &BasicValueEnum) -> Vec<(InstructionValue<'ctx>, bool)> {
+    //! Calls that may change the vector, and whether they are modelled (any other call given the vector may change it)
+    let mut mutations = Vec::new();
+    for bb in function.get_basic_blocks() {
+        let mut next_instruction = bb.get_first_instruction();
+        while let Some(instruction) = next_instruction {
+            next_instruction = instruction.get_next_instruction();
+            let function_path = match get_called_path(&instruction) {
+                Some(function_path) => function_path,
+                None => continue,
+            };
+            if function_path.starts_with("llvm.lifetime.") || function_path.starts_with("llvm.dbg.") {
+                continue;
+            }
+            let is_given_vector = (0..get_call_argument_count(&instruction))
+                .filter_map(|operand_index| instruction.get_operand(operand_index).and_then(|operand| operand.left()))
+                .any(|operand| get_vec_object(operand) == *object);
+            if !is_given_vector {
+                continue;
+            }
+            match get_vec_method(&function_path, &instruction) {
+                Some(method) if method.is_mutation() => mutations.push((instruction, true)),
+                Some(..) => {}
+                None => mutations.push((instruction, false)),
+            }
+        }
+    }
+    return mutations;
+}
+
+
+fn get_vec_state<'a>(module: &InkwellModule, solver: &'a Solver, analysis: &AnalysisContext, object_name: &str, site: &str, is_defined: bool) -> (Int<'a>, Int<'a>) {
+    //! Length and capacity of the vector after the given site, bounded unless they are defined by a modelled method
+    let length = Int::new_const(solver.get_context(), format!("{}.vec.len.{}", object_name, site));
+    let capacity = Int::new_const(solver.get_context(), format!("{}.vec.capacity.{}", object_name, site));
+    if !is_defined {
+        solver.assert(&Bool::and(solver.get_context(), &[
+            &length.ge(&Int::from_u64(solver.get_context(), 0)),
+            &length.le(&Int::from_u64(solver.get_context(), MAX_VEC_LENGTH)),
+            &capacity.ge(&length),
+            &capacity.le(&Int::from_i64(solver.get_context(), get_min_max_signed_int_of_width(get_pointer_width(module)).1)),
+        ]));
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("Length of vector {} is bounded to {} elements", object_name, MAX_VEC_LENGTH));
+    }
+    return (length, capacity);
+}
+
+
+fn get_vec_state_before<'a>(
+    module: &InkwellModule,
+    function: &FunctionValue,
+    instruction: &InstructionValue,
+    object: &BasicValueEnum,
+    object_name: &str,
+    solver: &'a Solver,
+    analysis: &AnalysisContext
+) -> (Int<'a>, Int<'a>) {
+    //! State left by the last call changing the vector, if it precedes the instruction on every path
+    //! Otherwise the state depends on the path taken, and is over-approximated by any bounded state
+    let function_graph = analysis.get_function_graph(function);
+    let mutations: Vec<(InstructionValue, bool)> = get_vec_mutations(function, object).into_iter()
+        .filter(|(mutation, _)| mutation != instruction && function_graph.may_precede(mutation, instruction))
+        .collect();
+    if mutations.is_empty() {
+        return get_vec_state(module, solver, analysis, object_name, "entry", false);
+    }
+    let last_mutation = mutations.iter().find(|(mutation, _)| {
+        function_graph.dominates(mutation, instruction) && mutations.iter().all(|(other, _)| other == mutation || !function_graph.may_precede(mutation, other))
+    });
+    return match last_mutation {
+        Some((mutation, is_modelled)) => get_vec_state(module, solver, analysis, object_name, &get_instruction_index(function, mutation).to_string(), *is_modelled),
+        None => {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Vector {} is changed on some paths only, its length is havocked", object_name));
+            get_vec_state(module, solver, analysis, object_name, &format!("merge.{}", get_instruction_index(function, instruction)), false)
+        }
+    };
+}
+
+
+pub fn codegen_vec_call<'a>(
+    module: &InkwellModule,
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    method: VecMethod
+) -> Bool<'a> {
+    //! Vectors are encoded by their length and capacity, a new state being defined by every call changing them
+    //! The vector is the first argument (`self`, or the return slot of constructors), its elements are not tracked
+    let function = instruction.get_parent().and_then(|bb| bb.get_parent()).unwrap();
+    let object = get_vec_object(instruction.get_operand(0).unwrap().left().unwrap());
+    let object_name = get_var_name(&object, solver, analysis, namespace);
+    let (length, capacity) = get_vec_state_before(module, &function, &instruction, &object, &object_name, solver, analysis);
+    let site = get_instruction_index(&function, &instruction).to_string();
+    let get_new_state = || get_vec_state(module, solver, analysis, &object_name, &site, true);
+    let zero = Int::from_u64(solver.get_context(), 0);
+
+    let assignment = match method {
+        VecMethod::New => {
+            let (new_length, new_capacity) = get_new_state();
+            Bool::and(solver.get_context(), &[&new_length._eq(&zero), &new_capacity._eq(&zero)])
+        }
+        VecMethod::WithCapacity => {
+            let (new_length, new_capacity) = get_new_state();
+            let requested_capacity = instruction.get_operand(1).unwrap().left().unwrap();
+            match get_unsigned(&requested_capacity, solver, analysis, namespace) {
+                Some(requested_capacity) => Bool::and(solver.get_context(), &[&new_length._eq(&zero), &new_capacity.ge(&requested_capacity)]),
+                None => {
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Requested capacity of vector {} is not an integer", object_name));
+                    new_length._eq(&zero)
+                }
+            }
+        }
+        VecMethod::Push => {
+            // A full vector grows to an unspecified capacity
+            let (new_length, new_capacity) = get_new_state();
+            let pushed_length = &length + &Int::from_u64(solver.get_context(), 1);
+            let grown_capacity = length.lt(&capacity).ite(&new_capacity._eq(&capacity), &new_capacity.ge(&pushed_length));
+            Bool::and(solver.get_context(), &[&new_length._eq(&pushed_length), &grown_capacity])
+        }
+        VecMethod::Clear => {
+            let (new_length, new_capacity) = get_new_state();
+            Bool::and(solver.get_context(), &[&new_length._eq(&zero), &new_capacity._eq(&capacity)])
+        }
+        VecMethod::Len => Int::new_const(solver.get_context(), get_var_name(&instruction, solver, analysis, namespace))._eq(&length),
+        VecMethod::Capacity => Int::new_const(solver.get_context(), get_var_name(&instruction, solver, analysis, namespace))._eq(&capacity),
+        VecMethod::IsEmpty => Bool::new_const(solver.get_context(), get_var_name(&instruction, solver, analysis, namespace))._eq(&length._eq(&zero)),
+        VecMethod::Index => {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Elements of vector {} are not tracked", object_name));
+            let index = instruction.get_operand(1).unwrap().left().unwrap();
+            let index = match get_unsigned(&index, solver, analysis, namespace) {
+                Some(index) => index,
+                None => {
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Index into vector {} is not an integer", object_name));
+                    return node_var;
+                }
+            };
+            let panic_site = PanicSite {
+                function_path: String::from(BOUNDS_CHECK_PANIC_PATH),
+                message: get_default_panic_message(BOUNDS_CHECK_PANIC_PATH).map(String::from),
+                is_undefined_behaviour: false,
+            };
+            return codegen_panic_site(node_var, &index.ge(&length), panic_site, solver, analysis, panic_target_node);
+        }
+    };
+    node_var = assignment.implies(&node_var);
+    return node_var;
+}
+
+
+pub fn is_allocation_call(instruction: &InstructionValue) -> bool {
+    return matches!(get_called_path(instruction).and_then(|function_path| get_heap_function(&function_path)), Some(HeapFunction::Allocation | HeapFunction::Reallocation));
+}
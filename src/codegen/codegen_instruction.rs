@@ -1,20 +1,48 @@
-use tracing::{warn};
-
 use inkwell::module::{Module as InkwellModule};
 use inkwell::IntPredicate;
-use inkwell::values::{FunctionValue, InstructionOpcode, InstructionValue, PhiValue};
+use inkwell::values::{AnyValue, FunctionValue, InstructionOpcode, InstructionValue, PhiValue};
 
 use z3::Solver;
 use z3::ast::{Ast, Bool, Int};
 
+use crate::analysis_context::AnalysisContext;
 use crate::codegen::codegen_basic_block::get_entry_condition;
 use crate::codegen::codegen_call::{codegen_call};
-use crate::utils::var_utils::get_var_name;
+use crate::codegen::codegen_global::{codegen_global_load, get_global_instruction_pointer, get_global_pointer};
+use crate::codegen::codegen_intrinsic::codegen_binary_arithmetic;
+use crate::codegen::codegen_str::{codegen_byte_load, codegen_byte_pointer, get_byte_pointer_assignment, is_byte_pointer, is_byte_sequence_type};
+use crate::control_flow_graph::get_invoke_destinations;
+use crate::soundness::SoundnessImpact;
+use crate::utils::var_utils::{get_lane_name, get_unsigned_value, get_var_name, is_function_pointer_type};
 
 
 fn get_field_to_extract(instruction: &InstructionValue) -> String {
-    let instruction_string = instruction.to_string();
-    return String::from(&instruction_string[instruction_string.rfind(" ").unwrap()+1..instruction_string.rfind("\"").unwrap()]);
+    //! Indices of the extracted field, joined by dots for nested aggregates (ex: `extractvalue { i8, { i32, i1 } } %a, 1, 0` -> `1.0`)
+    let instruction_llvm_string = instruction.print_to_string();
+    let instruction_str = instruction_llvm_string.to_str().unwrap();
+    // The indices are the trailing integer literals, followed by the metadata attachments (ex: `, !dbg !42`)
+    let instruction_str = &instruction_str[..instruction_str.find(", !").unwrap_or(instruction_str.len())];
+    let mut indices = Vec::<&str>::new();
+    for token in instruction_str.rsplit(", ") {
+        if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+        indices.insert(0, token);
+    }
+    return indices.join(".");
+}
+
+
+fn is_vector_opcode(opcode: &InstructionOpcode) -> bool {
+    //! Opcodes which support a vector result (arithmetic and insertions are encoded lane by lane, calls by their callee)
+    return matches!(opcode, InstructionOpcode::Add | InstructionOpcode::Sub | InstructionOpcode::Mul | InstructionOpcode::InsertElement | InstructionOpcode::Call | InstructionOpcode::Invoke);
+}
+
+
+fn get_constant_index(instruction: &InstructionValue, operand_index: u32) -> Option<u32> {
+    //! Value of a constant index operand (ex: the lane of `extractelement`)
+    let index = instruction.get_operand(operand_index).unwrap().left().unwrap().into_int_value();
+    return index.get_zero_extended_constant().and_then(|index| u32::try_from(index).ok());
 }
 
 
@@ -25,25 +53,46 @@ pub fn codegen_instruction<'a>(
     instruction: InstructionValue,
     function: &'a FunctionValue,
     solver: &'a Solver,
+    analysis: &AnalysisContext,
     namespace: &'a str,
     call_stack: &str,
+    panic_target_node: &str,
     return_register: &str
 ) -> Bool<'a> {
     let opcode = instruction.get_opcode();
+    if instruction.get_type().is_vector_type() && !is_vector_opcode(&opcode) {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Opcode {:?} is not supported on vectors", opcode));
+        return node_var;
+    }
     match &opcode {
         InstructionOpcode::Unreachable => {
             // NO-OP
         }
         InstructionOpcode::Call => {
             // Code gen function with return to POST_NODE and request to assign return value to new return register
-            node_var = codegen_call(module, node_var, instruction, solver, namespace, call_stack);
+            node_var = codegen_call(module, node_var, instruction, solver, analysis, namespace, call_stack, panic_target_node);
+        }
+        InstructionOpcode::Invoke => {
+            // Panics of the invoked function unwind to the cleanup of the unwind destination instead of the panic target node
+            let (_, unwind_destination) = get_invoke_destinations(&instruction, namespace).unwrap();
+            node_var = codegen_call(module, node_var, instruction, solver, analysis, namespace, call_stack, &unwind_destination);
+        }
+        InstructionOpcode::LandingPad => {
+            // NO-OP: the exception is only passed on by resume
+        }
+        InstructionOpcode::Resume => {
+            // NO-OP
+        }
+        InstructionOpcode::IndirectBr => {
+            // Every destination of the indirect branch is a successor, whatever the address branched to
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Destination of indirect branch {:?} is unconstrained", instruction.print_to_string().to_string()));
         }
         InstructionOpcode::Return => {
             if instruction.get_num_operands() == 0 {
                 // NO-OP
             } else if instruction.get_num_operands() == 1 {    
                 let operand = instruction.get_operand(0).unwrap().left().unwrap();        
-                let rvalue_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), solver, namespace);
+                let rvalue_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), solver, analysis, namespace);
                 if operand.get_type().to_string().eq("\"i1\"") {
                     let lvalue_var = Bool::new_const(
                         solver.get_context(),
@@ -67,10 +116,10 @@ pub fn codegen_instruction<'a>(
                     let assignment = lvalue_var._eq(&rvalue_var);
                     node_var = assignment.implies(&node_var);
                 } else {
-                    warn!("Currently unsupported type {:?} for return {:?}", operand.get_type().to_string(), instruction);
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for return {:?}", operand.get_type().to_string(), instruction));
                 }
             } else {
-                warn!("Currently unsupported number of operands {:?} for return {:?}", instruction.get_num_operands(), instruction);
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported number of operands {:?} for return {:?}", instruction.get_num_operands(), instruction));
             }
         }
         InstructionOpcode::Switch => {
@@ -79,11 +128,17 @@ pub fn codegen_instruction<'a>(
         InstructionOpcode::Load => {
             // TODO: Support non-int types here
             let operand = instruction.get_operand(0).unwrap().left().unwrap();
+            if let Some((global, offset)) = get_global_pointer(module, &operand, solver, analysis, namespace) {
+                return codegen_global_load(module, node_var, instruction, global, offset, solver, analysis, namespace);
+            }
+            if is_byte_pointer(&operand) && instruction.get_type().to_string().eq("\"i8\"") {
+                return codegen_byte_load(node_var, &instruction, solver, analysis, namespace);
+            }
             if !instruction.get_type().is_int_type() {
-                warn!("Currently unsupported type {:?} for load operand", instruction.get_type().to_string())
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for load operand", instruction.get_type().to_string()));
             }
-            let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
-            let rvalue_var_name = get_var_name(&operand, &solver, namespace);
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+            let rvalue_var_name = get_var_name(&operand, &solver, analysis, namespace);
             let lvalue_var = Int::new_const(
                 solver.get_context(),
                 lvalue_var_name
@@ -99,12 +154,17 @@ pub fn codegen_instruction<'a>(
             // TODO: Support non-int types here
             let operand1 = instruction.get_operand(0).unwrap().left().unwrap();
             if !operand1.get_type().is_int_type() {
-                warn!("Currently unsupported type {:?} for store operand", operand1.get_type().to_string())
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for store operand", operand1.get_type().to_string()));
             }
-            let operand2 = instruction.get_operand(1).unwrap().left().unwrap().into_pointer_value();
-            
-            let lvalue_var_name = get_var_name(&operand1, &solver, namespace);
-            let rvalue_var_name = get_var_name(&operand2, &solver, namespace);
+            let operand2 = instruction.get_operand(1).unwrap().left().unwrap();
+            if get_global_pointer(module, &operand2, solver, analysis, namespace).is_some() {
+                // Stores to a `static mut` are read back by the loads following them in the block, see `codegen_global_load`
+                return node_var;
+            }
+            let operand2 = operand2.into_pointer_value();
+
+            let lvalue_var_name = get_var_name(&operand1, &solver, analysis, namespace);
+            let rvalue_var_name = get_var_name(&operand2, &solver, analysis, namespace);
             let lvalue_var = Int::new_const(
                 solver.get_context(),
                 lvalue_var_name
@@ -120,10 +180,10 @@ pub fn codegen_instruction<'a>(
             // NO-OP
         }
         InstructionOpcode::Xor => {
-            let operand1_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, namespace);
-            let operand2_var_name = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, namespace);
+            let operand1_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let operand2_var_name = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, analysis, namespace);
             if !instruction.get_type().to_string().eq("\"i1\"") {
-                warn!("Currently unsupported type {:?} for xor operand", instruction.get_type().to_string());
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for xor operand", instruction.get_type().to_string()));
             }
             let operand1_var = Bool::new_const(
                 solver.get_context(),
@@ -134,7 +194,7 @@ pub fn codegen_instruction<'a>(
                 operand2_var_name
             );
             let rvalue_var = operand1_var.xor(&operand2_var);
-            let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
             let lvalue_var = Bool::new_const(
                 solver.get_context(),
                 lvalue_var_name
@@ -143,56 +203,38 @@ pub fn codegen_instruction<'a>(
             node_var = assignment.implies(&node_var);
         }
         InstructionOpcode::ICmp => {
-            let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
             let lvalue_var = Bool::new_const(solver.get_context(), lvalue_var_name);
-            let operand1 = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, namespace);
-            let operand2 = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, namespace);
-            let rvalue_operation;
-            
+            let operand1 = instruction.get_operand(0).unwrap().left().unwrap();
+            let operand2 = instruction.get_operand(1).unwrap().left().unwrap();
+            let mut lhs = Int::new_const(solver.get_context(), get_var_name(&operand1, &solver, analysis, namespace));
+            let mut rhs = Int::new_const(solver.get_context(), get_var_name(&operand2, &solver, analysis, namespace));
 
             // Split by the sub-instruction (denoting the type of comparison)
-            // TODO: can signed & unsigned comparisons be combined?
+            // Unsigned comparisons compare the unsigned values of the operands (ex: `-1 as u32` is above `0`)
             let icmp_type = instruction.get_icmp_predicate().unwrap();
-            match &icmp_type {
-                IntPredicate::EQ => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1)._eq(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    );
-                }
-                IntPredicate::NE => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1)._eq(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    ).not();
-                }
-                IntPredicate::SGE | IntPredicate::UGE => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1).ge(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    );
-                }
-                IntPredicate::SGT | IntPredicate::UGT => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1).gt(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    );
-                }
-                IntPredicate::SLE | IntPredicate::ULE => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1).le(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    );
-                }
-                IntPredicate::SLT | IntPredicate::ULT => {
-                    rvalue_operation = Int::new_const(&solver.get_context(), operand1).lt(
-                        &Int::new_const(&solver.get_context(), operand2)
-                    );
-                }
+            let is_unsigned = matches!(icmp_type, IntPredicate::UGE | IntPredicate::UGT | IntPredicate::ULE | IntPredicate::ULT);
+            if is_unsigned && operand1.get_type().is_int_type() {
+                let bit_width = operand1.get_type().into_int_type().get_bit_width();
+                lhs = get_unsigned_value(solver.get_context(), &lhs, bit_width);
+                rhs = get_unsigned_value(solver.get_context(), &rhs, bit_width);
             }
+            let rvalue_operation = match &icmp_type {
+                IntPredicate::EQ => lhs._eq(&rhs),
+                IntPredicate::NE => lhs._eq(&rhs).not(),
+                IntPredicate::SGE | IntPredicate::UGE => lhs.ge(&rhs),
+                IntPredicate::SGT | IntPredicate::UGT => lhs.gt(&rhs),
+                IntPredicate::SLE | IntPredicate::ULE => lhs.le(&rhs),
+                IntPredicate::SLT | IntPredicate::ULT => lhs.lt(&rhs),
+            };
 
             let assignment = lvalue_var._eq(&rvalue_operation);
             node_var = assignment.implies(&node_var);
         }
         InstructionOpcode::ExtractValue => {
-            let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
             let operand = instruction.get_operand(0).unwrap().left().unwrap();
-            let rvalue_var_name = format!("{}.{}", get_var_name(&operand, &solver, namespace), get_field_to_extract(&instruction));
+            let rvalue_var_name = format!("{}.{}", get_var_name(&operand, &solver, analysis, namespace), get_field_to_extract(&instruction));
             if instruction.get_type().to_string().eq("\"i1\"") {
                 let lvalue_var = Bool::new_const(
                     solver.get_context(),
@@ -215,22 +257,34 @@ pub fn codegen_instruction<'a>(
                 );
                 let assignment = lvalue_var._eq(&rvalue_var);
                 node_var = assignment.implies(&node_var);     
+            } else if is_byte_sequence_type(&instruction.get_type()) {
+                // Slices returned by the modelled methods of `str` point into the bytes of the string
+                let assignment = get_byte_pointer_assignment(solver, &lvalue_var_name, &rvalue_var_name, &Int::from_u64(solver.get_context(), 0));
+                node_var = assignment.implies(&node_var);
             }  else {
-                warn!("Currently unsupported type {:?} for extract value", operand.get_type().to_string())
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for extract value", operand.get_type().to_string()));
             } 
         }
         InstructionOpcode::Alloca => {
             // NO-OP
         }
+        InstructionOpcode::GetElementPtr | InstructionOpcode::BitCast => {
+            // Addresses into globals are resolved by the loads reading through them
+            if is_byte_pointer(&instruction.get_operand(0).unwrap().left().unwrap()) {
+                node_var = codegen_byte_pointer(node_var, &instruction, solver, analysis, namespace);
+            } else if get_global_instruction_pointer(module, &instruction, solver, analysis, namespace).is_none() {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Opcode {:?} is not supported as a statement for code gen", opcode));
+            }
+        }
         InstructionOpcode::Phi => {
             let phi_instruction: PhiValue = instruction.try_into().unwrap();
             let mut assignment = Bool::from_bool(solver.get_context(), true);
             for incoming_index in 0..phi_instruction.count_incoming() {
                 let incoming = phi_instruction.get_incoming(incoming_index).unwrap();
                 let predecessor = String::from(format!("{}{}", namespace, incoming.1.get_name().to_str().unwrap()));
-                let phi_condition = get_entry_condition(&solver, &function, &predecessor, &node, namespace);
-                let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
-                let rvalue_var_name = get_var_name(&incoming.0, &solver, namespace);
+                let phi_condition = get_entry_condition(&solver, analysis, &function, &predecessor, &node, namespace);
+                let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+                let rvalue_var_name = get_var_name(&incoming.0, &solver, analysis, namespace);
                 if instruction.get_type().to_string().eq("\"i1\"") {
                     let lvalue_var = Bool::new_const(
                         solver.get_context(),
@@ -241,7 +295,7 @@ pub fn codegen_instruction<'a>(
                         rvalue_var_name
                     );
                     assignment = Bool::and(&solver.get_context(), &[&assignment, &phi_condition.implies(&lvalue_var._eq(&rvalue_var))]);
-                } else if instruction.get_type().is_int_type() {
+                } else if instruction.get_type().is_int_type() || is_function_pointer_type(&instruction.get_type()) {
                     let lvalue_var = Int::new_const(
                         solver.get_context(),
                         lvalue_var_name
@@ -252,15 +306,15 @@ pub fn codegen_instruction<'a>(
                     );
                     assignment = Bool::and(&solver.get_context(), &[&assignment, &phi_condition.implies(&lvalue_var._eq(&rvalue_var))]);
                 } else {
-                    warn!("Currently unsupported type {:?} for Phi", incoming.0.get_type().to_string());
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for Phi", incoming.0.get_type().to_string()));
                 }
             }
             node_var = assignment.implies(&node_var);
         }
         InstructionOpcode::Trunc => {
             if instruction.get_type().to_string().eq("\"i1\"") {
-                let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
-                let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, namespace);
+                let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+                let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
                 let lvalue_var = Bool::new_const(
                     solver.get_context(),
                     lvalue_var_name
@@ -275,16 +329,16 @@ pub fn codegen_instruction<'a>(
                 let assignment = lvalue_var._eq(&right_most_bit._eq(&const_1));
                 node_var = assignment.implies(&node_var);
             } else {
-                warn!("Type {} is not a supported target type for the Trunc instruction!", instruction.get_type().to_string());
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Type {} is not a supported target type for the Trunc instruction!", instruction.get_type().to_string()));
             }
         }
         InstructionOpcode::Select => {
             let discriminant = instruction.get_operand(0).unwrap().left().unwrap();
-            let discriminant_name = get_var_name(&discriminant, &solver, namespace);
-            let operand_1_var_name = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, namespace);
-            let operand_2_var_name = get_var_name(&instruction.get_operand(2).unwrap().left().unwrap(), &solver, namespace);
+            let discriminant_name = get_var_name(&discriminant, &solver, analysis, namespace);
+            let operand_1_var_name = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let operand_2_var_name = get_var_name(&instruction.get_operand(2).unwrap().left().unwrap(), &solver, analysis, namespace);
             if !discriminant.get_type().to_string().eq("\"i1\"") {
-                warn!("Currently unsupported type {:?} for select discriminant", discriminant.get_type().to_string());
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for select discriminant", discriminant.get_type().to_string()));
             }
             let discriminant_var = Bool::new_const(
                 solver.get_context(),
@@ -299,10 +353,10 @@ pub fn codegen_instruction<'a>(
                     solver.get_context(),
                     operand_2_var_name
                 );                            
-                let select_1 = discriminant_var.implies(&Bool::new_const(solver.get_context(), get_var_name(&instruction, &solver, namespace))._eq(&operand_1_var));
-                let select_2 = discriminant_var.not().implies(&Bool::new_const(solver.get_context(), get_var_name(&instruction, &solver, namespace))._eq(&operand_2_var));
+                let select_1 = discriminant_var.implies(&Bool::new_const(solver.get_context(), get_var_name(&instruction, &solver, analysis, namespace))._eq(&operand_1_var));
+                let select_2 = discriminant_var.not().implies(&Bool::new_const(solver.get_context(), get_var_name(&instruction, &solver, analysis, namespace))._eq(&operand_2_var));
                 node_var = Bool::and(solver.get_context(), &[&select_1.implies(&node_var), &select_2.implies(&node_var)]);
-            } else if instruction.get_type().is_int_type() {
+            } else if instruction.get_type().is_int_type() || is_function_pointer_type(&instruction.get_type()) {
                 let operand_1_var = Int::new_const(
                     solver.get_context(),
                     operand_1_var_name
@@ -311,18 +365,77 @@ pub fn codegen_instruction<'a>(
                     solver.get_context(),
                     operand_2_var_name
                 );                            
-                let select_1 = discriminant_var.implies(&Int::new_const(solver.get_context(), get_var_name(&instruction, &solver, namespace))._eq(&operand_1_var));
-                let select_2 = discriminant_var.not().implies(&Int::new_const(solver.get_context(), get_var_name(&instruction, &solver, namespace))._eq(&operand_2_var));
+                let select_1 = discriminant_var.implies(&Int::new_const(solver.get_context(), get_var_name(&instruction, &solver, analysis, namespace))._eq(&operand_1_var));
+                let select_2 = discriminant_var.not().implies(&Int::new_const(solver.get_context(), get_var_name(&instruction, &solver, analysis, namespace))._eq(&operand_2_var));
                 let assignment = Bool::and(solver.get_context(), &[&select_1, &select_2]);
                 node_var = assignment.implies(&node_var);
             } else {
-                warn!("Currently unsupported type {:?} for select", instruction.get_type().to_string());
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for select", instruction.get_type().to_string()));
+            }
+        }
+        InstructionOpcode::Add | InstructionOpcode::Sub | InstructionOpcode::Mul => {
+            node_var = codegen_binary_arithmetic(node_var, instruction, solver, analysis, namespace, panic_target_node);
+        }
+        InstructionOpcode::Freeze => {
+            // An undef or poison operand already has an arbitrary value, which freeze picks once
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+            let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            if instruction.get_type().to_string().eq("\"i1\"") {
+                let assignment = Bool::new_const(solver.get_context(), lvalue_var_name)._eq(&Bool::new_const(solver.get_context(), operand_var_name));
+                node_var = assignment.implies(&node_var);
+            } else if instruction.get_type().is_int_type() {
+                let assignment = Int::new_const(solver.get_context(), lvalue_var_name)._eq(&Int::new_const(solver.get_context(), operand_var_name));
+                node_var = assignment.implies(&node_var);
+            } else {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for freeze", instruction.get_type().to_string()));
             }
         }
+        InstructionOpcode::ExtractElement => {
+            let vector_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let index = get_constant_index(&instruction, 1);
+            if index.is_none() {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Extracted element with a variable index is unconstrained: {:?}", instruction));
+                return node_var;
+            }
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+            let lane_var_name = get_lane_name(&vector_var_name, index.unwrap());
+            if instruction.get_type().to_string().eq("\"i1\"") {
+                let assignment = Bool::new_const(solver.get_context(), lvalue_var_name)._eq(&Bool::new_const(solver.get_context(), lane_var_name));
+                node_var = assignment.implies(&node_var);
+            } else if instruction.get_type().is_int_type() {
+                let assignment = Int::new_const(solver.get_context(), lvalue_var_name)._eq(&Int::new_const(solver.get_context(), lane_var_name));
+                node_var = assignment.implies(&node_var);
+            } else {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for extractelement", instruction.get_type().to_string()));
+            }
+        }
+        InstructionOpcode::InsertElement => {
+            let vector_type = instruction.get_type().into_vector_type();
+            let element_type = vector_type.get_element_type();
+            let index = get_constant_index(&instruction, 2);
+            if index.is_none() || !element_type.is_int_type() {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Inserted element is not supported: {:?}", instruction));
+                return node_var;
+            }
+            let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+            let vector_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let element_var_name = get_var_name(&instruction.get_operand(1).unwrap().left().unwrap(), &solver, analysis, namespace);
+            let mut lane_assignments = Vec::<Bool>::new();
+            for lane in 0..vector_type.get_size() {
+                let rvalue_var_name = if lane == index.unwrap() { element_var_name.clone() } else { get_lane_name(&vector_var_name, lane) };
+                if element_type.into_int_type().get_bit_width() == 1 {
+                    lane_assignments.push(Bool::new_const(solver.get_context(), get_lane_name(&lvalue_var_name, lane))._eq(&Bool::new_const(solver.get_context(), rvalue_var_name)));
+                } else {
+                    lane_assignments.push(Int::new_const(solver.get_context(), get_lane_name(&lvalue_var_name, lane))._eq(&Int::new_const(solver.get_context(), rvalue_var_name)));
+                }
+            }
+            let assignment = Bool::and(solver.get_context(), &lane_assignments.iter().collect::<Vec<&Bool>>());
+            node_var = assignment.implies(&node_var);
+        }
         InstructionOpcode::ZExt => {
             if instruction.get_operand(0).unwrap().left().unwrap().get_type().to_string().eq("\"i1\"") {
-                let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
-                let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, namespace);
+                let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+                let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, analysis, namespace);
                 let lvalue_var = Int::new_const(
                     solver.get_context(),
                     lvalue_var_name
@@ -338,8 +451,9 @@ pub fn codegen_instruction<'a>(
                 let assignment = Bool::and(solver.get_context(), &[&cast_1, &cast_2]);
                 node_var = assignment.implies(&node_var);
             } else if instruction.get_operand(0).unwrap().left().unwrap().get_type().is_int_type() {
-                let lvalue_var_name = get_var_name(&instruction, &solver, namespace);
-                let operand_var_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), &solver, namespace);
+                let operand = instruction.get_operand(0).unwrap().left().unwrap();
+                let lvalue_var_name = get_var_name(&instruction, &solver, analysis, namespace);
+                let operand_var_name = get_var_name(&operand, &solver, analysis, namespace);
                 let lvalue_var = Int::new_const(
                     solver.get_context(),
                     lvalue_var_name
@@ -348,14 +462,16 @@ pub fn codegen_instruction<'a>(
                     solver.get_context(),
                     operand_var_name
                 );
-                let assignment = lvalue_var._eq(&operand_var);
+                // Negative operands have their sign bit set, which becomes a value bit of the wider integer (ex: `-1i32` is `u32::MAX`)
+                let unsigned_operand = get_unsigned_value(solver.get_context(), &operand_var, operand.into_int_value().get_type().get_bit_width());
+                let assignment = lvalue_var._eq(&unsigned_operand);
                 node_var = assignment.implies(&node_var);
             } else {
-                warn!("Type {} is not a supported target type for the ZExt instruction!", instruction.get_type().to_string());
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Type {} is not a supported target type for the ZExt instruction!", instruction.get_type().to_string()));
             }
         }
         _ => {
-            warn!("Opcode {:?} is not supported as a statement for code gen", opcode);
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Opcode {:?} is not supported as a statement for code gen", opcode));
         }
     }
     return node_var;
@@ -0,0 +1,541 @@
+use inkwell::types::AnyType;
+use inkwell::values::{AnyValue, InstructionOpcode, InstructionValue};
+
+use z3::{Context as Z3Context, Solver};
+use z3::ast::{Ast, Bool, Int, BV};
+
+use crate::analysis_context::AnalysisContext;
+use crate::codegen::codegen_undefined_behaviour::codegen_undefined_behaviour;
+use crate::soundness::SoundnessImpact;
+use crate::utils::function_utils::get_call_argument_count;
+use crate::utils::var_utils::{get_lane_name, get_var_name};
+
+
+pub const CORE_NUM_IMPL_PREFIX: &str = "core::num::<impl ";
+
+
+type IntrinsicEncoder = for<'a> fn(&IntrinsicCall<'a>) -> Bool<'a>;
+
+pub struct IntrinsicModel {
+    /// Intrinsic without its overloaded type suffixes (ex: `llvm.smax`) or helper without its impl type (ex: `core::num::checked_add`)
+    pub name: &'static str,
+    /// Signedness of the integer operands, None if it comes from the impl type of the helper
+    pub is_signed: Option<bool>,
+    /// Whether the model covers every behaviour of the intrinsic, otherwise its uses are recorded as over-approximations
+    pub is_exact: bool,
+    /// Encodes the constraint the call puts on its result
+    pub encode: IntrinsicEncoder,
+}
+
+
+/// To model a new intrinsic, add an entry with a function encoding its constraint on the result
+pub const INTRINSIC_MODELS: &[IntrinsicModel] = &[
+    IntrinsicModel { name: "llvm.sadd.with.overflow", is_signed: Some(true), is_exact: true, encode: encode_add_with_overflow },
+    IntrinsicModel { name: "llvm.uadd.with.overflow", is_signed: Some(false), is_exact: true, encode: encode_add_with_overflow },
+    IntrinsicModel { name: "llvm.ssub.with.overflow", is_signed: Some(true), is_exact: true, encode: encode_sub_with_overflow },
+    IntrinsicModel { name: "llvm.usub.with.overflow", is_signed: Some(false), is_exact: true, encode: encode_sub_with_overflow },
+    IntrinsicModel { name: "llvm.smul.with.overflow", is_signed: Some(true), is_exact: true, encode: encode_mul_with_overflow },
+    IntrinsicModel { name: "llvm.umul.with.overflow", is_signed: Some(false), is_exact: true, encode: encode_mul_with_overflow },
+    IntrinsicModel { name: "llvm.sadd.sat", is_signed: Some(true), is_exact: true, encode: encode_add_sat },
+    IntrinsicModel { name: "llvm.uadd.sat", is_signed: Some(false), is_exact: true, encode: encode_add_sat },
+    IntrinsicModel { name: "llvm.ssub.sat", is_signed: Some(true), is_exact: true, encode: encode_sub_sat },
+    IntrinsicModel { name: "llvm.usub.sat", is_signed: Some(false), is_exact: true, encode: encode_sub_sat },
+    IntrinsicModel { name: "llvm.abs", is_signed: Some(true), is_exact: true, encode: encode_abs },
+    IntrinsicModel { name: "llvm.smax", is_signed: Some(true), is_exact: true, encode: encode_max },
+    IntrinsicModel { name: "llvm.umax", is_signed: Some(false), is_exact: true, encode: encode_max },
+    IntrinsicModel { name: "llvm.smin", is_signed: Some(true), is_exact: true, encode: encode_min },
+    IntrinsicModel { name: "llvm.umin", is_signed: Some(false), is_exact: true, encode: encode_min },
+    IntrinsicModel { name: "llvm.ctpop", is_signed: Some(false), is_exact: true, encode: encode_ctpop },
+    IntrinsicModel { name: "llvm.ctlz", is_signed: Some(false), is_exact: true, encode: encode_ctlz },
+    IntrinsicModel { name: "llvm.cttz", is_signed: Some(false), is_exact: true, encode: encode_cttz },
+    IntrinsicModel { name: "llvm.bswap", is_signed: Some(false), is_exact: true, encode: encode_bswap },
+    IntrinsicModel { name: "llvm.fshl", is_signed: Some(false), is_exact: true, encode: encode_fshl },
+    IntrinsicModel { name: "llvm.fshr", is_signed: Some(false), is_exact: true, encode: encode_fshr },
+    IntrinsicModel { name: "llvm.expect", is_signed: Some(true), is_exact: true, encode: encode_expect },
+    IntrinsicModel { name: "llvm.assume", is_signed: Some(true), is_exact: true, encode: encode_assume },
+    IntrinsicModel { name: "llvm.memcpy", is_signed: Some(false), is_exact: false, encode: encode_memcpy },
+    IntrinsicModel { name: "llvm.memmove", is_signed: Some(false), is_exact: false, encode: encode_memcpy },
+    IntrinsicModel { name: "llvm.memset", is_signed: Some(false), is_exact: false, encode: encode_memset },
+    IntrinsicModel { name: "llvm.lifetime.start", is_signed: Some(false), is_exact: true, encode: encode_no_op },
+    IntrinsicModel { name: "llvm.lifetime.end", is_signed: Some(false), is_exact: true, encode: encode_no_op },
+    IntrinsicModel { name: "core::num::checked_add", is_signed: None, is_exact: true, encode: encode_checked_add },
+    IntrinsicModel { name: "core::num::checked_sub", is_signed: None, is_exact: true, encode: encode_checked_sub },
+    IntrinsicModel { name: "core::num::checked_mul", is_signed: None, is_exact: true, encode: encode_checked_mul },
+    IntrinsicModel { name: "core::num::wrapping_add", is_signed: None, is_exact: true, encode: encode_wrapping_add },
+    IntrinsicModel { name: "core::num::wrapping_sub", is_signed: None, is_exact: true, encode: encode_wrapping_sub },
+    IntrinsicModel { name: "core::num::wrapping_mul", is_signed: None, is_exact: true, encode: encode_wrapping_mul },
+];
+
+
+#[derive(Debug, Clone, Copy)]
+enum ArithmeticOperation {
+    Add,
+    Sub,
+    Mul,
+}
+
+
+/// Operands and result of a modelled call, with integers encoded by the value of their two's complement bits (as everywhere else)
+pub struct IntrinsicCall<'a> {
+    ctx: &'a Z3Context,
+    operand_names: Vec<String>,
+    result_name: String,
+    bit_width: u32,
+    is_signed: bool,
+}
+
+impl<'a> IntrinsicCall<'a> {
+    fn get_int_operand(&self, index: usize) -> Int<'a> {
+        return Int::new_const(self.ctx, self.operand_names[index].as_str());
+    }
+
+    fn get_bool_operand(&self, index: usize) -> Bool<'a> {
+        return Bool::new_const(self.ctx, self.operand_names[index].as_str());
+    }
+
+    fn get_bv_operand(&self, index: usize) -> BV<'a> {
+        return BV::from_int(&self.get_int_operand(index), self.bit_width);
+    }
+
+    fn get_value_operand(&self, index: usize) -> Int<'a> {
+        //! Mathematical value of the operand under the signedness of the call
+        let operand = self.get_int_operand(index);
+        if self.is_signed {
+            return operand;
+        }
+        return self.to_unsigned(&operand);
+    }
+
+    fn get_int_result(&self, field: Option<u32>) -> Int<'a> {
+        return match field {
+            Some(field) => Int::new_const(self.ctx, format!("{}.{}", self.result_name, field)),
+            None => Int::new_const(self.ctx, self.result_name.as_str()),
+        };
+    }
+
+    fn get_bool_result(&self, field: Option<u32>) -> Bool<'a> {
+        return match field {
+            Some(field) => Bool::new_const(self.ctx, format!("{}.{}", self.result_name, field)),
+            None => Bool::new_const(self.ctx, self.result_name.as_str()),
+        };
+    }
+
+    fn get_int(&self, value: i64) -> Int<'a> {
+        return Int::from_i64(self.ctx, value);
+    }
+
+    fn get_modulus(&self) -> Int<'a> {
+        //! 2^bit_width, built from two halves as it does not fit an u64 for 64 bit integers
+        let half_modulus = Int::from_u64(self.ctx, 1u64 << (self.bit_width - 1));
+        return Int::mul(self.ctx, &[&half_modulus, &self.get_int(2)]);
+    }
+
+    fn get_bounds(&self) -> (Int<'a>, Int<'a>) {
+        //! Min and max values representable under the signedness of the call
+        if self.is_signed {
+            return (self.get_int(i64::MIN >> (64 - self.bit_width)), self.get_int(i64::MAX >> (64 - self.bit_width)));
+        }
+        return (self.get_int(0), Int::from_u64(self.ctx, u64::MAX >> (64 - self.bit_width)));
+    }
+
+    fn to_unsigned(&self, value: &Int<'a>) -> Int<'a> {
+        let wrapped_value = Int::add(self.ctx, &[value, &self.get_modulus()]);
+        return value.lt(&self.get_int(0)).ite(&wrapped_value, value);
+    }
+
+    fn to_encoding(&self, value: &Int<'a>) -> Int<'a> {
+        //! Wraps any mathematical value into the two's complement encoding of the bit width
+        let unsigned_value = value.modulo(&self.get_modulus());
+        let signed_max = self.get_int(i64::MAX >> (64 - self.bit_width));
+        let wrapped_value = Int::sub(self.ctx, &[&unsigned_value, &self.get_modulus()]);
+        return unsigned_value.gt(&signed_max).ite(&wrapped_value, &unsigned_value);
+    }
+
+    fn from_bv(&self, value: &BV<'a>) -> Int<'a> {
+        return value.to_int(true);
+    }
+
+    fn get_exact_result(&self, operation: ArithmeticOperation) -> Int<'a> {
+        let operand1 = self.get_value_operand(0);
+        let operand2 = self.get_value_operand(1);
+        return match operation {
+            ArithmeticOperation::Add => Int::add(self.ctx, &[&operand1, &operand2]),
+            ArithmeticOperation::Sub => Int::sub(self.ctx, &[&operand1, &operand2]),
+            ArithmeticOperation::Mul => Int::mul(self.ctx, &[&operand1, &operand2]),
+        };
+    }
+
+    fn is_overflow(&self, exact_result: &Int<'a>) -> Bool<'a> {
+        let (min_int, max_int) = self.get_bounds();
+        return Bool::or(self.ctx, &[&exact_result.gt(&max_int), &exact_result.lt(&min_int)]);
+    }
+
+    fn is_bit_set(&self, value: &BV<'a>, bit: u32) -> Bool<'a> {
+        return value.extract(bit, bit)._eq(&BV::from_u64(self.ctx, 1, 1));
+    }
+}
+
+
+fn is_overloaded_type_suffix(segment: &str) -> bool {
+    //! Integer (ex: `i32`), pointer (ex: `p0i8`) and vector (ex: `v4i32`) suffixes of overloaded intrinsics
+    let mut chars = segment.chars();
+    return match chars.next() {
+        Some('i') | Some('v') => segment.len() > 1 && chars.next().unwrap().is_ascii_digit(),
+        Some('p') => segment.starts_with("p0"),
+        _ => false,
+    };
+}
+
+
+pub fn get_intrinsic_name(function_path: &str) -> String {
+    //! Name under which the called function is modelled (ex: `llvm.smax.i32` -> `llvm.smax`, `core::num::<impl u8>::checked_add` -> `core::num::checked_add`)
+    if function_path.starts_with("llvm.") {
+        let mut segments: Vec<&str> = function_path.split('.').collect();
+        while segments.len() > 2 && is_overloaded_type_suffix(segments.last().unwrap()) {
+            segments.pop();
+        }
+        return segments.join(".");
+    }
+    if function_path.starts_with(CORE_NUM_IMPL_PREFIX) {
+        if let Some(impl_end_index) = function_path.find(">::") {
+            return format!("core::num::{}", &function_path[impl_end_index + ">::".len()..]);
+        }
+    }
+    return String::from(function_path);
+}
+
+
+pub fn get_intrinsic_model(intrinsic_name: &str) -> Option<&'static IntrinsicModel> {
+    return INTRINSIC_MODELS.iter().find(|model| model.name == intrinsic_name);
+}
+
+
+fn get_operand_names(instruction: &InstructionValue, operand_count: u32, solver: &Solver, analysis: &AnalysisContext, namespace: &str) -> (Vec<String>, Option<u32>) {
+    //! Names of the first operands and the bit width of the first integer operand
+    let mut operand_names = Vec::<String>::new();
+    let mut bit_width = None;
+    for i in 0..operand_count {
+        let operand = instruction.get_operand(i).unwrap().left().unwrap();
+        if bit_width.is_none() && operand.get_type().is_int_type() {
+            bit_width = Some(operand.get_type().into_int_type().get_bit_width());
+        }
+        // Constants that cannot be encoded are named (and recorded) as unconstrained by the symbol table
+        operand_names.push(get_var_name(&operand, solver, analysis, namespace));
+    }
+    return (operand_names, bit_width);
+}
+
+
+pub fn codegen_intrinsic<'a>(
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    model: &IntrinsicModel,
+    function_path: &str
+) -> Bool<'a> {
+    //! Encodes a call to a modelled intrinsic or helper, given the path of the called function
+    let (operand_names, bit_width) = get_operand_names(&instruction, get_call_argument_count(&instruction), solver, analysis, namespace);
+    let bit_width = bit_width.unwrap_or(1);
+    if bit_width > 64 {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Call {:?} on {} bit integers is not modelled", function_path, bit_width));
+        return node_var;
+    }
+
+    let is_signed = match model.is_signed {
+        Some(is_signed) => is_signed,
+        None => function_path[CORE_NUM_IMPL_PREFIX.len()..].starts_with('i'),
+    };
+    let mut result_name = String::new();
+    if !instruction.get_type().is_void_type() {
+        result_name = get_var_name(&instruction, solver, analysis, namespace);
+    }
+    let call = IntrinsicCall {
+        ctx: solver.get_context(),
+        operand_names,
+        result_name,
+        bit_width,
+        is_signed,
+    };
+    if !model.is_exact {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Call {:?} is only partially modelled", function_path));
+    }
+
+    let assignment = (model.encode)(&call);
+    node_var = assignment.implies(&node_var);
+    return node_var;
+}
+
+
+pub fn codegen_binary_arithmetic<'a>(
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! Encodes the `add`, `sub` and `mul` instructions (lane by lane for vectors), which wrap on overflow (ex: in release builds)
+    //! An overflow forbidden by the `nsw` or `nuw` flag makes the result poison, the UB checker mode reports it like a panic as undefined behaviour
+    let opcode = instruction.get_opcode();
+    let operation = match opcode {
+        InstructionOpcode::Add => ArithmeticOperation::Add,
+        InstructionOpcode::Sub => ArithmeticOperation::Sub,
+        _ => ArithmeticOperation::Mul,
+    };
+    let description = format!("{:?}", opcode);
+    let (lane_count, element_type) = if instruction.get_type().is_vector_type() {
+        let vector_type = instruction.get_type().into_vector_type();
+        (Some(vector_type.get_size()), vector_type.get_element_type().as_any_type_enum())
+    } else {
+        (None, instruction.get_type())
+    };
+    if !element_type.is_int_type() || element_type.into_int_type().get_bit_width() > 64 {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for {}", instruction.get_type().to_string(), description));
+        return node_var;
+    }
+
+    let instruction_llvm_string = instruction.print_to_string();
+    let instruction_str = instruction_llvm_string.to_str().unwrap();
+    let has_no_signed_wrap = analysis.options.check_undefined_behaviour && instruction_str.contains(" nsw ");
+    let has_no_unsigned_wrap = analysis.options.check_undefined_behaviour && instruction_str.contains(" nuw ");
+
+    let (operand_names, _) = get_operand_names(&instruction, 2, solver, analysis, namespace);
+    let result_name = get_var_name(&instruction, solver, analysis, namespace);
+    let lanes: Vec<Option<u32>> = match lane_count {
+        Some(lane_count) => (0..lane_count).map(|lane| Some(lane)).collect(),
+        None => vec![None],
+    };
+    let mut assignments = Vec::<Bool>::new();
+    let mut undefined_behaviours = Vec::<Bool>::new();
+    for lane in lanes {
+        let get_lane_var_name = |var_name: &String| match lane {
+            Some(lane) => get_lane_name(var_name, lane),
+            None => var_name.clone(),
+        };
+        let mut call = IntrinsicCall {
+            ctx: solver.get_context(),
+            operand_names: operand_names.iter().map(|operand_name| get_lane_var_name(operand_name)).collect(),
+            result_name: get_lane_var_name(&result_name),
+            bit_width: element_type.into_int_type().get_bit_width(),
+            is_signed: true,
+        };
+        assignments.push(encode_wrapping(&call, operation));
+        if has_no_signed_wrap {
+            undefined_behaviours.push(call.is_overflow(&call.get_exact_result(operation)));
+        }
+        if has_no_unsigned_wrap {
+            call.is_signed = false;
+            undefined_behaviours.push(call.is_overflow(&call.get_exact_result(operation)));
+        }
+    }
+
+    let assignment = Bool::and(solver.get_context(), &assignments.iter().collect::<Vec<&Bool>>());
+    node_var = assignment.implies(&node_var);
+    if undefined_behaviours.len() > 0 {
+        let is_undefined_behaviour = Bool::or(solver.get_context(), &undefined_behaviours.iter().collect::<Vec<&Bool>>());
+        let flags = [(has_no_unsigned_wrap, " nuw"), (has_no_signed_wrap, " nsw")].iter().filter(|(has_flag, _)| *has_flag).map(|(_, flag)| *flag).collect::<String>();
+        let description = format!("overflow of `{}{}`", description.to_lowercase(), flags);
+        node_var = codegen_undefined_behaviour(node_var, &is_undefined_behaviour, &instruction, description, solver, analysis, panic_target_node);
+    }
+    return node_var;
+}
+
+
+fn encode_with_overflow<'a>(call: &IntrinsicCall<'a>, operation: ArithmeticOperation) -> Bool<'a> {
+    //! Result is a `{ iN, i1 }` pair of the wrapped result and whether it overflowed
+    let exact_result = call.get_exact_result(operation);
+    let assignment_1 = call.get_int_result(Some(0))._eq(&call.to_encoding(&exact_result));
+    let assignment_2 = call.get_bool_result(Some(1))._eq(&call.is_overflow(&exact_result));
+    return Bool::and(call.ctx, &[&assignment_1, &assignment_2]);
+}
+
+fn encode_add_with_overflow<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_with_overflow(call, ArithmeticOperation::Add);
+}
+
+fn encode_sub_with_overflow<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_with_overflow(call, ArithmeticOperation::Sub);
+}
+
+fn encode_mul_with_overflow<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_with_overflow(call, ArithmeticOperation::Mul);
+}
+
+
+fn encode_sat<'a>(call: &IntrinsicCall<'a>, operation: ArithmeticOperation) -> Bool<'a> {
+    //! Result is clamped to the bounds of the type instead of wrapping
+    let exact_result = call.get_exact_result(operation);
+    let (min_int, max_int) = call.get_bounds();
+    let clamped_result = exact_result.gt(&max_int).ite(&max_int, &exact_result.lt(&min_int).ite(&min_int, &exact_result));
+    return call.get_int_result(None)._eq(&call.to_encoding(&clamped_result));
+}
+
+fn encode_add_sat<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_sat(call, ArithmeticOperation::Add);
+}
+
+fn encode_sub_sat<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_sat(call, ArithmeticOperation::Sub);
+}
+
+
+fn encode_checked<'a>(call: &IntrinsicCall<'a>, operation: ArithmeticOperation) -> Bool<'a> {
+    //! Result is an `Option<iN>` pair of the discriminant (0 for None, 1 for Some) and the value
+    let exact_result = call.get_exact_result(operation);
+    let discriminant = call.is_overflow(&exact_result).ite(&call.get_int(0), &call.get_int(1));
+    let assignment_1 = call.get_int_result(Some(0))._eq(&discriminant);
+    let assignment_2 = call.get_int_result(Some(1))._eq(&call.to_encoding(&exact_result));
+    return Bool::and(call.ctx, &[&assignment_1, &assignment_2]);
+}
+
+fn encode_checked_add<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_checked(call, ArithmeticOperation::Add);
+}
+
+fn encode_checked_sub<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_checked(call, ArithmeticOperation::Sub);
+}
+
+fn encode_checked_mul<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_checked(call, ArithmeticOperation::Mul);
+}
+
+
+fn encode_wrapping<'a>(call: &IntrinsicCall<'a>, operation: ArithmeticOperation) -> Bool<'a> {
+    return call.get_int_result(None)._eq(&call.to_encoding(&call.get_exact_result(operation)));
+}
+
+fn encode_wrapping_add<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_wrapping(call, ArithmeticOperation::Add);
+}
+
+fn encode_wrapping_sub<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_wrapping(call, ArithmeticOperation::Sub);
+}
+
+fn encode_wrapping_mul<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return encode_wrapping(call, ArithmeticOperation::Mul);
+}
+
+
+fn encode_abs<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! The absolute value of the min value wraps to itself (the poison flag is ignored)
+    let operand = call.get_int_operand(0);
+    let negated_operand = call.to_encoding(&operand.unary_minus());
+    let abs_value = operand.lt(&call.get_int(0)).ite(&negated_operand, &operand);
+    return call.get_int_result(None)._eq(&abs_value);
+}
+
+
+fn encode_max<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let is_first_greater = call.get_value_operand(0).ge(&call.get_value_operand(1));
+    return call.get_int_result(None)._eq(&is_first_greater.ite(&call.get_int_operand(0), &call.get_int_operand(1)));
+}
+
+fn encode_min<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let is_first_less = call.get_value_operand(0).le(&call.get_value_operand(1));
+    return call.get_int_result(None)._eq(&is_first_less.ite(&call.get_int_operand(0), &call.get_int_operand(1)));
+}
+
+
+fn encode_ctpop<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let operand = call.get_bv_operand(0);
+    let mut count = call.get_int(0);
+    for bit in 0..call.bit_width {
+        let bit_value = call.is_bit_set(&operand, bit).ite(&call.get_int(1), &call.get_int(0));
+        count = Int::add(call.ctx, &[&count, &bit_value]);
+    }
+    return call.get_int_result(None)._eq(&count);
+}
+
+fn encode_ctlz<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! A zero operand counts as the bit width, which refines the poison of the zero-is-poison flag
+    let operand = call.get_bv_operand(0);
+    let mut count = call.get_int(i64::from(call.bit_width));
+    // The highest set bit is checked last so that it takes precedence
+    for bit in 0..call.bit_width {
+        count = call.is_bit_set(&operand, bit).ite(&call.get_int(i64::from(call.bit_width - 1 - bit)), &count);
+    }
+    return call.get_int_result(None)._eq(&count);
+}
+
+fn encode_cttz<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! A zero operand counts as the bit width, which refines the poison of the zero-is-poison flag
+    let operand = call.get_bv_operand(0);
+    let mut count = call.get_int(i64::from(call.bit_width));
+    // The lowest set bit is checked last so that it takes precedence
+    for bit in (0..call.bit_width).rev() {
+        count = call.is_bit_set(&operand, bit).ite(&call.get_int(i64::from(bit)), &count);
+    }
+    return call.get_int_result(None)._eq(&count);
+}
+
+
+fn encode_bswap<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let operand = call.get_bv_operand(0);
+    let mut swapped_operand = operand.extract(7, 0);
+    for byte in 1..call.bit_width / 8 {
+        swapped_operand = swapped_operand.concat(&operand.extract(byte * 8 + 7, byte * 8));
+    }
+    return call.get_int_result(None)._eq(&call.from_bv(&swapped_operand));
+}
+
+
+fn get_shifted_concatenation<'a>(call: &IntrinsicCall<'a>, is_left_shift: bool) -> BV<'a> {
+    //! Concatenation of the first two operands shifted by the third operand modulo the bit width
+    let concatenation = call.get_bv_operand(0).concat(&call.get_bv_operand(1));
+    let bit_width = BV::from_u64(call.ctx, u64::from(call.bit_width), call.bit_width);
+    let shift = call.get_bv_operand(2).bvurem(&bit_width).zero_ext(call.bit_width);
+    if is_left_shift {
+        return concatenation.bvshl(&shift);
+    }
+    return concatenation.bvlshr(&shift);
+}
+
+fn encode_fshl<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let shifted_concatenation = get_shifted_concatenation(call, true);
+    let result = shifted_concatenation.extract(2 * call.bit_width - 1, call.bit_width);
+    return call.get_int_result(None)._eq(&call.from_bv(&result));
+}
+
+fn encode_fshr<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    let shifted_concatenation = get_shifted_concatenation(call, false);
+    let result = shifted_concatenation.extract(call.bit_width - 1, 0);
+    return call.get_int_result(None)._eq(&call.from_bv(&result));
+}
+
+
+fn encode_expect<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! The expected value is only a hint, the result is the first operand
+    if call.bit_width == 1 {
+        return call.get_bool_result(None)._eq(&call.get_bool_operand(0));
+    }
+    return call.get_int_result(None)._eq(&call.get_int_operand(0));
+}
+
+
+fn encode_assume<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! Paths violating the assumption are undefined behaviour, so they are excluded
+    return call.get_bool_operand(0);
+}
+
+
+fn encode_memcpy<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! Pointers are encoded as the single value they point to, so the destination takes the source's value
+    //! The copied length and the bytes of partially copied cells are ignored, so uses are recorded as over-approximations
+    return call.get_int_operand(0)._eq(&call.get_int_operand(1));
+}
+
+
+fn encode_memset<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    //! Only zeroing is modelled, as the value of a cell made of other repeated bytes depends on its width
+    let is_zeroing = Bool::and(call.ctx, &[&call.get_int_operand(1)._eq(&call.get_int(0)), &call.get_int_operand(2).gt(&call.get_int(0))]);
+    return is_zeroing.implies(&call.get_int_operand(0)._eq(&call.get_int(0)));
+}
+
+
+fn encode_no_op<'a>(call: &IntrinsicCall<'a>) -> Bool<'a> {
+    return Bool::from_bool(call.ctx, true);
+}
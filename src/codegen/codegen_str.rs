@@ -0,0 +1,353 @@
+use inkwell::types::{AnyType, AnyTypeEnum};
+use inkwell::values::{AnyValue, BasicValueEnum, FunctionValue, InstructionOpcode, InstructionValue};
+
+use z3::{Model, Solver, Sort};
+use z3::ast::{Array, Ast, Bool, Int};
+
+use crate::analysis_context::{AnalysisContext, PanicSite};
+use crate::codegen::codegen_heap::get_unsigned;
+use crate::codegen::codegen_undefined_behaviour::codegen_panic_site;
+use crate::soundness::SoundnessImpact;
+use crate::utils::function_utils::get_call_argument_count;
+use crate::utils::var_utils::get_var_name;
+
+
+/// Upper bound on the length in bytes of `&str` parameters, which keeps their UTF-8 constraint quantifier-free
+pub const MAX_STR_LENGTH: u64 = 8;
+
+/// Function reached by a string slice out of bounds or not on a char boundary
+pub const STR_SLICE_PANIC_PATH: &str = "core::str::slice_error_fail";
+
+const STR_LEN_PATH: &str = "core::str::<impl str>::len";
+const STR_AS_BYTES_PATH: &str = "core::str::<impl str>::as_bytes";
+const STR_IS_CHAR_BOUNDARY_PATH: &str = "core::str::<impl str>::is_char_boundary";
+const STR_INDEX_PATH: &str = "core::str::traits::<impl core::ops::index::Index<I> for str>::index";
+
+
+/// Ranges a string is sliced with, passed by their bounds (ex: `s[1..]` passes the start only, like `s[..1]` passes the end only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrRange {
+    Range,
+    RangeFromOrTo,
+    RangeFull,
+}
+
+
+/// Methods of `str` encoded over the bytes and length of the string, which are its first two arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrMethod {
+    Len,
+    AsBytes,
+    IsCharBoundary,
+    Index(StrRange),
+}
+
+
+pub fn is_byte_sequence_type(value_type: &AnyTypeEnum) -> bool {
+    //! Data pointers of `&str` and `&[u8]` point to an array of unknown length (ex: `[0 x i8]*`)
+    return value_type.is_pointer_type() && value_type.into_pointer_type().get_element_type().print_to_string().to_string() == "[0 x i8]";
+}
+
+
+pub fn is_byte_pointer(value: &BasicValueEnum) -> bool {
+    //! Pointers into a byte sequence, through casts and `getelementptr` (ex: `&s.as_bytes()[i]`)
+    if is_byte_sequence_type(&value.get_type().as_any_type_enum()) {
+        return true;
+    }
+    if !value.is_pointer_value() {
+        return false;
+    }
+    return match value.into_pointer_value().as_instruction() {
+        Some(instruction) if matches!(instruction.get_opcode(), InstructionOpcode::GetElementPtr | InstructionOpcode::BitCast) => {
+            is_byte_pointer(&instruction.get_operand(0).unwrap().left().unwrap())
+        }
+        _ => false,
+    };
+}
+
+
+pub fn get_str_length_parameter<'ctx>(function: &FunctionValue<'ctx>, parameter_index: u32, str_parameters: &Vec<String>) -> Option<BasicValueEnum<'ctx>> {
+    //! Length passed after the data pointer of a `&str` parameter (ex: `%s.1` after `%s.0`), None for other parameters
+    let data = function.get_nth_param(parameter_index)?;
+    let length = function.get_nth_param(parameter_index + 1)?;
+    if !is_byte_sequence_type(&data.get_type().as_any_type_enum()) || !length.is_int_value() {
+        return None;
+    }
+    let name = data.into_pointer_value().get_name().to_str().ok()?.strip_suffix(".0")?.to_string();
+    if !str_parameters.contains(&name) {
+        return None;
+    }
+    return Some(length);
+}
+
+
+fn get_bytes<'a>(solver: &'a Solver, var_name: &str) -> Array<'a> {
+    //! Bytes a pointer points into, as signed `i8` values indexed from the start of the sequence
+    return Array::new_const(solver.get_context(), format!("{}.bytes", var_name), &Sort::int(solver.get_context()), &Sort::int(solver.get_context()));
+}
+
+
+fn get_offset<'a>(solver: &'a Solver, var_name: &str) -> Int<'a> {
+    //! Index of the byte a pointer points to in its sequence
+    return Int::new_const(solver.get_context(), format!("{}.offset", var_name));
+}
+
+
+fn get_byte<'a>(bytes: &Array<'a>, index: &Int<'a>) -> Int<'a> {
+    return bytes.select(index).as_int().unwrap();
+}
+
+
+pub fn get_byte_pointer_assignment<'a>(solver: &'a Solver, lvalue_name: &str, rvalue_name: &str, offset: &Int<'a>) -> Bool<'a> {
+    //! The pointer points into the same bytes as the other one, the given number of bytes further
+    return Bool::and(solver.get_context(), &[
+        &get_bytes(solver, lvalue_name)._eq(&get_bytes(solver, rvalue_name)),
+        &get_offset(solver, lvalue_name)._eq(&(get_offset(solver, rvalue_name) + offset)),
+    ]);
+}
+
+
+fn get_byte_offset(instruction: &InstructionValue) -> Option<BasicValueEnum> {
+    //! Byte index of a `getelementptr` into a byte sequence (ex: `getelementptr [0 x i8], [0 x i8]* %p, i64 0, i64 %i` or `getelementptr i8, i8* %p, i64 %i`)
+    return match instruction.get_num_operands() {
+        2 => instruction.get_operand(1)?.left(),
+        3 if instruction.get_operand(1)?.left()?.into_int_value().get_zero_extended_constant() == Some(0) => instruction.get_operand(2)?.left(),
+        _ => None,
+    };
+}
+
+
+pub fn codegen_byte_pointer<'a>(
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Bool<'a> {
+    //! A cast or `getelementptr` of a pointer into a byte sequence moves within the same bytes
+    let base = instruction.get_operand(0).unwrap().left().unwrap();
+    let offset = match instruction.get_opcode() {
+        InstructionOpcode::BitCast => Some(Int::from_u64(solver.get_context(), 0)),
+        _ => get_byte_offset(instruction).map(|offset| Int::new_const(solver.get_context(), get_var_name(&offset, solver, analysis, namespace))),
+    };
+    if offset.is_none() {
+        analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Pointer {:?} into a byte sequence is not supported", instruction.print_to_string().to_string()));
+        return node_var;
+    }
+    let lvalue_name = get_var_name(instruction, solver, analysis, namespace);
+    let rvalue_name = get_var_name(&base, solver, analysis, namespace);
+    return get_byte_pointer_assignment(solver, &lvalue_name, &rvalue_name, &offset.unwrap()).implies(&node_var);
+}
+
+
+pub fn codegen_byte_load<'a>(
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Bool<'a> {
+    //! Loading through a pointer into a byte sequence reads the byte at its offset
+    let pointer_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), solver, analysis, namespace);
+    let lvalue_var = Int::new_const(solver.get_context(), get_var_name(instruction, solver, analysis, namespace));
+    let byte = get_byte(&get_bytes(solver, &pointer_name), &get_offset(solver, &pointer_name));
+    return lvalue_var._eq(&byte).implies(&node_var);
+}
+
+
+fn get_unsigned_byte<'a>(solver: &'a Solver, bytes: &Array<'a>, index: u64) -> Int<'a> {
+    let byte = get_byte(bytes, &Int::from_u64(solver.get_context(), index));
+    return byte.lt(&Int::from_i64(solver.get_context(), 0)).ite(&(&byte + &Int::from_u64(solver.get_context(), 256)), &byte);
+}
+
+
+fn is_utf8_sequence<'a>(solver: &'a Solver, bytes: &Array<'a>, length: &Int<'a>, index: u64) -> Bool<'a> {
+    //! The byte at the index is valid in UTF-8: either the lead of a well-formed sequence within the length, or a continuation byte of a preceding lead
+    let ctx = solver.get_context();
+    let is_in = |byte_index: u64, low: u64, high: u64| -> Bool<'a> {
+        let byte = get_unsigned_byte(solver, bytes, byte_index);
+        return Bool::and(ctx, &[&byte.ge(&Int::from_u64(ctx, low)), &byte.le(&Int::from_u64(ctx, high))]);
+    };
+    let is_within = |byte_index: u64| Int::from_u64(ctx, byte_index).lt(length);
+    let is_lead = |lead: u64| get_unsigned_byte(solver, bytes, index)._eq(&Int::from_u64(ctx, lead));
+
+    // The second byte of some leads is further restricted, which rules out overlong encodings, surrogates and code points above U+10FFFF
+    let is_ascii = is_in(index, 0x00, 0x7F);
+    let is_two_byte = Bool::and(ctx, &[&is_in(index, 0xC2, 0xDF), &is_within(index + 1), &is_in(index + 1, 0x80, 0xBF)]);
+    let second_of_three = is_lead(0xE0).ite(&is_in(index + 1, 0xA0, 0xBF), &is_lead(0xED).ite(&is_in(index + 1, 0x80, 0x9F), &is_in(index + 1, 0x80, 0xBF)));
+    let is_three_byte = Bool::and(ctx, &[&is_in(index, 0xE0, 0xEF), &is_within(index + 2), &second_of_three, &is_in(index + 2, 0x80, 0xBF)]);
+    let second_of_four = is_lead(0xF0).ite(&is_in(index + 1, 0x90, 0xBF), &is_lead(0xF4).ite(&is_in(index + 1, 0x80, 0x8F), &is_in(index + 1, 0x80, 0xBF)));
+    let is_four_byte = Bool::and(ctx, &[&is_in(index, 0xF0, 0xF4), &is_within(index + 3), &second_of_four, &is_in(index + 2, 0x80, 0xBF), &is_in(index + 3, 0x80, 0xBF)]);
+
+    // A continuation byte must be covered by one of the three preceding bytes, whose sequence then checks it
+    let mut covering_leads = Vec::<Bool>::new();
+    for (distance, low) in [(1, 0xC2), (2, 0xE0), (3, 0xF0)] {
+        if index >= distance {
+            covering_leads.push(is_in(index - distance, low, 0xF4));
+        }
+    }
+    let is_covered = Bool::or(ctx, &covering_leads.iter().collect::<Vec<&Bool>>());
+    let is_continuation = Bool::and(ctx, &[&is_in(index, 0x80, 0xBF), &is_covered]);
+
+    return Bool::or(ctx, &[&is_ascii, &is_two_byte, &is_three_byte, &is_four_byte, &is_continuation]);
+}
+
+
+pub fn constrain_str_parameter(solver: &Solver, analysis: &AnalysisContext, data_var_name: &str, length_var_name: &str) -> () {
+    //! A `&str` parameter holds valid UTF-8 bytes, up to a bounded length
+    let ctx = solver.get_context();
+    let bytes = get_bytes(solver, data_var_name);
+    let length = Int::new_const(ctx, length_var_name);
+    solver.assert(&get_offset(solver, data_var_name)._eq(&Int::from_u64(ctx, 0)));
+    solver.assert(&Bool::and(ctx, &[&length.ge(&Int::from_u64(ctx, 0)), &length.le(&Int::from_u64(ctx, MAX_STR_LENGTH))]));
+    for index in 0..MAX_STR_LENGTH {
+        let byte = get_byte(&bytes, &Int::from_u64(ctx, index));
+        solver.assert(&Bool::and(ctx, &[&byte.ge(&Int::from_i64(ctx, i64::from(i8::MIN))), &byte.le(&Int::from_i64(ctx, i64::from(i8::MAX)))]));
+        solver.assert(&Int::from_u64(ctx, index).lt(&length).implies(&is_utf8_sequence(solver, &bytes, &length, index)));
+    }
+    analysis.ledger.record(SoundnessImpact::Unsound, format!("Length of string {} is bounded to {} bytes", data_var_name, MAX_STR_LENGTH));
+}
+
+
+pub fn get_str_literal(model: &Model, solver: &Solver, data_var_name: &str, length_var_name: &str) -> Option<String> {
+    //! Escaped Rust literal of the string held by a `&str` parameter in the model (ex: `"a\u{301}"`)
+    let length = model.eval(&Int::new_const(solver.get_context(), length_var_name), true)?.as_u64()?;
+    let bytes = get_bytes(solver, data_var_name);
+    let mut string_bytes = Vec::<u8>::new();
+    for index in 0..length {
+        let byte = model.eval(&get_byte(&bytes, &Int::from_u64(solver.get_context(), index)), true)?.as_i64()?;
+        string_bytes.push(byte as u8);
+    }
+    return String::from_utf8(string_bytes).ok().map(|string| format!("{:?}", string));
+}
+
+
+fn get_bound_operands<'ctx>(instruction: &InstructionValue<'ctx>) -> Vec<BasicValueEnum<'ctx>> {
+    //! Integer arguments following the string, which are the bounds of a range, while a `&Location` of the caller may end the arguments
+    return (2..get_call_argument_count(instruction))
+        .filter_map(|operand_index| instruction.get_operand(operand_index).and_then(|operand| operand.left()))
+        .filter(|operand| operand.is_int_value())
+        .collect();
+}
+
+
+fn get_str_range(instruction: &InstructionValue) -> Option<StrRange> {
+    //! The range type is erased from the path of the generic `Index` instance, but its number of bounds is the number of integer arguments
+    let range = match get_bound_operands(instruction).len() {
+        0 => StrRange::RangeFull,
+        1 => StrRange::RangeFromOrTo,
+        2 => StrRange::Range,
+        _ => return None,
+    };
+    return Some(range);
+}
+
+
+pub fn get_str_method(function_path: &str, instruction: &InstructionValue) -> Option<StrMethod> {
+    //! Modelled method of `str` called by the instruction, whose first arguments are the data pointer and length of the string
+    let data = instruction.get_operand(0)?.left()?;
+    if get_call_argument_count(instruction) < 2 || !is_byte_sequence_type(&data.get_type().as_any_type_enum()) || !instruction.get_operand(1)?.left()?.is_int_value() {
+        return None;
+    }
+    let method = match function_path {
+        STR_LEN_PATH => StrMethod::Len,
+        STR_AS_BYTES_PATH => StrMethod::AsBytes,
+        STR_IS_CHAR_BOUNDARY_PATH => StrMethod::IsCharBoundary,
+        STR_INDEX_PATH => StrMethod::Index(get_str_range(instruction)?),
+        _ => return None,
+    };
+    return Some(method);
+}
+
+
+fn is_char_boundary<'a>(solver: &'a Solver, bytes: &Array<'a>, offset: &Int<'a>, length: &Int<'a>, index: &Int<'a>) -> Bool<'a> {
+    //! Like `str::is_char_boundary`: the start, the end, or a byte in between that is not a continuation byte (`b as i8 >= -0x40`)
+    let ctx = solver.get_context();
+    let zero = Int::from_u64(ctx, 0);
+    let is_inner_boundary = Bool::and(ctx, &[
+        &index.gt(&zero),
+        &index.lt(length),
+        &get_byte(bytes, &(offset + index)).ge(&Int::from_i64(ctx, -0x40)),
+    ]);
+    return Bool::or(ctx, &[&index._eq(&zero), &index._eq(length), &is_inner_boundary]);
+}
+
+
+fn get_slice_panic_site(message: &str) -> PanicSite {
+    return PanicSite {
+        function_path: String::from(STR_SLICE_PANIC_PATH),
+        message: Some(String::from(message)),
+        is_undefined_behaviour: false,
+    };
+}
+
+
+pub fn codegen_str_call<'a>(
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    method: StrMethod
+) -> Bool<'a> {
+    //! Strings are encoded by the bytes their data pointer points into and their length, slices sharing the bytes of the sliced string
+    let ctx = solver.get_context();
+    let data_name = get_var_name(&instruction.get_operand(0).unwrap().left().unwrap(), solver, analysis, namespace);
+    let length = get_unsigned(&instruction.get_operand(1).unwrap().left().unwrap(), solver, analysis, namespace).unwrap();
+    let bytes = get_bytes(solver, &data_name);
+    let offset = get_offset(solver, &data_name);
+    let result_name = get_var_name(&instruction, solver, analysis, namespace);
+    let bounds: Vec<Int> = get_bound_operands(&instruction).iter()
+        .filter_map(|operand| get_unsigned(operand, solver, analysis, namespace))
+        .collect();
+
+    // Returned slices are a pair of a data pointer and a length, read back by `extractvalue`
+    let get_slice_assignment = |start: &Int<'a>, end: &Int<'a>| -> Bool<'a> {
+        return Bool::and(ctx, &[
+            &get_byte_pointer_assignment(solver, &format!("{}.0", result_name), &data_name, start),
+            &Int::new_const(ctx, format!("{}.1", result_name))._eq(&(end - start)),
+        ]);
+    };
+    match method {
+        StrMethod::Len => {
+            node_var = Int::new_const(ctx, result_name.as_str())._eq(&length).implies(&node_var);
+        }
+        StrMethod::AsBytes => {
+            node_var = get_slice_assignment(&Int::from_u64(ctx, 0), &length).implies(&node_var);
+        }
+        StrMethod::IsCharBoundary => {
+            let index = bounds.first().unwrap();
+            node_var = Bool::new_const(ctx, result_name.as_str())._eq(&is_char_boundary(solver, &bytes, &offset, &length, index)).implies(&node_var);
+        }
+        StrMethod::Index(range) => {
+            let zero = Int::from_u64(ctx, 0);
+            let (start, end) = match (range, bounds.as_slice()) {
+                (StrRange::Range, [start, end, ..]) => (start.clone(), end.clone()),
+                (StrRange::RangeFromOrTo, [bound, ..]) => {
+                    // `s[a..]` and `s[..b]` call the same instance with one bound, so either reading may be taken
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Slicing with one bound is read as both `a..` and `..b` in {:?}", instruction.print_to_string().to_string()));
+                    let is_range_from = Bool::new_const(ctx, format!("{}.is_range_from", result_name));
+                    (is_range_from.ite(bound, &zero), is_range_from.ite(&length, bound))
+                }
+                (StrRange::RangeFull, _) => (zero.clone(), length.clone()),
+                _ => {
+                    analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Bounds of string slice {:?} are not supported", instruction.print_to_string().to_string()));
+                    return node_var;
+                }
+            };
+            node_var = get_slice_assignment(&start, &end).implies(&node_var);
+
+            // The checks are nested in the order `slice_error_fail` reports them, the outermost one first
+            let is_not_on_boundary = Bool::and(ctx, &[
+                &is_char_boundary(solver, &bytes, &offset, &length, &start),
+                &is_char_boundary(solver, &bytes, &offset, &length, &end),
+            ]).not();
+            node_var = codegen_panic_site(node_var, &is_not_on_boundary, get_slice_panic_site("byte index is not a char boundary"), solver, analysis, panic_target_node);
+            node_var = codegen_panic_site(node_var, &start.gt(&end), get_slice_panic_site("begin <= end when slicing a string"), solver, analysis, panic_target_node);
+            let is_out_of_bounds = Bool::or(ctx, &[&start.gt(&length), &end.gt(&length)]);
+            node_var = codegen_panic_site(node_var, &is_out_of_bounds, get_slice_panic_site("byte index is out of bounds of the string"), solver, analysis, panic_target_node);
+        }
+    }
+    return node_var;
+}
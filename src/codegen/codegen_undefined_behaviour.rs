@@ -0,0 +1,478 @@
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::{Module as InkwellModule};
+use inkwell::types::{AnyType, AnyTypeEnum, BasicTypeEnum};
+use inkwell::values::{AnyValue, BasicValueEnum, FunctionValue, GlobalValue, InstructionOpcode, InstructionValue};
+
+use z3::Solver;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::analysis_context::{AnalysisContext, PanicSite};
+use crate::codegen::codegen_global::{get_element_offset, get_global_pointer, get_pointer_width, get_target_data};
+use crate::codegen::codegen_heap::{get_allocation_size, get_freed_regions, is_allocation_call};
+use crate::llvm_constant::{get_constant_expression, ConstantExpression};
+use crate::panics::PANIC_SITE_VAR_NAME;
+use crate::soundness::SoundnessImpact;
+use crate::symbolic_execution::{get_module_name_from_file_name, get_panic_var_name};
+use crate::utils::function_utils::{get_called_function, get_function_name, get_function_path};
+use crate::utils::var_utils::get_var_name;
+
+
+/// Depth up to which the pointers returned by called functions are followed back to their origin
+const MAX_ORIGIN_CALL_DEPTH: u32 = 4;
+
+
+pub fn codegen_panic_site<'a>(
+    node_var: Bool<'a>,
+    condition: &Bool<'a>,
+    panic_site: PanicSite,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! Panics on the paths where the condition holds, recording the panic site they reach
+    let mut panic_sites = analysis.panic_sites.borrow_mut();
+    let panic_site_var = Int::new_const(solver.get_context(), PANIC_SITE_VAR_NAME);
+    let site_assignment = panic_site_var._eq(&Int::from_u64(solver.get_context(), panic_sites.len() as u64));
+    panic_sites.push(panic_site);
+
+    let panic_var = Bool::new_const(solver.get_context(), get_panic_var_name(panic_target_node));
+    let panic_target_node_var = Bool::new_const(solver.get_context(), panic_target_node);
+    let panic_path = site_assignment.implies(&panic_var._eq(&Bool::from_bool(solver.get_context(), true)).implies(&panic_target_node_var));
+    return condition.ite(&panic_path, &node_var);
+}
+
+
+pub fn codegen_undefined_behaviour<'a>(
+    node_var: Bool<'a>,
+    is_undefined_behaviour: &Bool<'a>,
+    instruction: &InstructionValue,
+    description: String,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! Reports the undefined behaviour like a panic on the paths where the condition holds, with a panic site describing it
+    let function_path = instruction.get_parent()
+        .and_then(|bb| bb.get_parent())
+        .map(|function| get_function_path(&function.as_global_value().as_pointer_value()))
+        .unwrap_or_default();
+    let panic_site = PanicSite {
+        function_path,
+        message: Some(description),
+        is_undefined_behaviour: true,
+    };
+    return codegen_panic_site(node_var, is_undefined_behaviour, panic_site, solver, analysis, panic_target_node);
+}
+
+
+pub fn get_address_var_name(var_name: &str) -> String {
+    //! Variable holding the address of a pointer, apart from the variable encoding the loads and stores through it
+    return format!("{}.address", var_name);
+}
+
+
+pub fn get_max_address<'a>(module: &InkwellModule, solver: &'a Solver) -> Int<'a> {
+    return Int::from_u64(solver.get_context(), u64::MAX >> (64 - get_pointer_width(module).min(64)));
+}
+
+
+fn is_aligned<'a>(solver: &'a Solver, address: &Int<'a>, alignment: u64) -> Bool<'a> {
+    return address.modulo(&Int::from_u64(solver.get_context(), alignment.max(1)))._eq(&Int::from_u64(solver.get_context(), 0));
+}
+
+
+fn get_object_address<'a>(module: &InkwellModule, solver: &'a Solver, var_name: String, alignment: u64) -> Int<'a> {
+    //! Address of an object in memory (ex: a stack slot, a global), which is never null and aligned
+    //! The address variable is only used by this object, so its domain is asserted right away
+    let address = Int::new_const(solver.get_context(), var_name);
+    let is_valid = Bool::and(solver.get_context(), &[
+        &address.gt(&Int::from_u64(solver.get_context(), 0)),
+        &address.le(&get_max_address(module, solver)),
+        &is_aligned(solver, &address, alignment),
+    ]);
+    solver.assert(&is_valid);
+    return address;
+}
+
+
+fn get_global_address<'a>(module: &InkwellModule, solver: &'a Solver, global: &GlobalValue) -> Int<'a> {
+    let var_name = get_address_var_name(&format!("@{}", global.get_name().to_str().unwrap()));
+    return get_object_address(module, solver, var_name, u64::from(global.get_alignment()));
+}
+
+
+fn get_constant_address(constant: &BasicValueEnum) -> Option<u64> {
+    //! Address of an integer constant cast to a pointer (ex: `i32* inttoptr (i64 4 to i32*)` -> 4, as created by `NonNull::dangling`)
+    return match get_constant_expression(constant)? {
+        ConstantExpression::IntToPtr(operand) if operand.is_int_value() => operand.into_int_value().get_zero_extended_constant(),
+        _ => None,
+    };
+}
+
+
+pub fn get_pointer_address<'a>(
+    module: &InkwellModule,
+    pointer: &BasicValueEnum,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<Int<'a>> {
+    //! Address held by a pointer, None for the constants that are not modelled
+    if !pointer.is_pointer_value() {
+        return None;
+    }
+    let pointer_value = pointer.into_pointer_value();
+    if pointer_value.is_null() {
+        return Some(Int::from_u64(solver.get_context(), 0));
+    }
+    if pointer_value.is_const() {
+        if let Some((global, offset)) = get_global_pointer(module, pointer, solver, analysis, namespace) {
+            return Some(get_global_address(module, solver, &global) + offset);
+        }
+        return get_constant_address(pointer).map(|address| Int::from_u64(solver.get_context(), address));
+    }
+    return Some(Int::new_const(solver.get_context(), get_address_var_name(&get_var_name(pointer, solver, analysis, namespace))));
+}
+
+
+fn get_gep_offset<'a>(
+    module: &InkwellModule,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<Int<'a>> {
+    //! Byte offset a `getelementptr` adds to its base, None if it is not over a typed pointer (ex: vectors of pointers)
+    let base = instruction.get_operand(0)?.left()?;
+    if !base.is_pointer_value() {
+        return None;
+    }
+    let source_type = BasicTypeEnum::try_from(base.into_pointer_value().get_type().get_element_type()).ok()?;
+    let mut indices = Vec::<Int>::new();
+    for operand_index in 1..instruction.get_num_operands() {
+        let index = instruction.get_operand(operand_index)?.left()?;
+        if !index.is_int_value() {
+            return None;
+        }
+        indices.push(Int::new_const(solver.get_context(), get_var_name(&index, solver, analysis, namespace)));
+    }
+    return get_element_offset(solver, &get_target_data(module), &source_type, &indices);
+}
+
+
+fn get_analysed_called_function<'ctx>(module: &InkwellModule<'ctx>, instruction: &InstructionValue) -> Option<FunctionValue<'ctx>> {
+    //! Function of the file called by the instruction, which is analysed at the call (and so assigns the address it returns)
+    let function = get_called_function(module, instruction)?;
+    let module_name = get_module_name_from_file_name(&String::from(module.get_name().to_str().unwrap()));
+    let is_analysed = get_function_name(&function.as_global_value().as_pointer_value()).contains(&module_name);
+    return if is_analysed && function.count_basic_blocks() > 0 { Some(function) } else { None };
+}
+
+
+fn is_known_pointer(module: &InkwellModule, pointer: &BasicValueEnum, solver: &Solver, analysis: &AnalysisContext, namespace: &str, call_depth: u32) -> bool {
+    //! Whether the address of the pointer is encoded, following it back to constants, parameters, stack slots and returned pointers
+    //! Pointers loaded from memory or merged by phis are not followed, as their addresses are unconstrained
+    if !pointer.is_pointer_value() {
+        return false;
+    }
+    let pointer_value = pointer.into_pointer_value();
+    if pointer_value.is_const() {
+        return get_pointer_address(module, pointer, solver, analysis, namespace).is_some();
+    }
+    let instruction = match pointer_value.as_instruction() {
+        Some(instruction) => instruction,
+        // Parameters of the analysed function are constrained on entry, those of called functions are assigned the arguments
+        None => return true,
+    };
+    return match instruction.get_opcode() {
+        InstructionOpcode::Alloca | InstructionOpcode::IntToPtr => true,
+        InstructionOpcode::Call | InstructionOpcode::Invoke if is_allocation_call(&instruction) => analysis.options.check_undefined_behaviour,
+        InstructionOpcode::BitCast => instruction.get_operand(0).and_then(|operand| operand.left())
+            .map_or(false, |operand| is_known_pointer(module, &operand, solver, analysis, namespace, call_depth)),
+        InstructionOpcode::GetElementPtr => get_gep_offset(module, &instruction, solver, analysis, namespace).is_some()
+            && is_known_pointer(module, &instruction.get_operand(0).unwrap().left().unwrap(), solver, analysis, namespace, call_depth),
+        InstructionOpcode::Call | InstructionOpcode::Invoke if call_depth < MAX_ORIGIN_CALL_DEPTH => {
+            match get_analysed_called_function(module, &instruction) {
+                Some(function) => get_returned_values(&function).iter()
+                    .all(|returned_value| is_known_pointer(module, returned_value, solver, analysis, namespace, call_depth + 1)),
+                None => false,
+            }
+        }
+        _ => false,
+    };
+}
+
+
+fn get_returned_values<'ctx>(function: &FunctionValue<'ctx>) -> Vec<BasicValueEnum<'ctx>> {
+    let mut returned_values = Vec::new();
+    for bb in function.get_basic_blocks() {
+        if let Some(terminator) = bb.get_terminator() {
+            if terminator.get_opcode() == InstructionOpcode::Return && terminator.get_num_operands() == 1 {
+                returned_values.push(terminator.get_operand(0).unwrap().left().unwrap());
+            }
+        }
+    }
+    return returned_values;
+}
+
+
+fn get_dereferenceable_size(function: &FunctionValue, parameter: &BasicValueEnum) -> Option<u64> {
+    //! Size of the object a reference parameter points to (ex: 8 for `&[u8; 8]`), given by its `dereferenceable` attribute
+    let parameter_index = function.get_params().iter().position(|function_parameter| function_parameter == parameter)?;
+    let attribute = function.get_enum_attribute(AttributeLoc::Param(u32::try_from(parameter_index).ok()?), Attribute::get_named_enum_kind_id("dereferenceable"))?;
+    return Some(attribute.get_enum_value());
+}
+
+
+fn get_object_description(object_size: &Int) -> String {
+    return match object_size.simplify().as_u64() {
+        Some(object_size) => format!("a {} byte object", object_size),
+        None => String::from("an allocation of symbolic size"),
+    };
+}
+
+
+fn get_pointer_object<'a>(
+    module: &InkwellModule,
+    pointer: &BasicValueEnum,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str
+) -> Option<(Int<'a>, Int<'a>)> {
+    //! Address and size of the object a pointer is derived from (ex: a stack slot, a global, an allocation or the referent of a reference parameter)
+    if !pointer.is_pointer_value() {
+        return None;
+    }
+    let pointer_value = pointer.into_pointer_value();
+    if pointer_value.is_const() {
+        let (global, _) = get_global_pointer(module, pointer, solver, analysis, namespace)?;
+        let global_type = global.as_pointer_value().get_type().get_element_type();
+        let global_size = Int::from_u64(solver.get_context(), get_target_data(module).get_abi_size(&global_type));
+        return Some((get_global_address(module, solver, &global), global_size));
+    }
+    let instruction = match pointer_value.as_instruction() {
+        Some(instruction) => instruction,
+        None => {
+            let referent_size = Int::from_u64(solver.get_context(), get_dereferenceable_size(function, pointer)?);
+            return Some((get_pointer_address(module, pointer, solver, analysis, namespace)?, referent_size));
+        }
+    };
+    return match instruction.get_opcode() {
+        InstructionOpcode::BitCast | InstructionOpcode::GetElementPtr => {
+            get_pointer_object(module, &instruction.get_operand(0)?.left()?, function, solver, analysis, namespace)
+        }
+        InstructionOpcode::Alloca => {
+            let allocated_type = instruction.get_type().into_pointer_type().get_element_type();
+            let count = instruction.get_operand(0)?.left()?.into_int_value().get_zero_extended_constant()?;
+            let size = Int::from_u64(solver.get_context(), get_target_data(module).get_abi_size(&allocated_type) * count);
+            Some((get_pointer_address(module, pointer, solver, analysis, namespace)?, size))
+        }
+        InstructionOpcode::Call | InstructionOpcode::Invoke if is_allocation_call(&instruction) => {
+            Some((get_pointer_address(module, pointer, solver, analysis, namespace)?, get_allocation_size(&instruction, solver, analysis, namespace)?))
+        }
+        _ => None,
+    };
+}
+
+
+pub fn constrain_pointer_parameter(module: &InkwellModule, function: &FunctionValue, parameter_index: u32, solver: &Solver, analysis: &AnalysisContext, namespace: &str) -> () {
+    //! Raw pointers may hold any address, references (`nonnull`) are never null and aligned to their `align` attribute
+    let parameter = function.get_nth_param(parameter_index).unwrap();
+    let address = get_pointer_address(module, &parameter, solver, analysis, namespace).unwrap();
+    let is_nonnull = function.get_enum_attribute(AttributeLoc::Param(parameter_index), Attribute::get_named_enum_kind_id("nonnull")).is_some();
+    let alignment = function.get_enum_attribute(AttributeLoc::Param(parameter_index), Attribute::get_named_enum_kind_id("align")).map_or(1, |attribute| attribute.get_enum_value());
+    let min_address = if is_nonnull { 1 } else { 0 };
+    solver.assert(&Bool::and(solver.get_context(), &[
+        &address.ge(&Int::from_u64(solver.get_context(), min_address)),
+        &address.le(&get_max_address(module, solver)),
+        &is_aligned(solver, &address, if is_nonnull { alignment } else { 1 }),
+    ]));
+}
+
+
+fn codegen_address_definition<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    return_register: &str
+) -> Bool<'a> {
+    //! Encodes the address computed by a cast, a `getelementptr`, an `alloca` or a returned pointer
+    let result_address = || Int::new_const(solver.get_context(), get_address_var_name(&get_var_name(instruction, solver, analysis, namespace)));
+    let operand = match instruction.get_operand(0).and_then(|operand| operand.left()) {
+        Some(operand) => operand,
+        None => return node_var,
+    };
+    let assignment = match instruction.get_opcode() {
+        InstructionOpcode::Alloca => {
+            let var_name = get_address_var_name(&get_var_name(instruction, solver, analysis, namespace));
+            get_object_address(module, solver, var_name, u64::from(instruction.get_alignment().unwrap_or(1)));
+            None
+        }
+        InstructionOpcode::BitCast if operand.is_pointer_value() && instruction.get_type().is_pointer_type() => {
+            get_pointer_address(module, &operand, solver, analysis, namespace).map(|address| result_address()._eq(&address))
+        }
+        InstructionOpcode::GetElementPtr => {
+            match (get_pointer_address(module, &operand, solver, analysis, namespace), get_gep_offset(module, instruction, solver, analysis, namespace)) {
+                (Some(base_address), Some(offset)) => Some(result_address()._eq(&(base_address + offset))),
+                _ => None,
+            }
+        }
+        InstructionOpcode::IntToPtr => {
+            let integer = Int::new_const(solver.get_context(), get_var_name(&operand, solver, analysis, namespace));
+            Some(result_address()._eq(&integer))
+        }
+        InstructionOpcode::PtrToInt => {
+            let integer = Int::new_const(solver.get_context(), get_var_name(instruction, solver, analysis, namespace));
+            get_pointer_address(module, &operand, solver, analysis, namespace).map(|address| integer._eq(&address))
+        }
+        InstructionOpcode::Return if operand.is_pointer_value() => {
+            let return_address = Int::new_const(solver.get_context(), get_address_var_name(return_register));
+            get_pointer_address(module, &operand, solver, analysis, namespace).map(|address| return_address._eq(&address))
+        }
+        _ => None,
+    };
+    return match assignment {
+        Some(assignment) => assignment.implies(&node_var),
+        None => node_var,
+    };
+}
+
+
+fn get_accessed_pointer<'ctx>(instruction: &InstructionValue<'ctx>) -> Option<(BasicValueEnum<'ctx>, AnyTypeEnum<'ctx>, &'static str)> {
+    //! Pointer read or written by a memory access, with the accessed type and the kind of access
+    return match instruction.get_opcode() {
+        InstructionOpcode::Load => Some((instruction.get_operand(0)?.left()?, instruction.get_type(), "load")),
+        InstructionOpcode::Store => {
+            let value = instruction.get_operand(0)?.left()?;
+            Some((instruction.get_operand(1)?.left()?, value.get_type().as_any_type_enum(), "store"))
+        }
+        _ => None,
+    };
+}
+
+
+fn codegen_memory_access_checks<'a>(
+    module: &InkwellModule,
+    mut node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! A load or store through a null, misaligned or out of bounds pointer is undefined behaviour
+    let (pointer, accessed_type, access_kind) = match get_accessed_pointer(instruction) {
+        Some(accessed_pointer) => accessed_pointer,
+        None => return node_var,
+    };
+    if !is_known_pointer(module, &pointer, solver, analysis, namespace, 0) {
+        analysis.ledger.record(SoundnessImpact::Unsound, format!("The {} through pointer {} of unknown origin is not checked for undefined behaviour", access_kind, get_var_name(&pointer, solver, analysis, namespace)));
+        return node_var;
+    }
+    let address = get_pointer_address(module, &pointer, solver, analysis, namespace).unwrap();
+    let access_size = get_target_data(module).get_store_size(&accessed_type);
+    let alignment = u64::from(instruction.get_alignment().unwrap_or(1));
+
+    // The checks are nested so that a null pointer is reported as such rather than as misaligned or out of bounds
+    if let Some((object_address, object_size)) = get_pointer_object(module, &pointer, function, solver, analysis, namespace) {
+        let offset = &address - &object_address;
+        let is_out_of_bounds = Bool::or(solver.get_context(), &[
+            &offset.lt(&Int::from_i64(solver.get_context(), 0)),
+            &(&offset + &Int::from_u64(solver.get_context(), access_size)).gt(&object_size),
+        ]);
+        let description = format!("dangling pointer dereference: {} of {} bytes out of the bounds of {}", access_kind, access_size, get_object_description(&object_size));
+        node_var = codegen_undefined_behaviour(node_var, &is_out_of_bounds, instruction, description, solver, analysis, panic_target_node);
+    }
+    if alignment > 1 {
+        let is_misaligned = is_aligned(solver, &address, alignment).not();
+        let description = format!("misaligned {} of {} bytes at an address that is not a multiple of {}", access_kind, access_size, alignment);
+        node_var = codegen_undefined_behaviour(node_var, &is_misaligned, instruction, description, solver, analysis, panic_target_node);
+    }
+    let freed_regions = get_freed_regions(module, instruction, function, solver, analysis, namespace);
+    if !freed_regions.is_empty() {
+        let is_in_freed_regions: Vec<Bool> = freed_regions.iter()
+            .map(|(freed_address, freed_size)| Bool::and(solver.get_context(), &[&address.ge(freed_address), &address.lt(&(freed_address + freed_size))]))
+            .collect();
+        let is_use_after_free = Bool::or(solver.get_context(), &is_in_freed_regions.iter().collect::<Vec<&Bool>>());
+        let description = format!("use after free: {} of {} bytes in a freed allocation", access_kind, access_size);
+        node_var = codegen_undefined_behaviour(node_var, &is_use_after_free, instruction, description, solver, analysis, panic_target_node);
+    }
+    let is_null = address._eq(&Int::from_u64(solver.get_context(), 0));
+    let description = format!("null pointer dereference: {} of {} bytes", access_kind, access_size);
+    node_var = codegen_undefined_behaviour(node_var, &is_null, instruction, description, solver, analysis, panic_target_node);
+    return node_var;
+}
+
+
+fn codegen_inbounds_check<'a>(
+    module: &InkwellModule,
+    node_var: Bool<'a>,
+    instruction: &InstructionValue,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str
+) -> Bool<'a> {
+    //! A `getelementptr inbounds` must stay within its object, or point one past its end
+    if !instruction.print_to_string().to_string().contains("getelementptr inbounds") {
+        return node_var;
+    }
+    let base = instruction.get_operand(0).unwrap().left().unwrap();
+    if !base.is_pointer_value() || !is_known_pointer(module, &base, solver, analysis, namespace, 0) {
+        return node_var;
+    }
+    let object = get_pointer_object(module, &base, function, solver, analysis, namespace);
+    let base_address = get_pointer_address(module, &base, solver, analysis, namespace);
+    let offset = get_gep_offset(module, instruction, solver, analysis, namespace);
+    let (object_address, object_size, base_address, offset) = match (object, base_address, offset) {
+        (Some((object_address, object_size)), Some(base_address), Some(offset)) => (object_address, object_size, base_address, offset),
+        _ => return node_var,
+    };
+    let object_offset = base_address + offset - object_address;
+    let is_out_of_bounds = Bool::or(solver.get_context(), &[
+        &object_offset.lt(&Int::from_i64(solver.get_context(), 0)),
+        &object_offset.gt(&object_size),
+    ]);
+    let description = format!("`getelementptr inbounds` out of the bounds of {}", get_object_description(&object_size));
+    return codegen_undefined_behaviour(node_var, &is_out_of_bounds, instruction, description, solver, analysis, panic_target_node);
+}
+
+
+pub fn codegen_undefined_behaviour_checks<'a>(
+    module: &InkwellModule,
+    mut node_var: Bool<'a>,
+    instruction: InstructionValue,
+    function: &FunctionValue,
+    solver: &'a Solver,
+    analysis: &AnalysisContext,
+    namespace: &str,
+    panic_target_node: &str,
+    return_register: &str
+) -> Bool<'a> {
+    //! Checks of the UB checker mode, encoded around the instruction (the arithmetic flags are checked with the arithmetic, see `codegen_binary_arithmetic`)
+    //! Pointers are given addresses, which are tracked apart from the values loaded and stored through them
+    match instruction.get_opcode() {
+        InstructionOpcode::Unreachable if instruction.get_previous_instruction().is_none() => {
+            // Only a lone `unreachable` is undefined behaviour, panicking blocks call a panic function first
+            let description = String::from("reachable `unreachable` (ex: `unreachable_unchecked` or an invalid enum discriminant)");
+            node_var = codegen_undefined_behaviour(node_var, &Bool::from_bool(solver.get_context(), true), &instruction, description, solver, analysis, panic_target_node);
+        }
+        InstructionOpcode::Load | InstructionOpcode::Store => {
+            node_var = codegen_memory_access_checks(module, node_var, &instruction, function, solver, analysis, namespace, panic_target_node);
+        }
+        InstructionOpcode::GetElementPtr => {
+            node_var = codegen_address_definition(module, node_var, &instruction, solver, analysis, namespace, return_register);
+            node_var = codegen_inbounds_check(module, node_var, &instruction, function, solver, analysis, namespace, panic_target_node);
+        }
+        _ => {
+            node_var = codegen_address_definition(module, node_var, &instruction, solver, analysis, namespace, return_register);
+        }
+    }
+    return node_var;
+}
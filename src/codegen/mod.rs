@@ -2,3 +2,8 @@ pub mod codegen_function;
 pub mod codegen_basic_block;
 pub mod codegen_instruction;
 pub mod codegen_call;
+pub mod codegen_intrinsic;
+pub mod codegen_global;
+pub mod codegen_undefined_behaviour;
+pub mod codegen_heap;
+pub mod codegen_str;
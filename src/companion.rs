@@ -0,0 +1,62 @@
+use std::fs;
+use std::process::Command;
+
+use tracing::{error};
+
+use crate::symbolic_execution::{FileDropper, get_temp_file_name};
+
+
+pub const COMPANION_CRATE_NAME: &str = "wombat";
+pub const COMPANION_ANY_PATH: &str = "wombat::any";
+pub const COMPANION_ASSUME_PATH: &str = "wombat::assume";
+pub const COMPANION_ASSERT_PATH: &str = "wombat::assert";
+
+/// The companion crate is embedded so that analyzed files can use it wherever the analyzer is installed
+const COMPANION_CRATE_SOURCE: &str = include_str!("../wombat/src/lib.rs");
+
+
+pub fn is_companion_call(function_path: &str) -> bool {
+    return function_path == COMPANION_ANY_PATH || function_path == COMPANION_ASSUME_PATH || function_path == COMPANION_ASSERT_PATH;
+}
+
+
+pub fn get_companion_rlib_file_name(file_name: &String) -> String {
+    //! Name of the companion crate library compiled for the given file
+    let temp_file_name = get_temp_file_name(file_name);
+    return format!("{}_{}.rlib", &temp_file_name[0..temp_file_name.rfind('.').unwrap_or(temp_file_name.len())], COMPANION_CRATE_NAME);
+}
+
+
+pub fn get_companion_crate_args(file_name: &String, target: Option<&str>) -> Option<Vec<String>> {
+    //! Compiles the companion crate if the file uses it, returning the rustc arguments linking it (empty if unused)
+    let source_result = fs::read_to_string(file_name);
+    if source_result.is_err() {
+        error!("{:?} is an invalid file. Please provide a valid file.", file_name);
+        return None;
+    }
+    if !source_result.unwrap().contains(&format!("{}::", COMPANION_CRATE_NAME)) {
+        return Some(Vec::new());
+    }
+
+    let companion_rlib_file_name = get_companion_rlib_file_name(file_name);
+    let companion_source_file_name = format!("{}.rs", &companion_rlib_file_name[0..companion_rlib_file_name.rfind('.').unwrap()]);
+    fs::write(&companion_source_file_name, COMPANION_CRATE_SOURCE).expect("Failed to write file!");
+    let _companion_source_file_dropper = FileDropper {
+        file_name: &companion_source_file_name,
+    };
+
+    let target_args = match target {
+        Some(target) => vec!["--target", target],
+        None => Vec::new(),
+    };
+    let status = Command::new("rustc")
+        .args(["--crate-type=rlib", "--edition=2021", "--crate-name", COMPANION_CRATE_NAME, &companion_source_file_name, "-o", &companion_rlib_file_name])
+        .args(target_args)
+        .status()
+        .expect("Failed to compile the companion crate!");
+    if !status.success() {
+        error!("Failed to compile the {} companion crate for {:?}", COMPANION_CRATE_NAME, file_name);
+        return None;
+    }
+    return Some(vec![String::from("--extern"), format!("{}={}", COMPANION_CRATE_NAME, companion_rlib_file_name)]);
+}
@@ -0,0 +1,147 @@
+use std::fs;
+
+use tracing::{debug, error};
+
+use inkwell::types::{AnyType, AnyTypeEnum, BasicTypeEnum};
+use inkwell::values::FunctionValue;
+
+use z3::Context as Z3Context;
+use z3::ast::Bool;
+
+use crate::expression::{encode_condition, parse_expression, ExpressionVariable, ExpressionVariables};
+use crate::parameter_domain::get_source_parameter_types;
+use crate::symbolic_execution::{get_temp_file_name, MAIN_FUNCTION_NAMESPACE, MAIN_FUNCTION_RETURN_REGISTER};
+use crate::utils::function_utils::{get_defined_function_name, get_source_signature, strip_function_hash};
+
+
+pub const REQUIRES_ATTRIBUTE_PREFIX: &str = "#[wombat::requires(";
+pub const ENSURES_ATTRIBUTE_PREFIX: &str = "#[wombat::ensures(";
+pub const RETURN_VALUE_NAME: &str = "ret";
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionContract {
+    /// Conditions assumed on the arguments
+    pub requires: Vec<String>,
+    /// Conditions checked on the arguments and the return value `ret` whenever the function returns
+    pub ensures: Vec<String>,
+}
+
+
+fn get_attribute_argument(line: &str, attribute_prefix: &str) -> Option<String> {
+    let trimmed_line = line.trim();
+    if !trimmed_line.starts_with(attribute_prefix) || !trimmed_line.ends_with(")]") {
+        return None;
+    }
+    return Some(String::from(&trimmed_line[attribute_prefix.len()..trimmed_line.len() - ")]".len()]));
+}
+
+
+pub fn preprocess_contract_source(source: &str, function_name: &str) -> (String, FunctionContract) {
+    //! Blanks out the contract attributes, which rustc does not know, keeping the line numbers of the source
+    //! Returns the compilable source and the contract of the given function
+    let mut contract = FunctionContract::default();
+    let mut pending_contract = FunctionContract::default();
+    let mut preprocessed_lines = Vec::<String>::new();
+    for line in source.lines() {
+        if let Some(condition) = get_attribute_argument(line, REQUIRES_ATTRIBUTE_PREFIX) {
+            pending_contract.requires.push(condition);
+            preprocessed_lines.push(String::new());
+            continue;
+        }
+        if let Some(condition) = get_attribute_argument(line, ENSURES_ATTRIBUTE_PREFIX) {
+            pending_contract.ensures.push(condition);
+            preprocessed_lines.push(String::new());
+            continue;
+        }
+        if let Some(defined_function_name) = get_defined_function_name(line.trim()) {
+            if defined_function_name == function_name {
+                contract = pending_contract.clone();
+            }
+            pending_contract = FunctionContract::default();
+        }
+        preprocessed_lines.push(String::from(line));
+    }
+    return (preprocessed_lines.join("\n"), contract);
+}
+
+
+pub fn prepare_source_file(file_name: &String, function_name: &String) -> Option<(String, FunctionContract)> {
+    //! Returns the name of the file to compile (a temporary file if contracts had to be removed) and the contract of the function
+    let source_result = fs::read_to_string(file_name);
+    if source_result.is_err() {
+        error!("{:?} is an invalid file. Please provide a valid file.", file_name);
+        return None;
+    }
+    let source = source_result.unwrap();
+    if !source.contains(REQUIRES_ATTRIBUTE_PREFIX) && !source.contains(ENSURES_ATTRIBUTE_PREFIX) {
+        return Some((file_name.clone(), FunctionContract::default()));
+    }
+
+    // Contracts apply to all instances of a generic function
+    let (preprocessed_source, contract) = preprocess_contract_source(&source, strip_function_hash(function_name));
+    debug!("Contract of {}: {:?}", function_name, contract);
+    let temp_source_file_name = get_temp_file_name(file_name);
+    fs::write(&temp_source_file_name, preprocessed_source).expect("Failed to write file!");
+    return Some((temp_source_file_name, contract));
+}
+
+
+fn is_unsigned_source_type(source_type: &str) -> bool {
+    return matches!(source_type.trim(), "u8" | "u16" | "u32" | "u64" | "u128" | "usize");
+}
+
+
+fn get_bit_width(var_type: &AnyTypeEnum) -> u32 {
+    //! Bit width of integer types, 0 for the other types (which are not read as unsigned)
+    if var_type.is_int_type() {
+        return var_type.into_int_type().get_bit_width();
+    }
+    return 0;
+}
+
+
+pub fn get_expression_variables(function: &FunctionValue, func_arg_names: &Vec<(String, String, BasicTypeEnum)>, source: &str, function_name: &str) -> ExpressionVariables {
+    //! Maps the source names of the arguments and `ret` to the Z3 constants holding their values, with their signedness from the source types
+    let source_function_name = strip_function_hash(function_name).rsplit("::").next().unwrap();
+    let parameter_types = get_source_parameter_types(source, source_function_name);
+    let source_return_type = get_source_signature(source, function_name)
+        .and_then(|signature| signature.rsplit_once("->").map(|(_, return_type)| return_type.trim().to_string()));
+
+    let mut variables = ExpressionVariables::new();
+    for (arg_name, z3_name, var_type) in func_arg_names {
+        let arg_name_without_namespace_and_percent = arg_name[MAIN_FUNCTION_NAMESPACE.len()..].replace("%", "");
+        let is_unsigned = parameter_types.get(&arg_name_without_namespace_and_percent).map_or(false, |source_type| is_unsigned_source_type(source_type));
+        variables.insert(arg_name_without_namespace_and_percent, ExpressionVariable {
+            z3_name: z3_name.clone(),
+            is_bool: var_type.to_string().eq("\"i1\""),
+            is_unsigned,
+            bit_width: get_bit_width(&var_type.as_any_type_enum()),
+        });
+    }
+    if let Some(return_type) = function.get_type().get_return_type() {
+        variables.insert(String::from(RETURN_VALUE_NAME), ExpressionVariable {
+            z3_name: String::from(MAIN_FUNCTION_RETURN_REGISTER),
+            is_bool: return_type.to_string().eq("\"i1\""),
+            is_unsigned: source_return_type.as_deref().map_or(false, is_unsigned_source_type),
+            bit_width: get_bit_width(&return_type.as_any_type_enum()),
+        });
+    }
+    return variables;
+}
+
+
+pub fn encode_contract_conditions<'a>(ctx: &'a Z3Context, conditions: &Vec<String>, variables: &ExpressionVariables) -> Option<Bool<'a>> {
+    //! Conjunction of the conditions, None if any of them is invalid
+    let mut conjunction = Bool::from_bool(ctx, true);
+    for condition in conditions {
+        match parse_expression(condition).and_then(|expression| encode_condition(ctx, &expression, variables)) {
+            Ok(encoded_condition) => conjunction = Bool::and(ctx, &[&conjunction, &encoded_condition]),
+            Err(message) => {
+                error!("Invalid condition {:?}: {}", condition, message);
+                return None;
+            }
+        }
+    }
+    return Some(conjunction);
+}
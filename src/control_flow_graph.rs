@@ -2,12 +2,26 @@ use std::collections::{HashMap, HashSet};
 
 use tracing::{warn};
 
-use inkwell::values::{FunctionValue, InstructionOpcode};
+use inkwell::values::{FunctionValue, InstructionOpcode, InstructionValue};
 
-use crate::symbolic_execution::COMMON_END_NODE;
+
+pub fn get_invoke_destinations(terminator: &InstructionValue, namespace: &str) -> Option<(String, String)> {
+    //! Normal and unwind destinations of an invoke (ex: `invoke void @f() to label %bb1 unwind label %cleanup` -> `bb1`, `cleanup`)
+    if terminator.get_opcode() != InstructionOpcode::Invoke {
+        return None;
+    }
+    // The destinations precede the called function, which is the last operand
+    let num_operands = terminator.get_num_operands();
+    let normal_basic_block = terminator.get_operand(num_operands - 3).unwrap().right().unwrap();
+    let unwind_basic_block = terminator.get_operand(num_operands - 2).unwrap().right().unwrap();
+    return Some((
+        format!("{}{}", namespace, normal_basic_block.get_name().to_str().unwrap()),
+        format!("{}{}", namespace, unwind_basic_block.get_name().to_str().unwrap()),
+    ));
+}
 
 
-pub fn get_forward_edges(function: &FunctionValue, namespace: &str, return_target_node: &str) -> HashMap<String, HashSet<String>> {
+pub fn get_forward_edges(function: &FunctionValue, namespace: &str, return_target_node: &str, panic_target_node: &str) -> HashMap<String, HashSet<String>> {
     let mut all_edges = HashMap::new();
     for bb in function.get_basic_blocks() {
         let mut node_edges = HashSet::new();
@@ -47,16 +61,25 @@ pub fn get_forward_edges(function: &FunctionValue, namespace: &str, return_targe
                     }
                 }
                 InstructionOpcode::IndirectBr => {
-                    warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
+                    // The first operand is the address branched to, followed by the possible destinations
+                    for operand in 1..num_operands {
+                        let successor_basic_block = terminator.get_operand(operand).unwrap().right().unwrap();
+                        let successor_basic_block_name = String::from(format!("{}{}", namespace, successor_basic_block.get_name().to_str().unwrap()));
+                        node_edges.insert(String::from(successor_basic_block_name));
+                    }
                 }
                 InstructionOpcode::Invoke => {
-                    warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
+                    // The unwind destination is only entered when the invoked function panics
+                    let (normal_basic_block_name, unwind_basic_block_name) = get_invoke_destinations(&terminator, namespace).unwrap();
+                    node_edges.insert(normal_basic_block_name);
+                    node_edges.insert(unwind_basic_block_name);
                 }
                 InstructionOpcode::CallBr => {
                     warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
                 }
                 InstructionOpcode::Resume => {
-                    warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
+                    // Unwinding continues in the caller once the cleanup is done
+                    node_edges.insert(String::from(panic_target_node));
                 }
                 InstructionOpcode::CatchSwitch => {
                     warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
@@ -68,7 +91,7 @@ pub fn get_forward_edges(function: &FunctionValue, namespace: &str, return_targe
                     warn!("Support for terminator opcode {:?} is not yet implemented for edge generation", opcode);
                 }
                 InstructionOpcode::Unreachable => {
-                    node_edges.insert(String::from(COMMON_END_NODE));
+                    node_edges.insert(String::from(panic_target_node));
                 }
                 _ => {
                     warn!("Opcode {:?} is not supported as a terminator for edge generation", opcode);
@@ -83,8 +106,8 @@ pub fn get_forward_edges(function: &FunctionValue, namespace: &str, return_targe
 }
 
 
-pub fn get_backward_edges(function: &FunctionValue, namespace: &str, return_target_node: &str) -> HashMap<String, HashSet<String>> {
-    let all_forward_edges = get_forward_edges(function, namespace, return_target_node);
+pub fn get_backward_edges(function: &FunctionValue, namespace: &str, return_target_node: &str, panic_target_node: &str) -> HashMap<String, HashSet<String>> {
+    let all_forward_edges = get_forward_edges(function, namespace, return_target_node, panic_target_node);
     let mut all_backward_edges = HashMap::new();
     for bb in function.get_basic_blocks() {
         let basic_block_name = String::from(format!("{}{}", namespace, bb.get_name().to_str().unwrap()));
@@ -101,9 +124,9 @@ pub fn get_backward_edges(function: &FunctionValue, namespace: &str, return_targ
 }
 
 
-pub fn forward_topological_sort(function: &FunctionValue, namespace: &str, return_target_node: &str) -> Vec<String> {
-    let forward_edges = get_forward_edges(function, namespace, return_target_node);
-    let backward_edges = get_backward_edges(function, namespace, return_target_node);
+pub fn forward_topological_sort(function: &FunctionValue, namespace: &str, return_target_node: &str, panic_target_node: &str) -> Vec<String> {
+    let forward_edges = get_forward_edges(function, namespace, return_target_node, panic_target_node);
+    let backward_edges = get_backward_edges(function, namespace, return_target_node, panic_target_node);
     let mut sorted = Vec::new();
     let mut unsorted = Vec::new();
     for bb in function.get_basic_blocks() {
@@ -153,8 +176,116 @@ pub fn forward_topological_sort(function: &FunctionValue, namespace: &str, retur
 }
 
 
-pub fn backward_topological_sort(function: &FunctionValue, namespace: &str, return_target_node: &str) -> Vec<String> {
-    let mut sorted = forward_topological_sort(function, namespace, return_target_node);
+pub fn backward_topological_sort(function: &FunctionValue, namespace: &str, return_target_node: &str, panic_target_node: &str) -> Vec<String> {
+    let mut sorted = forward_topological_sort(function, namespace, return_target_node, panic_target_node);
     sorted.reverse();
     return sorted;
-}
\ No newline at end of file
+}
+
+fn is_block_reachable(forward_edges: &HashMap<String, HashSet<String>>, start: &str, target: &str) -> bool {
+    //! Whether the target block is reachable from the start block
+    let mut visited = HashSet::new();
+    let mut pending = vec![String::from(start)];
+    while let Some(node) = pending.pop() {
+        if node == target {
+            return true;
+        }
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        if let Some(successors) = forward_edges.get(&node) {
+            pending.extend(successors.iter().cloned());
+        }
+    }
+    return false;
+}
+
+
+fn is_before_in_block(earlier: &InstructionValue, later: &InstructionValue) -> bool {
+    let mut next_instruction = earlier.get_next_instruction();
+    while let Some(instruction) = next_instruction {
+        if instruction == *later {
+            return true;
+        }
+        next_instruction = instruction.get_next_instruction();
+    }
+    return false;
+}
+
+
+/// Control flow graph of a function (without namespace) with its dominators, built once and queried for every instruction
+pub struct FunctionGraph {
+    /// Successors of each basic block
+    forward_edges: HashMap<String, HashSet<String>>,
+    /// Basic blocks dominating each basic block, itself included (all blocks for the blocks unreachable from the entry)
+    dominators: HashMap<String, HashSet<String>>,
+}
+
+impl FunctionGraph {
+    pub fn new(function: &FunctionValue) -> FunctionGraph {
+        //! Dominators are the fixpoint of `dom(b) = {b} ∪ ⋂ dom(p)` over the predecessors `p` of `b`
+        let forward_edges = get_forward_edges(function, "", "", "");
+        let block_names: Vec<String> = function.get_basic_blocks().iter().map(|bb| String::from(bb.get_name().to_str().unwrap())).collect();
+        let all_blocks: HashSet<String> = block_names.iter().cloned().collect();
+        let mut predecessors = HashMap::<&str, Vec<&str>>::new();
+        for (node, successors) in &forward_edges {
+            for successor in successors {
+                predecessors.entry(successor.as_str()).or_insert_with(Vec::new).push(node.as_str());
+            }
+        }
+
+        let mut dominators = HashMap::<String, HashSet<String>>::new();
+        for (index, block_name) in block_names.iter().enumerate() {
+            let initial_dominators = if index == 0 { HashSet::from([block_name.clone()]) } else { all_blocks.clone() };
+            dominators.insert(block_name.clone(), initial_dominators);
+        }
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for block_name in block_names.iter().skip(1) {
+                let mut new_dominators: Option<HashSet<String>> = None;
+                for predecessor in predecessors.get(block_name.as_str()).into_iter().flatten() {
+                    let predecessor_dominators = match dominators.get(*predecessor) {
+                        Some(predecessor_dominators) => predecessor_dominators,
+                        None => continue,
+                    };
+                    new_dominators = Some(match new_dominators {
+                        Some(new_dominators) => new_dominators.intersection(predecessor_dominators).cloned().collect(),
+                        None => predecessor_dominators.clone(),
+                    });
+                }
+                let mut new_dominators = new_dominators.unwrap_or_else(|| all_blocks.clone());
+                new_dominators.insert(block_name.clone());
+                if new_dominators != dominators[block_name] {
+                    dominators.insert(block_name.clone(), new_dominators);
+                    is_changed = true;
+                }
+            }
+        }
+        return FunctionGraph { forward_edges, dominators };
+    }
+
+    pub fn dominates(&self, dominator: &InstructionValue, instruction: &InstructionValue) -> bool {
+        //! Whether every path from the entry of the function to the instruction executes the dominator first
+        let dominator_block = dominator.get_parent().unwrap();
+        let block = instruction.get_parent().unwrap();
+        if dominator_block == block {
+            return is_before_in_block(dominator, instruction);
+        }
+        return self.dominators.get(block.get_name().to_str().unwrap())
+            .map_or(false, |dominators| dominators.contains(dominator_block.get_name().to_str().unwrap()));
+    }
+
+    pub fn may_precede(&self, earlier: &InstructionValue, later: &InstructionValue) -> bool {
+        //! Whether some path of the function executes the earlier instruction and then the later one
+        let earlier_block = earlier.get_parent().unwrap();
+        let later_block = later.get_parent().unwrap();
+        if earlier_block == later_block && is_before_in_block(earlier, later) {
+            return true;
+        }
+        let later_block_name = later_block.get_name().to_str().unwrap();
+        return self.forward_edges.get(earlier_block.get_name().to_str().unwrap()).map_or(false, |successors| {
+            successors.iter().any(|successor| is_block_reachable(&self.forward_edges, successor, later_block_name))
+        });
+    }
+}
@@ -0,0 +1,362 @@
+use tracing::{debug, error};
+
+use inkwell::context::Context as InkwellContext;
+use inkwell::module::{Module as InkwellModule};
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::FunctionValue;
+
+use z3::{Model, Solver};
+use z3::Context as Z3Context;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::analysis_context::AnalysisContext;
+use crate::codegen::codegen_function::codegen_function;
+use crate::module_loader::load_module;
+use crate::parameter_domain::get_all_parameter_ranges;
+use crate::soundness::SoundnessImpact;
+use crate::symbolic_execution::{
+    AnalysisOptions, Verdict, debug_symbol_table, constrain_function_parameters, convert_to_dsa, get_panic_var_name, get_solver, get_solver_config, get_verdict,
+    is_refused_by_strict_mode, print_soundness_report, COMMON_END_NODE, MAIN_FUNCTION_RETURN_REGISTER
+};
+use crate::utils::function_utils::{get_function_argument_names, get_function_by_name};
+use crate::utils::var_utils::get_var_name;
+
+
+pub const LHS_NAMESPACE: &str = "lhs.";
+pub const RHS_NAMESPACE: &str = "rhs.";
+pub const DEBUG_NAMESPACE: &str = "debug.";
+pub const RELEASE_NAMESPACE: &str = "release.";
+
+pub const DEBUG_COMPILE_ARGS: &[&str] = &["-C", "opt-level=0"];
+pub const RELEASE_COMPILE_ARGS: &[&str] = &["-C", "opt-level=3", "-C", "overflow-checks=off"];
+
+
+fn get_value_string(model: &Model, solver: &Solver, z3_name: &str, var_type: &BasicTypeEnum) -> Option<String> {
+    let value_string = if var_type.to_string().eq("\"i1\"") {
+        format!("{:?}", model.eval(&Bool::new_const(solver.get_context(), z3_name), true).unwrap())
+    } else if var_type.is_int_type() {
+        format!("{:?}", model.eval(&Int::new_const(solver.get_context(), z3_name), true).unwrap())
+    } else {
+        return None;
+    };
+    return Some(value_string.replace("(", "").replace(")", "").replace(" ", ""));
+}
+
+
+fn get_outcome_string(model: &Model, solver: &Solver, function: &FunctionValue, namespace: &str) -> String {
+    let end_node = format!("{}{}", namespace, COMMON_END_NODE);
+    let panic_var = Bool::new_const(solver.get_context(), get_panic_var_name(&end_node));
+    if model.eval(&panic_var, true).unwrap().as_bool() == Some(true) {
+        return String::from("panics");
+    }
+    let return_type = function.get_type().get_return_type();
+    if return_type.is_none() {
+        return String::from("returns");
+    }
+    let return_register = format!("{}{}", namespace, MAIN_FUNCTION_RETURN_REGISTER);
+    return match get_value_string(model, solver, &return_register, &return_type.unwrap()) {
+        Some(value_string) => format!("returns {}", value_string),
+        None => String::from("returns an unsupported value"),
+    };
+}
+
+
+fn encode_function<'a>(module: &InkwellModule, function: &FunctionValue, solver: &'a Solver, analysis: &AnalysisContext, namespace: &str, function_name: &String) -> (Bool<'a>, Bool<'a>) {
+    //! Encodes the function in its own namespace, returning its panic variable and start node
+    //! The end node is both the return and the panic target, and is falsified so that the start node is only false along the executed path
+    let end_node = format!("{}{}", namespace, COMMON_END_NODE);
+    let return_register = format!("{}{}", namespace, MAIN_FUNCTION_RETURN_REGISTER);
+    let call_stack = function.get_name().to_str().unwrap();
+    codegen_function(module, function, solver, analysis, namespace, call_stack, &end_node, &end_node, &return_register);
+    constrain_function_parameters(module, function, solver, analysis, namespace, function_name);
+
+    solver.assert(&Bool::new_const(solver.get_context(), end_node.as_str()).not());
+    let start_node = function.get_first_basic_block().unwrap();
+    let start_node_var = Bool::new_const(solver.get_context(), format!("{}{}", namespace, start_node.get_name().to_str().unwrap()));
+    let panic_var = Bool::new_const(solver.get_context(), get_panic_var_name(&end_node));
+    return (panic_var, start_node_var);
+}
+
+
+fn get_return_mismatch<'a>(function: &FunctionValue, solver: &'a Solver, analysis: &AnalysisContext) -> Bool<'a> {
+    //! Condition under which the return values of both functions differ
+    let return_type = function.get_type().get_return_type();
+    if return_type.is_none() {
+        return Bool::from_bool(solver.get_context(), false);
+    }
+    let return_type = return_type.unwrap();
+    let lhs_return_register = format!("{}{}", LHS_NAMESPACE, MAIN_FUNCTION_RETURN_REGISTER);
+    let rhs_return_register = format!("{}{}", RHS_NAMESPACE, MAIN_FUNCTION_RETURN_REGISTER);
+    if return_type.to_string().eq("\"i1\"") {
+        let lhs_return = Bool::new_const(solver.get_context(), lhs_return_register);
+        let rhs_return = Bool::new_const(solver.get_context(), rhs_return_register);
+        return lhs_return._eq(&rhs_return).not();
+    } else if return_type.is_int_type() {
+        let lhs_return = Int::new_const(solver.get_context(), lhs_return_register);
+        let rhs_return = Int::new_const(solver.get_context(), rhs_return_register);
+        return lhs_return._eq(&rhs_return).not();
+    }
+    analysis.ledger.record(SoundnessImpact::Unsound, format!("Currently unsupported return type {:?} is not compared", return_type.to_string()));
+    return Bool::from_bool(solver.get_context(), false);
+}
+
+
+fn get_value_equality<'a>(solver: &'a Solver, name: &String, other_name: &String, is_bool: bool) -> Bool<'a> {
+    if is_bool {
+        return Bool::new_const(solver.get_context(), name.as_str())._eq(&Bool::new_const(solver.get_context(), other_name.as_str()));
+    }
+    return Int::new_const(solver.get_context(), name.as_str())._eq(&Int::new_const(solver.get_context(), other_name.as_str()));
+}
+
+
+fn constrain_external_calls(solver: &Solver, analysis: &AnalysisContext, namespace: &str, other_namespace: &str) -> () {
+    //! External calls are havocked independently, but the same call with the same arguments gives the same result in both functions
+    let external_calls = analysis.external_calls.borrow();
+    for call in external_calls.iter().filter(|call| call.namespace.starts_with(namespace)) {
+        for other_call in external_calls.iter().filter(|other_call| other_call.namespace.starts_with(other_namespace)) {
+            let is_same_signature = call.arguments.iter().map(|(_, is_bool)| is_bool).eq(other_call.arguments.iter().map(|(_, is_bool)| is_bool));
+            if call.function_name.ne(&other_call.function_name) || !is_same_signature {
+                continue;
+            }
+            let equal_arguments: Vec<Bool> = call.arguments.iter().zip(other_call.arguments.iter())
+                .map(|((name, is_bool), (other_name, _))| get_value_equality(solver, name, other_name, *is_bool))
+                .collect();
+            let mut equal_results = Vec::<Bool>::new();
+            if let (Some((name, is_bool)), Some((other_name, other_is_bool))) = (&call.return_value, &other_call.return_value) {
+                if is_bool == other_is_bool {
+                    equal_results.push(get_value_equality(solver, name, other_name, *is_bool));
+                }
+            }
+            if let (Some(name), Some(other_name)) = (&call.may_panic_var_name, &other_call.may_panic_var_name) {
+                equal_results.push(get_value_equality(solver, name, other_name, true));
+            }
+            let equal_arguments_refs: Vec<&Bool> = equal_arguments.iter().collect();
+            let equal_results_refs: Vec<&Bool> = equal_results.iter().collect();
+            solver.assert(&Bool::and(solver.get_context(), &equal_arguments_refs).implies(&Bool::and(solver.get_context(), &equal_results_refs)));
+        }
+    }
+}
+
+
+pub fn equivalence_checking(file_name: &String, function_name: &String, other_file_name: &String, other_function_name: &String, options: &AnalysisOptions) -> Option<Verdict> {
+    //! Checks that both functions panic on the same inputs and return the same values otherwise
+    //! The verdict is safe when they are equivalent and unsafe when a distinguishing input exists
+    let context = InkwellContext::create();
+
+    let lhs_module_option = load_module(&context, file_name, function_name, options, &[]);
+    if lhs_module_option.is_none() {
+        return None;
+    }
+    let lhs_module = lhs_module_option.unwrap();
+    // Functions of the same file are taken from a single module
+    let rhs_module_option = if other_file_name.ne(file_name) {
+        let rhs_module_option = load_module(&context, other_file_name, other_function_name, options, &[]);
+        if rhs_module_option.is_none() {
+            return None;
+        }
+        rhs_module_option
+    } else {
+        None
+    };
+    let rhs_module = rhs_module_option.as_ref().unwrap_or(&lhs_module);
+
+    let mut analysis = AnalysisContext::new(options);
+    analysis.stubs.extend(lhs_module.stubs.clone());
+    analysis.stubs.extend(rhs_module.stubs.clone());
+
+    let lhs_function_option = get_function_by_name(&lhs_module.module, &format!("{}::{}", lhs_module.module_name, function_name));
+    let rhs_function_option = get_function_by_name(&rhs_module.module, &format!("{}::{}", rhs_module.module_name, other_function_name));
+    if lhs_function_option.is_none() || rhs_function_option.is_none() {
+        error!("Functions {:?} and {:?} must both be defined", function_name, other_function_name);
+        return None;
+    }
+    let lhs_function = lhs_function_option.unwrap();
+    let rhs_function = rhs_function_option.unwrap();
+    if lhs_function.get_type() != rhs_function.get_type() {
+        error!("Functions {:?} and {:?} have different signatures", function_name, other_function_name);
+        return None;
+    }
+
+    let cfg = get_solver_config(options);
+    let ctx = Z3Context::new(&cfg);
+    let solver = get_solver(&ctx, options);
+
+    // Save function argument names before removing store/alloca instructions
+    let func_arg_names = get_function_argument_names(lhs_function, &solver, &analysis, LHS_NAMESPACE);
+
+    // Parameter ranges are read from loads which are removed when converting to DSA
+    analysis.parameter_ranges.extend(get_all_parameter_ranges(&lhs_module.module));
+    analysis.parameter_ranges.extend(get_all_parameter_ranges(&rhs_module.module));
+    convert_to_dsa(&lhs_module.module, &analysis);
+    if rhs_module_option.is_some() {
+        convert_to_dsa(&rhs_module.module, &analysis);
+    }
+
+    let (lhs_panic_var, lhs_start_node_var) = encode_function(&lhs_module.module, &lhs_function, &solver, &analysis, LHS_NAMESPACE, function_name);
+    let (rhs_panic_var, rhs_start_node_var) = encode_function(&rhs_module.module, &rhs_function, &solver, &analysis, RHS_NAMESPACE, other_function_name);
+
+    // Both functions are called with the same arguments
+    let lhs_params = lhs_function.get_params();
+    let rhs_params = rhs_function.get_params();
+    for (lhs_param, rhs_param) in lhs_params.iter().zip(rhs_params.iter()) {
+        let lhs_param_name = get_var_name(lhs_param, &solver, &analysis, LHS_NAMESPACE);
+        let rhs_param_name = get_var_name(rhs_param, &solver, &analysis, RHS_NAMESPACE);
+        if lhs_param.get_type().to_string().eq("\"i1\"") {
+            solver.assert(&get_value_equality(&solver, &lhs_param_name, &rhs_param_name, true));
+        } else if lhs_param.get_type().is_int_type() {
+            solver.assert(&get_value_equality(&solver, &lhs_param_name, &rhs_param_name, false));
+        }
+    }
+
+    constrain_external_calls(&solver, &analysis, LHS_NAMESPACE, RHS_NAMESPACE);
+
+    if is_refused_by_strict_mode(&analysis) {
+        return None;
+    }
+
+    // Both functions execute, and either only one of them panics or they return different values
+    solver.assert(&lhs_start_node_var.not());
+    solver.assert(&rhs_start_node_var.not());
+    let panic_mismatch = lhs_panic_var._eq(&rhs_panic_var).not();
+    let return_mismatch = Bool::and(solver.get_context(), &[&lhs_panic_var.not(), &get_return_mismatch(&lhs_function, &solver, &analysis)]);
+    solver.assert(&Bool::or(solver.get_context(), &[&panic_mismatch, &return_mismatch]));
+
+    debug!("{}", format!("\nSolver:\n{:?}", solver));
+
+    let satisfiability = solver.check();
+
+    let verdict = get_verdict(&solver, &analysis, satisfiability);
+    let equivalence_string = match &verdict {
+        Verdict::Safe { .. } => String::from("equivalent"),
+        Verdict::Unsafe => String::from("not equivalent"),
+        Verdict::Unknown(reason) => format!("unknown ({})", reason),
+    };
+    println!("\nEquivalence of {} and {}: {}", function_name, other_function_name, equivalence_string);
+    print_soundness_report(&analysis);
+
+    // Exhibit a distinguishing input if the functions differ
+    if verdict == Verdict::Unsafe {
+        let model = solver.get_model().unwrap();
+        debug!("\n{:?}", model);
+        debug_symbol_table(&analysis);
+        println!("\nDistinguishing values:");
+        for (arg_name, z3_name, var_type) in &func_arg_names {
+            let arg_name_without_namespace_and_percent = arg_name[LHS_NAMESPACE.len()..].replace("%", "");
+            if let Some(value_string) = get_value_string(&model, &solver, z3_name, var_type) {
+                println!("\t{:?} = {}", &arg_name_without_namespace_and_percent, value_string);
+            }
+        }
+        println!("\n{} {}", function_name, get_outcome_string(&model, &solver, &lhs_function, LHS_NAMESPACE));
+        println!("{} {}", other_function_name, get_outcome_string(&model, &solver, &rhs_function, RHS_NAMESPACE));
+    }
+
+    return Some(verdict);
+}
+
+
+pub fn differential_checking(file_name: &String, function_name: &String, options: &AnalysisOptions) -> Option<Verdict> {
+    //! Looks for inputs on which the debug build of the function panics while the release build returns (ex: a silently wrapped overflow)
+    //! The verdict is safe when there are none and unsafe when such an input exists
+    let context = InkwellContext::create();
+
+    // Both builds are loaded one after the other, as they share the same bytecode file
+    let debug_module_option = load_module(&context, file_name, function_name, options, DEBUG_COMPILE_ARGS);
+    if debug_module_option.is_none() {
+        return None;
+    }
+    let debug_module = debug_module_option.unwrap();
+    let release_module_option = load_module(&context, file_name, function_name, options, RELEASE_COMPILE_ARGS);
+    if release_module_option.is_none() {
+        return None;
+    }
+    let release_module = release_module_option.unwrap();
+
+    let mut analysis = AnalysisContext::new(options);
+    analysis.stubs.extend(debug_module.stubs.clone());
+    analysis.stubs.extend(release_module.stubs.clone());
+
+    let debug_function_option = get_function_by_name(&debug_module.module, &format!("{}::{}", debug_module.module_name, function_name));
+    let release_function_option = get_function_by_name(&release_module.module, &format!("{}::{}", release_module.module_name, function_name));
+    if debug_function_option.is_none() || release_function_option.is_none() {
+        error!("Function {:?} must be defined in both the debug and the release build", function_name);
+        return None;
+    }
+    let debug_function = debug_function_option.unwrap();
+    let release_function = release_function_option.unwrap();
+    if debug_function.get_params().len() != release_function.get_params().len() {
+        error!("Function {:?} has different parameters in the debug and the release build", function_name);
+        return None;
+    }
+
+    let cfg = get_solver_config(options);
+    let ctx = Z3Context::new(&cfg);
+    let solver = get_solver(&ctx, options);
+
+    // Save function argument names before removing store/alloca instructions
+    let func_arg_names = get_function_argument_names(debug_function, &solver, &analysis, DEBUG_NAMESPACE);
+
+    // Parameter ranges are read from loads which are removed when converting to DSA
+    analysis.parameter_ranges.extend(get_all_parameter_ranges(&release_module.module));
+    analysis.parameter_ranges.extend(get_all_parameter_ranges(&debug_module.module));
+    convert_to_dsa(&debug_module.module, &analysis);
+    convert_to_dsa(&release_module.module, &analysis);
+
+    let (debug_panic_var, debug_start_node_var) = encode_function(&debug_module.module, &debug_function, &solver, &analysis, DEBUG_NAMESPACE, function_name);
+    let (release_panic_var, release_start_node_var) = encode_function(&release_module.module, &release_function, &solver, &analysis, RELEASE_NAMESPACE, function_name);
+
+    // Both builds are called with the same arguments
+    let debug_params = debug_function.get_params();
+    let release_params = release_function.get_params();
+    for (debug_param, release_param) in debug_params.iter().zip(release_params.iter()) {
+        let debug_param_name = get_var_name(debug_param, &solver, &analysis, DEBUG_NAMESPACE);
+        let release_param_name = get_var_name(release_param, &solver, &analysis, RELEASE_NAMESPACE);
+        if debug_param.get_type().to_string().eq("\"i1\"") {
+            solver.assert(&get_value_equality(&solver, &debug_param_name, &release_param_name, true));
+        } else if debug_param.get_type().is_int_type() {
+            solver.assert(&get_value_equality(&solver, &debug_param_name, &release_param_name, false));
+        }
+    }
+
+    constrain_external_calls(&solver, &analysis, DEBUG_NAMESPACE, RELEASE_NAMESPACE);
+
+    if is_refused_by_strict_mode(&analysis) {
+        return None;
+    }
+
+    // Both builds execute, the debug build panics and the release build returns
+    solver.assert(&debug_start_node_var.not());
+    solver.assert(&release_start_node_var.not());
+    solver.assert(&debug_panic_var);
+    solver.assert(&release_panic_var.not());
+
+    debug!("{}", format!("\nSolver:\n{:?}", solver));
+
+    let satisfiability = solver.check();
+
+    let verdict = get_verdict(&solver, &analysis, satisfiability);
+    let differential_string = match &verdict {
+        Verdict::Safe { .. } => String::from("none"),
+        Verdict::Unsafe => String::from("found"),
+        Verdict::Unknown(reason) => format!("unknown ({})", reason),
+    };
+    println!("\nPanics only in the debug build of {}: {}", function_name, differential_string);
+    print_soundness_report(&analysis);
+
+    // Exhibit an input on which the release build silently carries on
+    if verdict == Verdict::Unsafe {
+        let model = solver.get_model().unwrap();
+        debug!("\n{:?}", model);
+        debug_symbol_table(&analysis);
+        println!("\nDistinguishing values:");
+        for (arg_name, z3_name, var_type) in &func_arg_names {
+            let arg_name_without_namespace_and_percent = arg_name[DEBUG_NAMESPACE.len()..].replace("%", "");
+            if let Some(value_string) = get_value_string(&model, &solver, z3_name, var_type) {
+                println!("\t{:?} = {}", &arg_name_without_namespace_and_percent, value_string);
+            }
+        }
+        println!("\nDebug build {}", get_outcome_string(&model, &solver, &debug_function, DEBUG_NAMESPACE));
+        println!("Release build {}", get_outcome_string(&model, &solver, &release_function, RELEASE_NAMESPACE));
+    }
+
+    return Some(verdict);
+}
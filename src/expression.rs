@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use z3::Context as Z3Context;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::utils::var_utils::get_unsigned_value;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,
+    Neg,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Implies,
+}
+
+
+/// Expressions over the arguments of a function and its return value `ret` (ex: `x > 0 && ret >= x`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    Integer(i64),
+    Boolean(bool),
+    Variable(String),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionVariable {
+    /// Name of the Z3 constant holding the value of the variable
+    pub z3_name: String,
+    pub is_bool: bool,
+    /// Whether the integer is unsigned in the source (ex: `u32`), as its encoding is the signed value of its bits
+    pub is_unsigned: bool,
+    /// Bit width of the integer (1 for booleans)
+    pub bit_width: u32,
+}
+
+pub type ExpressionVariables = HashMap<String, ExpressionVariable>;
+
+
+pub enum EncodedExpression<'a> {
+    Int(Int<'a>),
+    Bool(Bool<'a>),
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Integer(i64),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+/// Longest symbols first, so that `==>` is not read as `==` followed by `>`
+const SYMBOLS: &[&str] = &["==>", "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")"];
+
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::<Token>::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut index = 0;
+    'outer: while index < chars.len() {
+        let c = chars[index];
+        if c.is_whitespace() {
+            index += 1;
+        } else if c.is_ascii_digit() {
+            let start_index = index;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '_') {
+                index += 1;
+            }
+            let literal: String = chars[start_index..index].iter().filter(|c| **c != '_').collect();
+            match literal.parse::<i64>() {
+                Ok(value) => tokens.push(Token::Integer(value)),
+                Err(..) => return Err(format!("Integer literal {} does not fit 64 bits", literal)),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start_index = index;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            tokens.push(Token::Identifier(chars[start_index..index].iter().collect()));
+        } else {
+            for symbol in SYMBOLS {
+                let symbol_chars: Vec<char> = symbol.chars().collect();
+                if chars[index..].starts_with(&symbol_chars) {
+                    tokens.push(Token::Symbol(*symbol));
+                    index += symbol_chars.len();
+                    continue 'outer;
+                }
+            }
+            return Err(format!("Unexpected character {:?} at position {}", c, index));
+        }
+    }
+    return Ok(tokens);
+}
+
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek_symbol(&self) -> Option<&'static str> {
+        return match self.tokens.get(self.index) {
+            Some(Token::Symbol(symbol)) => Some(*symbol),
+            _ => None,
+        };
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.peek_symbol() != Some(symbol) {
+            return Err(format!("Expected {:?} but found {:?}", symbol, self.tokens.get(self.index)));
+        }
+        self.index += 1;
+        return Ok(());
+    }
+
+    fn parse_binary_level(&mut self, operators: &[(&str, BinaryOperator)], parse_operand: fn(&mut Parser) -> Result<Expression, String>) -> Result<Expression, String> {
+        //! Parses a left associative chain of the given operators
+        let mut lhs = parse_operand(self)?;
+        'outer: loop {
+            for (symbol, operator) in operators {
+                if self.peek_symbol() == Some(*symbol) {
+                    self.index += 1;
+                    let rhs = parse_operand(self)?;
+                    lhs = Expression::Binary(*operator, Box::new(lhs), Box::new(rhs));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_implication(&mut self) -> Result<Expression, String> {
+        //! Implications are right associative (ex: `a ==> b ==> c` is `a ==> (b ==> c)`)
+        let lhs = self.parse_disjunction()?;
+        if self.peek_symbol() == Some("==>") {
+            self.index += 1;
+            let rhs = self.parse_implication()?;
+            return Ok(Expression::Binary(BinaryOperator::Implies, Box::new(lhs), Box::new(rhs)));
+        }
+        return Ok(lhs);
+    }
+
+    fn parse_disjunction(&mut self) -> Result<Expression, String> {
+        return self.parse_binary_level(&[("||", BinaryOperator::Or)], Parser::parse_conjunction);
+    }
+
+    fn parse_conjunction(&mut self) -> Result<Expression, String> {
+        return self.parse_binary_level(&[("&&", BinaryOperator::And)], Parser::parse_comparison);
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, String> {
+        //! Comparisons do not chain (ex: `a < b < c` is rejected)
+        let operators = [
+            ("==", BinaryOperator::Eq), ("!=", BinaryOperator::Ne), ("<=", BinaryOperator::Le),
+            (">=", BinaryOperator::Ge), ("<", BinaryOperator::Lt), (">", BinaryOperator::Gt),
+        ];
+        let lhs = self.parse_sum()?;
+        for (symbol, operator) in operators {
+            if self.peek_symbol() == Some(symbol) {
+                self.index += 1;
+                let rhs = self.parse_sum()?;
+                return Ok(Expression::Binary(operator, Box::new(lhs), Box::new(rhs)));
+            }
+        }
+        return Ok(lhs);
+    }
+
+    fn parse_sum(&mut self) -> Result<Expression, String> {
+        return self.parse_binary_level(&[("+", BinaryOperator::Add), ("-", BinaryOperator::Sub)], Parser::parse_product);
+    }
+
+    fn parse_product(&mut self) -> Result<Expression, String> {
+        return self.parse_binary_level(&[("*", BinaryOperator::Mul), ("/", BinaryOperator::Div), ("%", BinaryOperator::Rem)], Parser::parse_unary);
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, String> {
+        if self.peek_symbol() == Some("!") {
+            self.index += 1;
+            return Ok(Expression::Unary(UnaryOperator::Not, Box::new(self.parse_unary()?)));
+        }
+        if self.peek_symbol() == Some("-") {
+            self.index += 1;
+            return Ok(Expression::Unary(UnaryOperator::Neg, Box::new(self.parse_unary()?)));
+        }
+        return self.parse_primary();
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, String> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        return match token {
+            Some(Token::Integer(value)) => Ok(Expression::Integer(value)),
+            Some(Token::Identifier(name)) if name == "true" => Ok(Expression::Boolean(true)),
+            Some(Token::Identifier(name)) if name == "false" => Ok(Expression::Boolean(false)),
+            Some(Token::Identifier(name)) => Ok(Expression::Variable(name)),
+            Some(Token::Symbol("(")) => {
+                let expression = self.parse_implication()?;
+                self.expect_symbol(")")?;
+                Ok(expression)
+            }
+            Some(token) => Err(format!("Unexpected token {:?}", token)),
+            None => Err(String::from("Unexpected end of expression")),
+        };
+    }
+}
+
+
+pub fn parse_expression(text: &str) -> Result<Expression, String> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        index: 0,
+    };
+    let expression = parser.parse_implication()?;
+    if parser.index < parser.tokens.len() {
+        return Err(format!("Unexpected token {:?} after the end of the expression", parser.tokens[parser.index]));
+    }
+    return Ok(expression);
+}
+
+
+fn encode_int<'a>(ctx: &'a Z3Context, expression: &Expression, variables: &ExpressionVariables) -> Result<Int<'a>, String> {
+    return match encode_expression(ctx, expression, variables)? {
+        EncodedExpression::Int(value) => Ok(value),
+        EncodedExpression::Bool(..) => Err(format!("Expected an integer but found the condition {:?}", expression)),
+    };
+}
+
+
+pub fn encode_condition<'a>(ctx: &'a Z3Context, expression: &Expression, variables: &ExpressionVariables) -> Result<Bool<'a>, String> {
+    return match encode_expression(ctx, expression, variables)? {
+        EncodedExpression::Bool(value) => Ok(value),
+        EncodedExpression::Int(..) => Err(format!("Expected a condition but found the integer {:?}", expression)),
+    };
+}
+
+
+fn encode_truncated_division<'a>(ctx: &'a Z3Context, lhs: &Int<'a>, rhs: &Int<'a>) -> Int<'a> {
+    //! Division rounding towards zero as in Rust (Z3 rounds towards negative infinity for positive divisors)
+    let is_lhs_negative = lhs.lt(&Int::from_i64(ctx, 0));
+    return is_lhs_negative.ite(&lhs.unary_minus().div(rhs).unary_minus(), &lhs.div(rhs));
+}
+
+
+pub fn encode_expression<'a>(ctx: &'a Z3Context, expression: &Expression, variables: &ExpressionVariables) -> Result<EncodedExpression<'a>, String> {
+    //! Integers are mathematical (they do not overflow) and division truncates towards zero
+    return match expression {
+        Expression::Integer(value) => Ok(EncodedExpression::Int(Int::from_i64(ctx, *value))),
+        Expression::Boolean(value) => Ok(EncodedExpression::Bool(Bool::from_bool(ctx, *value))),
+        Expression::Variable(name) => match variables.get(name) {
+            Some(variable) if variable.is_bool => Ok(EncodedExpression::Bool(Bool::new_const(ctx, variable.z3_name.as_str()))),
+            // Unsigned integers above the signed maximum are encoded as negative values (ex: `u32::MAX` as -1)
+            Some(variable) if variable.is_unsigned => Ok(EncodedExpression::Int(get_unsigned_value(ctx, &Int::new_const(ctx, variable.z3_name.as_str()), variable.bit_width))),
+            Some(variable) => Ok(EncodedExpression::Int(Int::new_const(ctx, variable.z3_name.as_str()))),
+            None => Err(format!("Unknown variable {:?}", name)),
+        },
+        Expression::Unary(UnaryOperator::Not, operand) => Ok(EncodedExpression::Bool(encode_condition(ctx, operand, variables)?.not())),
+        Expression::Unary(UnaryOperator::Neg, operand) => Ok(EncodedExpression::Int(encode_int(ctx, operand, variables)?.unary_minus())),
+        Expression::Binary(operator, lhs, rhs) => match operator {
+            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Implies => {
+                let lhs = encode_condition(ctx, lhs, variables)?;
+                let rhs = encode_condition(ctx, rhs, variables)?;
+                Ok(EncodedExpression::Bool(match operator {
+                    BinaryOperator::And => Bool::and(ctx, &[&lhs, &rhs]),
+                    BinaryOperator::Or => Bool::or(ctx, &[&lhs, &rhs]),
+                    _ => lhs.implies(&rhs),
+                }))
+            }
+            BinaryOperator::Eq | BinaryOperator::Ne => {
+                // Both integers and conditions can be compared for equality
+                let is_equal = match (encode_expression(ctx, lhs, variables)?, encode_expression(ctx, rhs, variables)?) {
+                    (EncodedExpression::Int(lhs), EncodedExpression::Int(rhs)) => lhs._eq(&rhs),
+                    (EncodedExpression::Bool(lhs), EncodedExpression::Bool(rhs)) => lhs._eq(&rhs),
+                    _ => return Err(format!("Mismatched types in comparison {:?}", expression)),
+                };
+                if *operator == BinaryOperator::Ne {
+                    return Ok(EncodedExpression::Bool(is_equal.not()));
+                }
+                Ok(EncodedExpression::Bool(is_equal))
+            }
+            BinaryOperator::Lt | BinaryOperator::Le | BinaryOperator::Gt | BinaryOperator::Ge => {
+                let lhs = encode_int(ctx, lhs, variables)?;
+                let rhs = encode_int(ctx, rhs, variables)?;
+                Ok(EncodedExpression::Bool(match operator {
+                    BinaryOperator::Lt => lhs.lt(&rhs),
+                    BinaryOperator::Le => lhs.le(&rhs),
+                    BinaryOperator::Gt => lhs.gt(&rhs),
+                    _ => lhs.ge(&rhs),
+                }))
+            }
+            BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Rem => {
+                let lhs = encode_int(ctx, lhs, variables)?;
+                let rhs = encode_int(ctx, rhs, variables)?;
+                Ok(EncodedExpression::Int(match operator {
+                    BinaryOperator::Add => Int::add(ctx, &[&lhs, &rhs]),
+                    BinaryOperator::Sub => Int::sub(ctx, &[&lhs, &rhs]),
+                    BinaryOperator::Mul => Int::mul(ctx, &[&lhs, &rhs]),
+                    BinaryOperator::Div => encode_truncated_division(ctx, &lhs, &rhs),
+                    _ => Int::sub(ctx, &[&lhs, &Int::mul(ctx, &[&rhs, &encode_truncated_division(ctx, &lhs, &rhs)])]),
+                }))
+            }
+        },
+    };
+}
+
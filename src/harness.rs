@@ -0,0 +1,42 @@
+/// Function analysed when checking a whole program from its entry point
+pub const ENTRY_POINT_NAME: &str = "main";
+
+/// Paths of the standard library functions reading the inputs of a program (command line arguments, environment variables and stdin)
+const INPUT_PATH_PREFIXES: &[&str] = &["std::env::", "std::io::stdio::stdin", "std::io::stdio::Stdin"];
+
+/// Paths of the standard library functions writing the outputs of a program (ex: `println!`)
+const OUTPUT_PATH_PREFIXES: &[&str] = &["std::io::stdio::_print", "std::io::stdio::_eprint"];
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolicInput {
+    /// Path of the function the input was read with (ex: `std::env::var_os`)
+    pub function_path: String,
+    /// Field of the returned pair the value is read from, None if the function returns the value itself
+    pub field: Option<u32>,
+    /// Z3 name of the value read
+    pub var_name: String,
+    pub is_bool: bool,
+}
+
+
+pub fn is_entry_point(function_name: &str) -> bool {
+    return function_name == ENTRY_POINT_NAME;
+}
+
+
+fn has_path_prefix(function_path: &str, prefixes: &[&str]) -> bool {
+    //! Trait methods are matched by their implementing type (ex: `<std::io::stdio::StdinLock as std::io::BufRead>::read_line`)
+    let path = function_path.trim_start_matches('<');
+    return prefixes.iter().any(|prefix| path.starts_with(prefix));
+}
+
+
+pub fn is_input_call(function_path: &str) -> bool {
+    return has_path_prefix(function_path, INPUT_PATH_PREFIXES);
+}
+
+
+pub fn is_output_call(function_path: &str) -> bool {
+    return has_path_prefix(function_path, OUTPUT_PATH_PREFIXES);
+}
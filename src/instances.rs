@@ -0,0 +1,86 @@
+use inkwell::context::Context as InkwellContext;
+
+use crate::module_loader::load_module;
+use crate::symbolic_execution::{symbolic_execution, AnalysisOptions, Verdict};
+use crate::utils::function_utils::{get_function_instances, get_function_name, get_source_signature};
+
+
+pub fn list_functions(file_name: &String, options: &AnalysisOptions) -> Option<()> {
+    //! Prints the functions defined in the file, with the name selecting each of them and their signature in the source
+    let context = InkwellContext::create();
+    let loaded_module = load_module(&context, file_name, &String::new(), options, &[])?;
+    let module_prefix = format!("{}::", loaded_module.module_name);
+
+    println!("Functions in {}:", file_name);
+    let mut next_function = loaded_module.module.get_first_function();
+    while let Some(current_function) = next_function {
+        next_function = current_function.get_next_function();
+        if current_function.count_basic_blocks() == 0 {
+            continue;
+        }
+        // Functions of other crates (ex: instantiated generics of `std`) are listed by their full path, without a signature
+        let function_name = get_function_name(&current_function.as_global_value().as_pointer_value());
+        match function_name.strip_prefix(&module_prefix) {
+            Some(selected_name) => match get_source_signature(&loaded_module.source, selected_name) {
+                Some(signature) => println!("\t{}: {}", selected_name, signature),
+                None => println!("\t{}", selected_name),
+            },
+            None => println!("\t{}", function_name),
+        }
+    }
+    return Some(());
+}
+
+
+pub fn get_function_instance_names(file_name: &String, function_name: &String, options: &AnalysisOptions) -> Option<Vec<String>> {
+    //! Names selecting each instance of the function (ex: `foo::h0123456789abcdef` for one monomorphisation of `foo<T>`)
+    let context = InkwellContext::create();
+    let loaded_module = load_module(&context, file_name, function_name, options, &[])?;
+    let module_prefix = format!("{}::", loaded_module.module_name);
+    let instances = get_function_instances(&loaded_module.module, &format!("{}{}", module_prefix, function_name));
+    return Some(instances.iter()
+        .map(|instance| get_function_name(&instance.as_global_value().as_pointer_value())[module_prefix.len()..].to_string())
+        .collect());
+}
+
+
+pub fn all_instances_analysis(file_name: &String, function_name: &String, options: &AnalysisOptions) -> Option<Verdict> {
+    //! Analyses every instance of the function, the function is unsafe if any of its instances is
+    //! None if no instance is unsafe but some could not be analysed
+    let instance_names = get_function_instance_names(file_name, function_name, options)?;
+    if instance_names.is_empty() {
+        println!("Function {:?} is not defined, use --list-functions to see the defined functions", function_name);
+        return None;
+    }
+
+    // An instance that cannot be analysed (ex: with --strict) is reported and the other instances are still analysed
+    let mut verdicts = Vec::<Verdict>::new();
+    let mut failed_instance_count = 0;
+    for instance_name in &instance_names {
+        println!("\nInstance {}:", instance_name);
+        match symbolic_execution(file_name, instance_name, options) {
+            Some(verdict) => verdicts.push(verdict),
+            None => {
+                println!("Instance {} could not be analysed", instance_name);
+                failed_instance_count += 1;
+            }
+        }
+    }
+
+    let verdict = if verdicts.contains(&Verdict::Unsafe) {
+        Verdict::Unsafe
+    } else if failed_instance_count > 0 {
+        println!("\nSafety of the {} instances of {}: {} instances could not be analysed", instance_names.len(), function_name, failed_instance_count);
+        return None;
+    } else if let Some(unknown_verdict) = verdicts.iter().find(|verdict| matches!(verdict, Verdict::Unknown(..))) {
+        unknown_verdict.clone()
+    } else {
+        let unsound_constructs = verdicts.iter().map(|verdict| match verdict {
+            Verdict::Safe { unsound_constructs } => *unsound_constructs,
+            _ => 0,
+        }).sum();
+        Verdict::Safe { unsound_constructs }
+    };
+    println!("\nSafety of the {} instances of {}: {}", instance_names.len(), function_name, verdict);
+    return Some(verdict);
+}
@@ -1,4 +1,19 @@
 pub mod symbolic_execution;
+pub mod analysis_context;
 pub mod codegen;
+pub mod companion;
+pub mod contracts;
 pub mod control_flow_graph;
+pub mod equivalence;
+pub mod expression;
+pub mod harness;
+pub mod instances;
+pub mod llvm_constant;
+pub mod module_loader;
+pub mod panics;
+pub mod parameter_domain;
+pub mod reachability;
+pub mod soundness;
+pub mod stubs;
+pub mod symbol_table;
 pub mod utils;
@@ -0,0 +1,119 @@
+use inkwell::values::{AsValueRef, BasicValueEnum};
+
+use llvm_sys::LLVMOpcode;
+use llvm_sys::core::{
+    LLVMGetConstOpcode, LLVMGetElementAsConstant, LLVMGetNumOperands, LLVMGetOperand, LLVMIsAConstantArray, LLVMIsAConstantDataSequential, LLVMIsAConstantExpr,
+    LLVMIsAConstantStruct, LLVMIsAConstantVector, LLVMIsAGlobalValue, LLVMIsNull, LLVMIsUndef
+};
+
+
+/// Constant expression (ex: `bitcast (i32* @X to i8*)`), with the operands of interest
+pub enum ConstantExpression<'ctx> {
+    /// `bitcast` or `addrspacecast` of the operand
+    PointerCast(BasicValueEnum<'ctx>),
+    /// `getelementptr` on the base pointer, with its indices
+    GetElementPtr(BasicValueEnum<'ctx>, Vec<BasicValueEnum<'ctx>>),
+    /// `inttoptr` of the operand
+    IntToPtr(BasicValueEnum<'ctx>),
+    /// Expressions which are not evaluated (ex: `ptrtoint`)
+    Other,
+}
+
+
+fn get_operands<'ctx>(value: &BasicValueEnum<'ctx>) -> Vec<BasicValueEnum<'ctx>> {
+    //! Operands of a constant expression or aggregate
+    let value_ref = value.as_value_ref();
+    let operand_count = u32::try_from(unsafe { LLVMGetNumOperands(value_ref) }).unwrap_or(0);
+    return (0..operand_count).map(|index| unsafe { BasicValueEnum::new(LLVMGetOperand(value_ref, index)) }).collect();
+}
+
+
+pub fn get_constant_expression<'ctx>(value: &BasicValueEnum<'ctx>) -> Option<ConstantExpression<'ctx>> {
+    //! Constant expression the value is, None if it is not a constant expression
+    let value_ref = value.as_value_ref();
+    if unsafe { LLVMIsAConstantExpr(value_ref) }.is_null() {
+        return None;
+    }
+    let operands = get_operands(value);
+    let (first_operand, other_operands) = operands.split_first()?;
+    return Some(match unsafe { LLVMGetConstOpcode(value_ref) } {
+        LLVMOpcode::LLVMBitCast | LLVMOpcode::LLVMAddrSpaceCast => ConstantExpression::PointerCast(*first_operand),
+        LLVMOpcode::LLVMGetElementPtr => ConstantExpression::GetElementPtr(*first_operand, other_operands.to_vec()),
+        LLVMOpcode::LLVMIntToPtr => ConstantExpression::IntToPtr(*first_operand),
+        _ => ConstantExpression::Other,
+    });
+}
+
+
+pub fn get_global_name(value: &BasicValueEnum) -> Option<String> {
+    //! Name of the global or function the value is, None if it is another value
+    if !value.is_pointer_value() || unsafe { LLVMIsAGlobalValue(value.as_value_ref()) }.is_null() {
+        return None;
+    }
+    return Some(value.into_pointer_value().get_name().to_str().unwrap().to_string());
+}
+
+
+pub fn get_base_global(value: &BasicValueEnum) -> Option<String> {
+    //! Global a constant pointer expression is based on (ex: `getelementptr inbounds ([4 x i32], [4 x i32]* @X, i64 0, i64 2)` -> `X`)
+    if let Some(name) = get_global_name(value) {
+        return Some(name);
+    }
+    return match get_constant_expression(value)? {
+        ConstantExpression::PointerCast(operand) => get_base_global(&operand),
+        ConstantExpression::GetElementPtr(base, _) => get_base_global(&base),
+        _ => None,
+    };
+}
+
+
+pub fn get_cast_global(value: &BasicValueEnum) -> Option<String> {
+    //! Global a constant pointer is a cast of, pointing to its first byte (ex: `bitcast (<{ i8* }>* @vtable.0 to [3 x i64]*)` -> `vtable.0`)
+    if let Some(name) = get_global_name(value) {
+        return Some(name);
+    }
+    return match get_constant_expression(value)? {
+        ConstantExpression::PointerCast(operand) => get_cast_global(&operand),
+        _ => None,
+    };
+}
+
+
+pub fn is_global_constant_expression(value: &BasicValueEnum) -> bool {
+    //! Whether a constant is a cast of or an address into a global (ex: `i8* bitcast (i32* @X to i8*)`)
+    return get_constant_expression(value).is_some() && get_base_global(value).is_some();
+}
+
+
+pub fn is_zero_constant(value: &BasicValueEnum) -> bool {
+    //! Whether a constant is all zero bytes (ex: `zeroinitializer`, `null` or `0`)
+    return unsafe { LLVMIsNull(value.as_value_ref()) } != 0;
+}
+
+
+pub fn is_undefined_constant(value: &BasicValueEnum) -> bool {
+    //! Whether a constant is `undef` or `poison`
+    return unsafe { LLVMIsUndef(value.as_value_ref()) } != 0;
+}
+
+
+pub fn get_aggregate_elements<'ctx>(value: &BasicValueEnum<'ctx>) -> Option<Vec<BasicValueEnum<'ctx>>> {
+    //! Elements of a constant array, vector or struct, None if the value is not one (ex: `zeroinitializer`)
+    let value_ref = value.as_value_ref();
+    // Arrays and vectors of integers (ex: `c"\01\00\00\00"`) store their elements as data rather than operands
+    if !unsafe { LLVMIsAConstantDataSequential(value_ref) }.is_null() {
+        let element_count = match value {
+            BasicValueEnum::ArrayValue(array_value) => array_value.get_type().len(),
+            BasicValueEnum::VectorValue(vector_value) => vector_value.get_type().get_size(),
+            _ => return None,
+        };
+        return Some((0..element_count).map(|index| unsafe { BasicValueEnum::new(LLVMGetElementAsConstant(value_ref, index)) }).collect());
+    }
+    let is_aggregate = unsafe {
+        !LLVMIsAConstantArray(value_ref).is_null() || !LLVMIsAConstantStruct(value_ref).is_null() || !LLVMIsAConstantVector(value_ref).is_null()
+    };
+    if !is_aggregate {
+        return None;
+    }
+    return Some(get_operands(value));
+}
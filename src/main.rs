@@ -3,7 +3,10 @@ use clap::Parser;
 use tracing_core::Level;
 use tracing_subscriber::FmtSubscriber;
 
-use wombat_symx::symbolic_execution::symbolic_execution;
+use wombat_symx::symbolic_execution::{symbolic_execution, AnalysisOptions, ExternalCallPolicy, EXIT_CODE_ERROR, EXIT_CODE_SAFE};
+use wombat_symx::equivalence::{differential_checking, equivalence_checking};
+use wombat_symx::instances::{all_instances_analysis, list_functions};
+use wombat_symx::reachability::reachability_analysis;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -12,13 +15,73 @@ struct Args {
     #[clap(short, long)]
     debug: bool,
 
+    /// Report basic blocks and branches that are unreachable instead of checking for panics
+    #[clap(long)]
+    dead_code: bool,
+
+    /// Solver timeout in milliseconds
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// Solver resource limit (deterministic alternative to the timeout)
+    #[clap(long)]
+    rlimit: Option<u32>,
+
+    /// Maximum memory used by the solver in megabytes
+    #[clap(long)]
+    memory: Option<u32>,
+
+    /// Treat any skipped or approximated construct as an error
+    #[clap(long)]
+    strict: bool,
+
+    /// Encoding of calls to functions outside of the module (havoc, no-panic or may-panic)
+    #[clap(long, default_value = "havoc")]
+    external_calls: ExternalCallPolicy,
+
+    /// Rust file with functions annotated with `#[wombat::stub("path::to::fn")]` replacing the called functions
+    #[clap(long)]
+    stubs: Option<String>,
+
+    /// Condition over the argument names and `ret` checked whenever the function returns (ex: 'ret >= 0'), may be repeated
+    #[clap(long)]
+    ensures: Vec<String>,
+
+    /// Also report undefined behaviour in unsafe code (ex: null pointer dereferences, misaligned loads, reachable `unreachable_unchecked`)
+    #[clap(long = "ub")]
+    undefined_behaviour: bool,
+
+    /// Target triple to compile the file for (ex: `thumbv7em-none-eabihf`), whose data layout gives the width of pointers and `usize`
+    #[clap(long)]
+    target: Option<String>,
+
+    /// Check that the function is equivalent to the given function instead of checking for panics
+    #[clap(long)]
+    equivalent_to: Option<String>,
+
+    /// File defining the function given to --equivalent-to (defaults to the analyzed file)
+    #[clap(long, requires = "equivalent-to")]
+    other_file: Option<String>,
+
+    /// Report inputs on which the debug build panics while the release build (without overflow checks) returns
+    #[clap(long)]
+    differential: bool,
+
+    /// List the functions defined in the file (with the hash selecting each instance of generic functions) instead of analysing one
+    #[clap(long)]
+    list_functions: bool,
+
+    /// Analyse every monomorphised instance of a generic function
+    #[clap(long)]
+    all_instances: bool,
+
     /// Set file name to perform symbolic execution on
     #[clap()]
     file_name: String,
 
-    /// Set function to perform symbolic execution on
-    #[clap()]
-    function_name: String,
+    /// Set function to perform symbolic execution on (ex: `foo`, or `foo::h0123456789abcdef` for one instance of a generic function)
+    #[clap(required_unless_present = "list-functions")]
+    function_name: Option<String>,
 }
 
 fn main() {
@@ -34,7 +97,52 @@ fn main() {
     let _guard = tracing::subscriber::set_default(subscriber);
 
     let file_name = String::from(&features.file_name);
-    let function_name = String::from(&features.function_name);
-    
-    symbolic_execution(&file_name, &function_name);
+    let function_name = features.function_name.clone().unwrap_or_default();
+
+    let options = AnalysisOptions {
+        timeout: features.timeout,
+        rlimit: features.rlimit,
+        memory: features.memory,
+        strict: features.strict,
+        external_calls: features.external_calls,
+        stub_file: features.stubs.clone(),
+        ensures: features.ensures.clone(),
+        check_undefined_behaviour: features.undefined_behaviour,
+        target: features.target.clone(),
+    };
+
+    // Exit codes distinguish safe, unsafe, unknown and error results
+    let exit_code = if features.list_functions {
+        match list_functions(&file_name, &options) {
+            Some(..) => EXIT_CODE_SAFE,
+            None => EXIT_CODE_ERROR,
+        }
+    } else if features.dead_code {
+        match reachability_analysis(&file_name, &function_name, &options) {
+            Some(report) => report.get_exit_code(),
+            None => EXIT_CODE_ERROR,
+        }
+    } else if features.differential {
+        match differential_checking(&file_name, &function_name, &options) {
+            Some(verdict) => verdict.get_exit_code(),
+            None => EXIT_CODE_ERROR,
+        }
+    } else if let Some(other_function_name) = &features.equivalent_to {
+        let other_file_name = features.other_file.clone().unwrap_or(file_name.clone());
+        match equivalence_checking(&file_name, &function_name, &other_file_name, other_function_name, &options) {
+            Some(verdict) => verdict.get_exit_code(),
+            None => EXIT_CODE_ERROR,
+        }
+    } else if features.all_instances {
+        match all_instances_analysis(&file_name, &function_name, &options) {
+            Some(verdict) => verdict.get_exit_code(),
+            None => EXIT_CODE_ERROR,
+        }
+    } else {
+        match symbolic_execution(&file_name, &function_name, &options) {
+            Some(verdict) => verdict.get_exit_code(),
+            None => EXIT_CODE_ERROR,
+        }
+    };
+    std::process::exit(exit_code);
 }
\ No newline at end of file
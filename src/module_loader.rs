@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::fs;
+
+use inkwell::context::Context as InkwellContext;
+use inkwell::module::{Module as InkwellModule};
+
+use crate::companion::{get_companion_crate_args, get_companion_rlib_file_name};
+use crate::contracts::{prepare_source_file, FunctionContract};
+use crate::stubs::link_stub_file;
+use crate::symbolic_execution::{AnalysisOptions, FileDropper, compile_to_bytecode, get_bytecode_file_name, get_inkwell_module, get_module_name_from_file_name};
+
+
+pub struct LoadedModule<'a> {
+    pub module: InkwellModule<'a>,
+    /// Crate name prefixing the paths of the functions defined in the module
+    pub module_name: String,
+    /// Contract of the loaded function, whose attributes are removed from the compiled source
+    pub contract: FunctionContract,
+    /// Compiled source, without the contract attributes
+    pub source: String,
+    /// Paths of stubbed functions mapped to the names of the stubs linked into the module
+    pub stubs: HashMap<String, String>,
+}
+
+
+pub fn load_module<'a>(context: &'a InkwellContext, file_name: &String, function_name: &String, options: &AnalysisOptions, extra_args: &[&str]) -> Option<LoadedModule<'a>> {
+    //! Compiles the file (without contracts, against the companion crate if used), loads its module and links the stub file into it
+    //! The temporary files are removed once the module is loaded
+    let source_option = prepare_source_file(file_name, function_name);
+    if source_option.is_none() {
+        return None;
+    }
+    let (analyzed_file_name, contract) = source_option.unwrap();
+    let _temp_source_file_dropper = if analyzed_file_name.ne(file_name) {
+        Some(FileDropper {
+            file_name: &analyzed_file_name,
+        })
+    } else {
+        None
+    };
+    let source = fs::read_to_string(&analyzed_file_name).unwrap_or_default();
+
+    // Verification harnesses may use the companion crate, which is compiled alongside
+    let target = options.target.as_deref();
+    let companion_args_option = get_companion_crate_args(&analyzed_file_name, target);
+    if companion_args_option.is_none() {
+        return None;
+    }
+    let companion_args = companion_args_option.unwrap();
+    let companion_rlib_file_name = get_companion_rlib_file_name(&analyzed_file_name);
+    let _companion_rlib_file_dropper = if companion_args.len() > 0 {
+        Some(FileDropper {
+            file_name: &companion_rlib_file_name,
+        })
+    } else {
+        None
+    };
+
+    let bytecode_file_name = get_bytecode_file_name(&analyzed_file_name);
+    let mut compile_args: Vec<&str> = extra_args.to_vec();
+    compile_args.extend(companion_args.iter().map(|arg| arg.as_str()));
+    if !compile_to_bytecode(&analyzed_file_name, &bytecode_file_name, target, &compile_args) {
+        return None;
+    }
+
+    let _temp_bc_file_dropper = FileDropper {
+        file_name: &bytecode_file_name,
+    };
+
+    let module_result = get_inkwell_module(context, &bytecode_file_name);
+    if module_result.is_none() {
+        return None;
+    }
+    let module = module_result.unwrap();
+
+    let mut stubs = HashMap::new();
+    if let Some(stub_file_name) = &options.stub_file {
+        let stubs_result = link_stub_file(context, &module, stub_file_name, target);
+        if stubs_result.is_none() {
+            return None;
+        }
+        stubs = stubs_result.unwrap();
+    }
+
+    return Some(LoadedModule {
+        module,
+        module_name: get_module_name_from_file_name(&bytecode_file_name),
+        contract,
+        source,
+        stubs,
+    });
+}
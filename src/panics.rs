@@ -0,0 +1,59 @@
+/// Name of the variable telling which panic site a counterexample panics at
+pub const PANIC_SITE_VAR_NAME: &str = "wombat_symx_panic_site";
+
+/// Paths of the functions that always panic (ex: the slow paths of `unwrap` and `expect`), matched as prefixes
+const PANIC_SINK_PATH_PREFIXES: &[&str] = &[
+    "core::panicking::panic",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+    "core::str::slice_error_fail",
+    "std::panicking::begin_panic",
+];
+
+/// Methods of `Option` and `Result` that may panic, whose generic instances are defined in the module and analysed like its own functions
+const PANICKING_METHOD_PATHS: &[&str] = &[
+    "core::option::Option::unwrap",
+    "core::option::Option::expect",
+    "core::result::Result::unwrap",
+    "core::result::Result::expect",
+    "core::result::Result::unwrap_err",
+    "core::result::Result::expect_err",
+];
+
+/// Messages of the panic sinks that are not given one by their caller
+const DEFAULT_PANIC_MESSAGES: &[(&str, &str)] = &[
+    ("core::option::unwrap_failed", "called `Option::unwrap()` on a `None` value"),
+    ("core::panicking::panic_bounds_check", "index out of bounds"),
+];
+
+
+pub fn is_panic_sink(function_path: &str) -> bool {
+    return PANIC_SINK_PATH_PREFIXES.iter().any(|prefix| function_path.starts_with(prefix));
+}
+
+
+pub fn get_path_without_generics(function_path: &str) -> String {
+    //! Path with its generic arguments removed (ex: `core::option::Option<i32>::unwrap` -> `core::option::Option::unwrap`)
+    let mut path = String::new();
+    let mut depth = 0;
+    for c in function_path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => path.push(c),
+            _ => {}
+        }
+    }
+    return path;
+}
+
+
+pub fn is_panicking_method(function_path: &str) -> bool {
+    return PANICKING_METHOD_PATHS.contains(&get_path_without_generics(function_path).as_str());
+}
+
+
+pub fn get_default_panic_message(function_path: &str) -> Option<&'static str> {
+    return DEFAULT_PANIC_MESSAGES.iter().find(|(path, _)| function_path.starts_with(path)).map(|(_, message)| *message);
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use inkwell::module::{Module as InkwellModule};
+use inkwell::values::{BasicValueEnum, FunctionValue, InstructionOpcode};
+
+use z3::Solver;
+use z3::ast::{Bool, Int};
+
+use crate::utils::function_utils::get_function_name;
+use crate::utils::var_utils::get_unsigned_value;
+
+
+/// Half-open ranges of the unsigned values a parameter may take, as given by `!range` metadata (ex: `[(0, 3)]` for an enum with three variants)
+/// A range whose start is above its end wraps around (ex: `[(1, 0)]` for `NonZeroU32`)
+pub type ValueRanges = Vec<(u64, u64)>;
+
+/// Fieldless enums of the source mapped to their variants, with the discriminant of each variant
+pub type SourceEnums = HashMap<String, Vec<(i128, String)>>;
+
+
+fn get_slot_base<'ctx>(pointer: BasicValueEnum<'ctx>) -> BasicValueEnum<'ctx> {
+    //! Pointer to the start of the stack slot, through casts and `getelementptr` to its first field (ex: the discriminant of an `Option<i32>`)
+    let mut pointer = pointer;
+    while pointer.is_pointer_value() {
+        let instruction = match pointer.into_pointer_value().as_instruction() {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        let is_slot_start = match instruction.get_opcode() {
+            InstructionOpcode::BitCast => true,
+            InstructionOpcode::GetElementPtr => (1..instruction.get_num_operands())
+                .filter_map(|index| instruction.get_operand(index).and_then(|operand| operand.left()))
+                .all(|index| index.is_int_value() && index.into_int_value().get_zero_extended_constant() == Some(0)),
+            _ => false,
+        };
+        match instruction.get_operand(0).and_then(|operand| operand.left()) {
+            Some(operand) if is_slot_start => pointer = operand,
+            _ => break,
+        }
+    }
+    return pointer;
+}
+
+
+fn get_load_ranges(function: &FunctionValue, parameter: &BasicValueEnum, range_kind_id: u32) -> Option<ValueRanges> {
+    //! Ranges of the loads reading back the parameter from the stack slot it is stored to
+    //! Debug builds store parameters on entry, and only the loads carry `!range` metadata (which `mem2reg` drops)
+    //! Pairs (ex: `Option<i32>`) are stored field by field, and their first field may be loaded through a cast of the slot
+    let entry_block = function.get_first_basic_block()?;
+    let mut slots = Vec::new();
+    let mut next_instruction = entry_block.get_first_instruction();
+    while let Some(instruction) = next_instruction {
+        if instruction.get_opcode() == InstructionOpcode::Store && instruction.get_operand(0)?.left()? == *parameter {
+            slots.push(get_slot_base(instruction.get_operand(1)?.left()?));
+        }
+        next_instruction = instruction.get_next_instruction();
+    }
+
+    for bb in function.get_basic_blocks() {
+        let mut next_instruction = bb.get_first_instruction();
+        while let Some(instruction) = next_instruction {
+            next_instruction = instruction.get_next_instruction();
+            if instruction.get_opcode() != InstructionOpcode::Load || !slots.contains(&get_slot_base(instruction.get_operand(0)?.left()?)) {
+                continue;
+            }
+            if let Some(range_metadata) = instruction.get_metadata(range_kind_id) {
+                let bounds = range_metadata.get_node_values().iter()
+                    .map(|bound| bound.into_int_value().get_zero_extended_constant())
+                    .collect::<Option<Vec<u64>>>()?;
+                return Some(bounds.chunks(2).filter(|bound| bound.len() == 2).map(|bound| (bound[0], bound[1])).collect());
+            }
+        }
+    }
+    return None;
+}
+
+
+pub fn get_all_parameter_ranges(module: &InkwellModule) -> HashMap<String, Vec<Option<ValueRanges>>> {
+    //! Ranges of the parameters of every function, which must be read before converting to DSA
+    let range_kind_id = module.get_context().get_kind_id("range");
+    let mut all_parameter_ranges = HashMap::new();
+    let mut next_function = module.get_first_function();
+    while let Some(current_function) = next_function {
+        let parameter_ranges: Vec<Option<ValueRanges>> = current_function.get_params().iter()
+            .map(|parameter| get_load_ranges(&current_function, parameter, range_kind_id))
+            .collect();
+        if parameter_ranges.iter().any(|ranges| ranges.is_some()) {
+            all_parameter_ranges.insert(get_function_name(&current_function.as_global_value().as_pointer_value()), parameter_ranges);
+        }
+        next_function = current_function.get_next_function();
+    }
+    return all_parameter_ranges;
+}
+
+
+pub fn get_range_constraint<'a>(solver: &'a Solver<'_>, var_name: &str, bit_width: u32, ranges: &ValueRanges) -> Bool<'a> {
+    //! Constrains a (signed) variable to the ranges, which bound its unsigned value
+    let var = Int::new_const(solver.get_context(), var_name);
+    let unsigned_var = get_unsigned_value(solver.get_context(), &var, bit_width);
+    let mut in_ranges = Vec::<Bool>::new();
+    for (start, end) in ranges {
+        let start_var = Int::from_u64(solver.get_context(), *start);
+        let end_var = Int::from_u64(solver.get_context(), *end);
+        let in_range = if start < end {
+            Bool::and(solver.get_context(), &[&unsigned_var.ge(&start_var), &unsigned_var.lt(&end_var)])
+        } else {
+            Bool::or(solver.get_context(), &[&unsigned_var.ge(&start_var), &unsigned_var.lt(&end_var)])
+        };
+        in_ranges.push(in_range);
+    }
+    return Bool::or(solver.get_context(), &in_ranges.iter().collect::<Vec<&Bool>>());
+}
+
+
+fn split_top_level(text: &str) -> Vec<String> {
+    //! Splits on the commas that are not nested in brackets (ex: `a: i32, b: Result<u8, i8>` -> `a: i32`, `b: Result<u8, i8>`)
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut item = String::new();
+    let mut previous = ' ';
+    for c in text.chars() {
+        match c {
+            // The arrow of function types (ex: `fn(i32) -> i32`) is not a closing bracket
+            '>' if previous == '-' => {}
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(item.trim().to_string());
+                item.clear();
+                previous = c;
+                continue;
+            }
+            _ => {}
+        }
+        item.push(c);
+        previous = c;
+    }
+    if !item.trim().is_empty() {
+        items.push(item.trim().to_string());
+    }
+    return items;
+}
+
+
+fn get_braced_body<'s>(source: &'s str, start_index: usize, open: char, close: char) -> Option<&'s str> {
+    //! Text between the first opening bracket after the index and its matching closing bracket
+    let open_index = start_index + source[start_index..].find(open)?;
+    let mut depth = 0;
+    for (index, c) in source[open_index..].char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(&source[open_index + 1..open_index + index]);
+            }
+        }
+    }
+    return None;
+}
+
+
+pub fn get_source_enums(source: &str) -> SourceEnums {
+    //! Fieldless enums defined in the source, with the discriminants of their variants (ex: `enum Color { Red, Green = 4, Blue }`)
+    let mut enums = SourceEnums::new();
+    for (enum_index, _) in source.match_indices("enum ") {
+        if enum_index > 0 && source[..enum_index].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let name_start_index = enum_index + "enum ".len();
+        let name: String = source[name_start_index..].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        let body = match get_braced_body(source, name_start_index, '{', '}') {
+            Some(body) => body,
+            None => continue,
+        };
+        let mut variants = Vec::new();
+        let mut discriminant: i128 = 0;
+        let mut is_fieldless = true;
+        for variant in split_top_level(body) {
+            // Attributes and doc comments of the variant (ex: `#[default]`)
+            let variant = variant.lines().map(|line| line.trim()).filter(|line| !line.starts_with("#[") && !line.starts_with("//")).collect::<Vec<&str>>().join(" ");
+            if variant.is_empty() {
+                continue;
+            }
+            if variant.contains('(') || variant.contains('{') {
+                is_fieldless = false;
+                break;
+            }
+            let variant_name = match variant.split_once('=') {
+                Some((variant_name, value)) => {
+                    match value.trim().replace('_', "").parse::<i128>() {
+                        Ok(value) => discriminant = value,
+                        Err(..) => {
+                            is_fieldless = false;
+                            break;
+                        }
+                    }
+                    variant_name.trim().to_string()
+                }
+                None => variant.trim().to_string(),
+            };
+            variants.push((discriminant, variant_name));
+            discriminant += 1;
+        }
+        if is_fieldless && !name.is_empty() {
+            enums.insert(name, variants);
+        }
+    }
+    return enums;
+}
+
+
+pub fn get_source_parameter_types(source: &str, function_name: &str) -> HashMap<String, String> {
+    //! Types of the parameters of the function, as written in the source (ex: `fn f(c: Color, x: i32)` -> `c: Color`, `x: i32`)
+    let mut parameter_types = HashMap::new();
+    let signature_start = format!("fn {}", function_name);
+    for (fn_index, _) in source.match_indices(&signature_start) {
+        let after_name = &source[fn_index + signature_start.len()..];
+        if !after_name.trim_start().starts_with('(') && !after_name.trim_start().starts_with('<') {
+            continue;
+        }
+        let parameters = match get_braced_body(source, fn_index, '(', ')') {
+            Some(parameters) => parameters,
+            None => continue,
+        };
+        for parameter in split_top_level(parameters) {
+            if let Some((name, parameter_type)) = parameter.split_once(':') {
+                let name = name.trim().trim_start_matches("mut ").trim();
+                parameter_types.insert(name.to_string(), parameter_type.trim().to_string());
+            }
+        }
+        break;
+    }
+    return parameter_types;
+}
+
+
+pub fn get_typed_value_string(value: i64, bit_width: u32, source_type: &str, enums: &SourceEnums) -> Option<String> {
+    //! Rust expression of a value of the given source type (ex: `Color::Green`, `'a'`), None for plain integers
+    let unsigned_value = if bit_width >= 64 { value as u64 } else { (value as u64) & ((1u64 << bit_width) - 1) };
+    if let Some(variants) = enums.get(source_type) {
+        return variants.iter()
+            .find(|(discriminant, _)| *discriminant == i128::from(value) || *discriminant == i128::from(unsigned_value))
+            .map(|(_, variant_name)| format!("{}::{}", source_type, variant_name));
+    }
+    if source_type == "char" {
+        return char::from_u32(u32::try_from(unsigned_value).ok()?).map(|c| format!("{:?}", c));
+    }
+    let nonzero_type = source_type.trim_start_matches("std::num::").trim_start_matches("core::num::");
+    if nonzero_type.starts_with("NonZero") {
+        let nonzero_value = if nonzero_type.starts_with("NonZeroU") { unsigned_value.to_string() } else { value.to_string() };
+        return Some(format!("std::num::{}::new({}).unwrap()", nonzero_type, nonzero_value));
+    }
+    return None;
+}
+
+
+pub fn get_str_parameters(parameter_types: &HashMap<String, String>) -> Vec<String> {
+    //! Parameters of type `&str`, with any lifetime (ex: `s: &'a str`)
+    let mut str_parameters = Vec::new();
+    for (name, parameter_type) in parameter_types {
+        let referent_type = parameter_type.trim_start_matches('&').trim_start();
+        let referent_type = match referent_type.strip_prefix('\'') {
+            Some(lifetime_and_type) => lifetime_and_type.split_once(' ').map(|(_, referent_type)| referent_type).unwrap_or(""),
+            None => referent_type,
+        };
+        if parameter_type.starts_with('&') && referent_type.trim() == "str" {
+            str_parameters.push(name.clone());
+        }
+    }
+    return str_parameters;
+}
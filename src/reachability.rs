@@ -0,0 +1,234 @@
+use tracing::{debug};
+
+use inkwell::context::Context as InkwellContext;
+use inkwell::values::InstructionOpcode;
+
+use z3::{Solver, SatResult};
+use z3::Context as Z3Context;
+use z3::ast::{Bool};
+
+use crate::analysis_context::AnalysisContext;
+use crate::codegen::codegen_function::codegen_function;
+use crate::contracts::{encode_contract_conditions, get_expression_variables};
+use crate::module_loader::{load_module, LoadedModule};
+use crate::parameter_domain::get_all_parameter_ranges;
+use crate::codegen::codegen_basic_block::{get_entry_condition, get_entry_probe_name, get_exit_probe_name};
+use crate::control_flow_graph::get_forward_edges;
+use crate::symbolic_execution::{
+    AnalysisOptions, UnknownReason, constrain_function_parameters, convert_to_dsa, get_solver, get_solver_config, is_refused_by_strict_mode, print_soundness_report,
+    COMMON_END_NODE, EXIT_CODE_SAFE, EXIT_CODE_UNKNOWN, MAIN_FUNCTION_NAMESPACE, MAIN_FUNCTION_RETURN_REGISTER
+};
+use crate::utils::debug_info::{get_basic_block_debug_location, get_debug_locations, get_instruction_debug_location};
+use crate::utils::function_utils::{get_all_function_argument_names, get_function_by_name, get_function_name};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachableCode {
+    /// Basic block which is unreachable, or which contains the never taken branch
+    pub block: String,
+    /// Successor of the never taken branch (None if the whole block is unreachable)
+    pub successor: Option<String>,
+    /// Source (line, column) of the block or branch, if debug info is available
+    pub location: Option<(u32, u32)>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadCodeReport {
+    /// Blocks and branches proven unreachable
+    pub unreachable_code: Vec<UnreachableCode>,
+    /// Blocks and branches the solver could not decide, with the reason (ex: a timeout), which are counted as reachable
+    pub undecided_code: Vec<(UnreachableCode, UnknownReason)>,
+}
+
+impl DeadCodeReport {
+    pub fn get_exit_code(&self) -> i32 {
+        //! Like the verdicts, undecided code makes the result unknown
+        if self.undecided_code.len() > 0 {
+            return EXIT_CODE_UNKNOWN;
+        }
+        return EXIT_CODE_SAFE;
+    }
+}
+
+
+fn check_probe(solver: &Solver, probe_names: &Vec<String>, target_probe_name: &String, extra_assumption: Option<&Bool>) -> SatResult {
+    //! Checks whether the target probe can be falsified while all other probes hold, unsat proving it unreachable
+    let mut assumptions = Vec::<Bool>::new();
+    for probe_name in probe_names {
+        let probe_var = Bool::new_const(solver.get_context(), probe_name.as_str());
+        if probe_name.eq(target_probe_name) {
+            assumptions.push(probe_var.not());
+        } else {
+            assumptions.push(probe_var);
+        }
+    }
+    if let Some(assumption) = extra_assumption {
+        assumptions.push(assumption.clone());
+    }
+    return solver.check_assumptions(&assumptions);
+}
+
+
+fn get_unknown_reason(solver: &Solver) -> UnknownReason {
+    return UnknownReason::from_solver_reason(&solver.get_reason_unknown().unwrap_or(String::from("unknown")));
+}
+
+
+fn get_location_string(code: &UnreachableCode, file_name: &String) -> String {
+    return match code.location {
+        Some((line, column)) => format!("{}:{}:{}", file_name, line, column),
+        None => String::from("unknown location"),
+    };
+}
+
+
+pub fn reachability_analysis(file_name: &String, function_name: &String, options: &AnalysisOptions) -> Option<DeadCodeReport> {
+    //! Reports the basic blocks and conditional branches of the function that can never be reached from its entry
+    let context = InkwellContext::create();
+
+    // Debug info is required to map basic blocks back to source locations
+    let loaded_module_option = load_module(&context, file_name, function_name, options, &["-g"]);
+    if loaded_module_option.is_none() {
+        return None;
+    }
+    let LoadedModule { module, module_name, contract, source, stubs } = loaded_module_option.unwrap();
+
+    let mut analysis = AnalysisContext::new(options);
+    analysis.stubs = stubs;
+
+    let target_function_name_prefix = format!("{}::{}", module_name, function_name);
+    let debug_locations = get_debug_locations(&module);
+
+    let cfg = get_solver_config(options);
+    let ctx = Z3Context::new(&cfg);
+    let solver = get_solver(&ctx, options);
+
+    // Save function argument names before removing store/alloca instructions
+    let all_func_arg_names = get_all_function_argument_names(&module, &solver, &analysis, MAIN_FUNCTION_NAMESPACE);
+
+    // Parameter ranges are read from loads which are removed when converting to DSA
+    analysis.parameter_ranges = get_all_parameter_ranges(&module);
+    convert_to_dsa(&module, &analysis);
+
+    let function_option = get_function_by_name(&module, &target_function_name_prefix);
+    if function_option.is_none() {
+        return None;
+    }
+    let function = function_option.unwrap();
+
+    let func_arg_names_option = all_func_arg_names.get(&get_function_name(&function.as_global_value().as_pointer_value()));
+    if func_arg_names_option.is_none() {
+        return None;
+    }
+    let func_arg_names = func_arg_names_option.unwrap();
+
+    let call_stack = function.get_name().to_str().unwrap();
+    codegen_function(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, call_stack, COMMON_END_NODE, COMMON_END_NODE, MAIN_FUNCTION_RETURN_REGISTER);
+    constrain_function_parameters(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, function_name);
+
+    // Code is only reachable from arguments satisfying the preconditions, postconditions play no role
+    let contract_variables = get_expression_variables(&function, func_arg_names, &source, function_name);
+    let precondition_option = encode_contract_conditions(solver.get_context(), &contract.requires, &contract_variables);
+    if precondition_option.is_none() {
+        return None;
+    }
+    solver.assert(&precondition_option.unwrap());
+
+    // Skipped constructs may make code look unreachable that is not
+    if is_refused_by_strict_mode(&analysis) {
+        return None;
+    }
+
+    // Any way of leaving the function is acceptable, only falsifying a probe is of interest
+    let common_end_node_var = Bool::new_const(solver.get_context(), String::from(COMMON_END_NODE));
+    solver.assert(&common_end_node_var);
+
+    let start_node = function.get_first_basic_block().unwrap();
+    let start_node_var_name = format!("{}{}", MAIN_FUNCTION_NAMESPACE, start_node.get_name().to_str().unwrap());
+    let start_node_var = Bool::new_const(solver.get_context(), String::from(start_node_var_name));
+    solver.assert(&start_node_var.not());
+
+    debug!("{}", format!("\nSolver:\n{:?}", solver));
+
+    let mut probe_names = Vec::<String>::new();
+    for bb in function.get_basic_blocks() {
+        let node = format!("{}{}", MAIN_FUNCTION_NAMESPACE, bb.get_name().to_str().unwrap());
+        probe_names.push(get_entry_probe_name(&node));
+        probe_names.push(get_exit_probe_name(&node));
+    }
+
+    let forward_edges = get_forward_edges(&function, MAIN_FUNCTION_NAMESPACE, COMMON_END_NODE, COMMON_END_NODE);
+    let mut unreachable_code = Vec::<UnreachableCode>::new();
+    let mut undecided_code = Vec::<(UnreachableCode, UnknownReason)>::new();
+    for bb in function.get_basic_blocks() {
+        let node = format!("{}{}", MAIN_FUNCTION_NAMESPACE, bb.get_name().to_str().unwrap());
+        let code = UnreachableCode {
+            block: node.clone(),
+            successor: None,
+            location: get_basic_block_debug_location(&bb, &debug_locations),
+        };
+        match check_probe(&solver, &probe_names, &get_entry_probe_name(&node), None) {
+            SatResult::Unsat => {
+                unreachable_code.push(code);
+                continue;
+            }
+            SatResult::Unknown => undecided_code.push((code, get_unknown_reason(&solver))),
+            SatResult::Sat => {}
+        }
+
+        // Check every direction of the conditional branches of reachable blocks
+        let terminator = bb.get_terminator().unwrap();
+        let is_conditional_branch = match terminator.get_opcode() {
+            InstructionOpcode::Br => terminator.get_num_operands() == 3,
+            InstructionOpcode::Switch => true,
+            _ => false,
+        };
+        if !is_conditional_branch {
+            continue;
+        }
+        let mut successors = Vec::from_iter(forward_edges.get(&node).unwrap().iter().cloned());
+        successors.sort();
+        for successor in successors {
+            let branch_condition = get_entry_condition(&solver, &analysis, &function, &node, &successor, MAIN_FUNCTION_NAMESPACE);
+            let code = UnreachableCode {
+                block: node.clone(),
+                successor: Some(successor),
+                location: get_instruction_debug_location(&terminator, &debug_locations),
+            };
+            match check_probe(&solver, &probe_names, &get_exit_probe_name(&node), Some(&branch_condition)) {
+                SatResult::Unsat => unreachable_code.push(code),
+                SatResult::Unknown => undecided_code.push((code, get_unknown_reason(&solver))),
+                SatResult::Sat => {}
+            }
+        }
+    }
+
+    println!("\nUnreachable code in {}:", function_name);
+    if unreachable_code.len() == 0 {
+        println!("\tNone");
+    }
+    for code in &unreachable_code {
+        match &code.successor {
+            Some(successor) => println!("\tBranch {:?} -> {:?} is never taken ({})", code.block, successor, get_location_string(code, file_name)),
+            None => println!("\tBlock {:?} is never reached ({})", code.block, get_location_string(code, file_name)),
+        }
+    }
+    // Only a proof makes code unreachable, so undecided code is counted as reachable
+    if undecided_code.len() > 0 {
+        println!("\nUndecided code in {} (counted as reachable):", function_name);
+    }
+    for (code, reason) in &undecided_code {
+        match &code.successor {
+            Some(successor) => println!("\tBranch {:?} -> {:?} ({}): {}", code.block, successor, get_location_string(code, file_name), reason),
+            None => println!("\tBlock {:?} ({}): {}", code.block, get_location_string(code, file_name), reason),
+        }
+    }
+
+    print_soundness_report(&analysis);
+
+    return Some(DeadCodeReport {
+        unreachable_code,
+        undecided_code,
+    });
+}
@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+
+use tracing::{warn};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundnessImpact {
+    /// More behaviours than the program has are encoded, which may only lead to spurious counterexamples
+    OverApproximation,
+    /// Behaviours of the program are missing from the encoding, which may hide panics
+    Unsound,
+    /// Behaviours of the program are excluded because the user chose to assume them away
+    Assumption,
+}
+
+impl std::fmt::Display for SoundnessImpact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoundnessImpact::OverApproximation => write!(f, "over-approximation"),
+            SoundnessImpact::Unsound => write!(f, "unsound"),
+            SoundnessImpact::Assumption => write!(f, "assumption"),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoundnessEvent {
+    pub impact: SoundnessImpact,
+    pub description: String,
+}
+
+
+#[derive(Debug, Default)]
+pub struct SoundnessLedger {
+    events: RefCell<Vec<SoundnessEvent>>,
+}
+
+impl SoundnessLedger {
+    pub fn new() -> SoundnessLedger {
+        return SoundnessLedger::default();
+    }
+
+    pub fn record(&self, impact: SoundnessImpact, description: String) -> () {
+        //! Records a construct that could not be encoded precisely (and reports it as a warning)
+        warn!("{} ({})", description, impact);
+        self.events.borrow_mut().push(SoundnessEvent {
+            impact,
+            description,
+        });
+    }
+
+    pub fn get_events(&self) -> Vec<SoundnessEvent> {
+        return self.events.borrow().clone();
+    }
+
+    pub fn count(&self, impact: SoundnessImpact) -> usize {
+        return self.events.borrow().iter().filter(|event| event.impact == impact).count();
+    }
+
+    pub fn count_skipped(&self) -> usize {
+        //! Number of constructs that were skipped or approximated without being requested by the user
+        return self.events.borrow().iter().filter(|event| event.impact != SoundnessImpact::Assumption).count();
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+
+use tracing::{debug, error};
+
+use inkwell::context::Context as InkwellContext;
+use inkwell::module::{Module as InkwellModule};
+use inkwell::values::{FunctionValue, PointerValue};
+
+use crate::analysis_context::AnalysisContext;
+use crate::symbolic_execution::{FileDropper, compile_to_bytecode, get_bytecode_file_name, get_inkwell_module, get_temp_file_name};
+use crate::utils::function_utils::{get_defined_function_name, get_function_path};
+
+
+pub const STUB_ATTRIBUTE_PREFIX: &str = "#[wombat::stub(";
+
+
+pub fn preprocess_stub_source(stub_source: &str) -> (String, HashMap<String, String>) {
+    //! Replaces the stub attributes with `#[no_mangle]` so stubs keep their names in the bytecode
+    //! Returns the compilable source and the map of stubbed function paths to stub names
+    let mut stubs = HashMap::<String, String>::new();
+    let mut preprocessed_lines = Vec::<String>::new();
+    let mut pending_target_path: Option<String> = None;
+    for line in stub_source.lines() {
+        let trimmed_line = line.trim();
+        if trimmed_line.starts_with(STUB_ATTRIBUTE_PREFIX) {
+            let start_index = trimmed_line.find('"').unwrap_or(0) + 1;
+            let end_index = trimmed_line.rfind('"').unwrap_or(trimmed_line.len());
+            pending_target_path = Some(String::from(&trimmed_line[start_index..end_index]));
+            preprocessed_lines.push(line.replace(trimmed_line, "#[no_mangle]"));
+            continue;
+        }
+        if let Some(target_path) = &pending_target_path {
+            if let Some(stub_name) = get_defined_function_name(trimmed_line) {
+                stubs.insert(target_path.clone(), stub_name);
+                pending_target_path = None;
+            }
+        }
+        preprocessed_lines.push(String::from(line));
+    }
+    return (preprocessed_lines.join("\n"), stubs);
+}
+
+
+fn strip_generic_arguments(path: &str) -> String {
+    //! Removes generic arguments (ex: `Vec<T,A>::len` -> `Vec::len`) but keeps impl blocks (ex: `<impl i32>`)
+    let mut stripped_path = String::new();
+    let mut depth = 0;
+    let mut previous_char = ' ';
+    for c in path.chars() {
+        if c == '<' && (depth > 0 || previous_char.is_alphanumeric() || previous_char == '_') {
+            depth += 1;
+        } else if c == '>' && depth > 0 {
+            depth -= 1;
+        } else if depth == 0 {
+            stripped_path.push(c);
+        }
+        previous_char = c;
+    }
+    return stripped_path;
+}
+
+
+fn normalize_function_path(path: &str) -> String {
+    //! `std` and `alloc` re-export most of `core`, so they are treated as the same crate
+    let stripped_path = strip_generic_arguments(path);
+    for facade_crate in ["std::", "alloc::"] {
+        if stripped_path.starts_with(facade_crate) {
+            return format!("core::{}", &stripped_path[facade_crate.len()..]);
+        }
+    }
+    return stripped_path;
+}
+
+
+pub fn is_stub_target(call_path: &str, target_path: &str) -> bool {
+    return normalize_function_path(call_path) == normalize_function_path(target_path);
+}
+
+
+pub fn get_stub_function<'ctx>(module: &InkwellModule<'ctx>, analysis: &AnalysisContext, call_operand: &PointerValue) -> Option<FunctionValue<'ctx>> {
+    let call_path = get_function_path(call_operand);
+    for (target_path, stub_name) in &analysis.stubs {
+        if is_stub_target(&call_path, target_path) {
+            return module.get_function(stub_name);
+        }
+    }
+    return None;
+}
+
+
+pub fn link_stub_file<'a>(context: &'a InkwellContext, module: &InkwellModule<'a>, stub_file_name: &String, target: Option<&str>) -> Option<HashMap<String, String>> {
+    //! Compiles the stub file and links it into the module, returning the map of stubbed function paths to stub names
+    let stub_source_result = fs::read_to_string(stub_file_name);
+    if stub_source_result.is_err() {
+        error!("{:?} is an invalid stub file. Please provide a valid file.", stub_file_name);
+        return None;
+    }
+    let (preprocessed_stub_source, stubs) = preprocess_stub_source(&stub_source_result.unwrap());
+
+    let temp_stub_file_name = get_temp_file_name(stub_file_name);
+    fs::write(&temp_stub_file_name, preprocessed_stub_source).expect("Failed to write file!");
+    let _temp_stub_file_dropper = FileDropper {
+        file_name: &temp_stub_file_name,
+    };
+
+    let stub_bytecode_file_name = get_bytecode_file_name(&temp_stub_file_name);
+    // The stubs are compiled for the target of the module they are linked into
+    if !compile_to_bytecode(&temp_stub_file_name, &stub_bytecode_file_name, target, &["--crate-type=lib"]) {
+        return None;
+    }
+    let _temp_stub_bc_file_dropper = FileDropper {
+        file_name: &stub_bytecode_file_name,
+    };
+
+    let stub_module_result = get_inkwell_module(context, &stub_bytecode_file_name);
+    if stub_module_result.is_none() {
+        return None;
+    }
+    if let Err(message) = module.link_in_module(stub_module_result.unwrap()) {
+        error!("Failed to link stub file {:?}: {}", stub_file_name, message.to_string());
+        return None;
+    }
+
+    for (target_path, stub_name) in &stubs {
+        debug!("Stub {:?} replaces calls to {:?}", stub_name, target_path);
+    }
+    return Some(stubs);
+}
@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use inkwell::values::{AnyValue, AnyValueEnum, AsValueRef, BasicValue, BasicValueEnum, IntValue, VectorValue};
+
+use z3::Solver;
+use z3::ast::{Ast, Bool, Int};
+
+use crate::llvm_constant::{get_cast_global, is_global_constant_expression};
+use crate::analysis_context::AnalysisContext;
+use crate::soundness::SoundnessImpact;
+
+
+pub const CONST_NAMESPACE: &str = "const_";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// Function argument or instruction result
+    Register,
+    /// Integer or boolean literal (and vectors of them), whose value is asserted
+    Literal,
+    /// Global variable or function, named by its symbol, or constant expression on a global
+    Global,
+    /// `undef` or `poison`, which may take a different value on every use
+    Undefined,
+    /// Constant which cannot be encoded (ex: `ptrtoint` expression, float or i128 literal), left unconstrained
+    Unsupported,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// LLVM printout of the value, only used for diagnostics
+    pub description: String,
+}
+
+
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    /// Z3 names of the values, keyed on the namespace and the LLVM value handle, with whether the value is an instruction
+    names: RefCell<HashMap<(String, usize), (String, bool)>>,
+    /// Reverse map of the Z3 names to the values they stand for
+    symbols: RefCell<HashMap<String, Symbol>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        return SymbolTable::default();
+    }
+
+    pub fn get_name(&self, value: &dyn AnyValue, solver: &Solver, analysis: &AnalysisContext, namespace: &str) -> String {
+        //! Z3 name of the value in the namespace, stable for the lifetime of the analysis
+        //! The value of literals is asserted on first use, and every use of `undef` or `poison` gets a fresh name
+        let value_enum = value.as_any_value_enum();
+        let kind = get_symbol_kind(&value_enum);
+        let key = (String::from(if kind == SymbolKind::Register { namespace } else { "" }), value.as_value_ref() as usize);
+        if kind != SymbolKind::Undefined {
+            if let Some((name, _)) = self.names.borrow().get(&key) {
+                return name.clone();
+            }
+        }
+
+        let name = match kind {
+            SymbolKind::Register => {
+                let llvm_name = get_llvm_name(&value_enum);
+                if llvm_name.is_empty() {
+                    analysis.get_fresh_name(&format!("{}%", namespace))
+                } else {
+                    format!("{}%{}", namespace, llvm_name)
+                }
+            }
+            SymbolKind::Literal => get_literal_name(&value_enum, solver, analysis),
+            SymbolKind::Global => {
+                let llvm_name = get_llvm_name(&value_enum);
+                // Casts of globals share the name of the global, other constant expressions on globals are named by their printout
+                if llvm_name.is_empty() {
+                    match get_cast_global(&value_enum.into_pointer_value().as_basic_value_enum()) {
+                        Some(global_name) => format!("@{}", global_name),
+                        None => value.print_to_string().to_string(),
+                    }
+                } else {
+                    format!("@{}", llvm_name)
+                }
+            }
+            SymbolKind::Undefined => analysis.get_fresh_name(&format!("{}undef", CONST_NAMESPACE)),
+            SymbolKind::Unsupported => {
+                analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Constant {:?} is unconstrained", value.print_to_string().to_string()));
+                analysis.get_fresh_name(&format!("{}unsupported", CONST_NAMESPACE))
+            }
+        };
+        self.names.borrow_mut().insert(key, (name.clone(), is_instruction(&value_enum)));
+        self.symbols.borrow_mut().entry(name.clone()).or_insert(Symbol {
+            kind,
+            description: value.print_to_string().to_string(),
+        });
+        return name;
+    }
+
+    pub fn forget_instructions(&self) -> () {
+        //! Drops the names of instructions, as passes rewriting the module (ex: converting to DSA) free instructions whose handles get reused
+        //! Arguments, globals and literals keep their names, so that names saved before the rewrite remain valid
+        let mut names = self.names.borrow_mut();
+        let mut symbols = self.symbols.borrow_mut();
+        names.retain(|_, (name, is_instruction)| {
+            if *is_instruction {
+                symbols.remove(name);
+            }
+            return !*is_instruction;
+        });
+    }
+
+    pub fn get_symbol(&self, name: &str) -> Option<Symbol> {
+        //! Value a Z3 name stands for (lanes and fields are looked up by the name of their vector or aggregate)
+        let symbols = self.symbols.borrow();
+        let mut name = name;
+        loop {
+            if let Some(symbol) = symbols.get(name) {
+                return Some(symbol.clone());
+            }
+            let last_dot_index = name.rfind('.')?;
+            if !name[last_dot_index + 1..].chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            name = &name[..last_dot_index];
+        }
+    }
+
+    pub fn get_symbols(&self) -> Vec<(String, Symbol)> {
+        let mut symbols: Vec<(String, Symbol)> = self.symbols.borrow().iter().map(|(name, symbol)| (name.clone(), symbol.clone())).collect();
+        symbols.sort_by(|a, b| a.0.cmp(&b.0));
+        return symbols;
+    }
+}
+
+
+fn get_literal_name(value_enum: &AnyValueEnum, solver: &Solver, analysis: &AnalysisContext) -> String {
+    return match value_enum {
+        AnyValueEnum::IntValue(int_value) => {
+            let literal = get_int_literal(int_value).unwrap();
+            let name = format!("{}{}", CONST_NAMESPACE, literal);
+            assert_literal(solver, &name, &literal);
+            name
+        }
+        AnyValueEnum::VectorValue(vector_value) => {
+            let lane_literals = get_vector_literals(vector_value);
+            // Lanes of a named constant would be shared by all of its uses, so partially undefined vectors get a fresh name
+            let name = if lane_literals.iter().any(|literal| literal.is_none()) {
+                analysis.get_fresh_name(&format!("{}vector", CONST_NAMESPACE))
+            } else {
+                format!("{}<{}>", CONST_NAMESPACE, lane_literals.iter().map(|literal| literal.clone().unwrap()).collect::<Vec<String>>().join(","))
+            };
+            for (lane, literal) in lane_literals.iter().enumerate() {
+                if let Some(literal) = literal {
+                    assert_literal(solver, &format!("{}.{}", name, lane), literal);
+                }
+            }
+            name
+        }
+        _ => panic!("Only integer and vector values can be literals"),
+    };
+}
+
+
+fn get_llvm_name(value_enum: &AnyValueEnum) -> String {
+    let name = match value_enum {
+        AnyValueEnum::ArrayValue(value) => value.get_name(),
+        AnyValueEnum::IntValue(value) => value.get_name(),
+        AnyValueEnum::FloatValue(value) => value.get_name(),
+        AnyValueEnum::PhiValue(value) => value.get_name(),
+        AnyValueEnum::FunctionValue(value) => value.get_name(),
+        AnyValueEnum::PointerValue(value) => value.get_name(),
+        AnyValueEnum::StructValue(value) => value.get_name(),
+        AnyValueEnum::VectorValue(value) => value.get_name(),
+        AnyValueEnum::InstructionValue(value) => return value.get_name().map(|name| name.to_str().unwrap().to_string()).unwrap_or_default(),
+        _ => return String::new(),
+    };
+    return name.to_str().unwrap().to_string();
+}
+
+
+fn is_instruction(value_enum: &AnyValueEnum) -> bool {
+    return match value_enum {
+        AnyValueEnum::ArrayValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::IntValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::FloatValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::PointerValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::StructValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::VectorValue(value) => value.as_instruction().is_some(),
+        AnyValueEnum::PhiValue(..) | AnyValueEnum::InstructionValue(..) => true,
+        _ => false,
+    };
+}
+
+
+fn get_int_literal(int_value: &IntValue) -> Option<String> {
+    //! Literal of a constant integer (`true`/`false` for booleans), None for undefined or wider than 64 bit constants
+    if !int_value.is_const() || int_value.is_undef() {
+        return None;
+    }
+    let literal = int_value.get_sign_extended_constant()?;
+    if int_value.get_type().get_bit_width() == 1 {
+        return Some(String::from(if literal != 0 { "true" } else { "false" }));
+    }
+    return Some(literal.to_string());
+}
+
+
+fn get_vector_literals(vector_value: &VectorValue) -> Vec<Option<String>> {
+    //! Literals of the lanes of a constant vector, None for the undefined lanes
+    let index_type = vector_value.get_type().get_context().i32_type();
+    let mut lane_literals = Vec::<Option<String>>::new();
+    for lane in 0..vector_value.get_type().get_size() {
+        let lane_literal = match vector_value.const_extract_element(index_type.const_int(lane as u64, false)) {
+            BasicValueEnum::IntValue(lane_value) => get_int_literal(&lane_value),
+            _ => None,
+        };
+        lane_literals.push(lane_literal);
+    }
+    return lane_literals;
+}
+
+
+fn get_symbol_kind(value_enum: &AnyValueEnum) -> SymbolKind {
+    return match value_enum {
+        AnyValueEnum::IntValue(value) if value.is_const() => {
+            if value.is_undef() {
+                SymbolKind::Undefined
+            } else if get_int_literal(value).is_some() {
+                SymbolKind::Literal
+            } else {
+                SymbolKind::Unsupported
+            }
+        }
+        AnyValueEnum::VectorValue(value) if value.is_const() => {
+            if value.is_undef() {
+                SymbolKind::Undefined
+            } else if value.get_type().get_element_type().is_int_type() {
+                SymbolKind::Literal
+            } else {
+                SymbolKind::Unsupported
+            }
+        }
+        AnyValueEnum::PointerValue(value) if value.is_const() => {
+            if value.is_undef() {
+                SymbolKind::Undefined
+            } else if !value.get_name().to_bytes().is_empty() || is_global_constant_expression(&value.as_basic_value_enum()) {
+                SymbolKind::Global
+            } else {
+                SymbolKind::Unsupported
+            }
+        }
+        AnyValueEnum::FunctionValue(..) => SymbolKind::Global,
+        AnyValueEnum::FloatValue(value) if value.is_const() => SymbolKind::Unsupported,
+        AnyValueEnum::StructValue(value) if value.is_const() => SymbolKind::Unsupported,
+        AnyValueEnum::ArrayValue(value) if value.is_const() => SymbolKind::Unsupported,
+        _ => SymbolKind::Register,
+    };
+}
+
+
+fn assert_literal(solver: &Solver, name: &str, literal: &str) -> () {
+    if literal.eq("true") || literal.eq("false") {
+        let bool_const = Bool::new_const(solver.get_context(), name);
+        solver.assert(&bool_const._eq(&Bool::from_bool(solver.get_context(), literal.eq("true"))));
+    } else {
+        let int_const = Int::new_const(solver.get_context(), name);
+        solver.assert(&int_const._eq(&Int::from_i64(solver.get_context(), literal.parse::<i64>().unwrap())));
+    }
+}
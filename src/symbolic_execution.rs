@@ -8,15 +8,27 @@ use inkwell::context::Context as InkwellContext;
 use inkwell::module::{Module as InkwellModule};
 use inkwell::memory_buffer::MemoryBuffer;
 use inkwell::passes::{PassManager, PassManagerBuilder};
+use inkwell::values::FunctionValue;
 
-use z3::{Config, Solver, SatResult};
+use z3::{Config, Params, Solver, SatResult};
 use z3::Context as Z3Context;
 use z3::ast::{Int, Bool, Ast};
 
+use crate::analysis_context::AnalysisContext;
 use crate::codegen::codegen_function::codegen_function;
+use crate::codegen::codegen_basic_block::{get_entry_probe_name, get_exit_probe_name};
+use crate::codegen::codegen_str::{constrain_str_parameter, get_str_length_parameter, get_str_literal};
+use crate::codegen::codegen_undefined_behaviour::{constrain_pointer_parameter, get_address_var_name};
+use crate::companion::{get_companion_crate_args, get_companion_rlib_file_name};
+use crate::contracts::{encode_contract_conditions, get_expression_variables};
+use crate::harness::is_entry_point;
+use crate::module_loader::{load_module, LoadedModule};
+use crate::panics::PANIC_SITE_VAR_NAME;
+use crate::parameter_domain::{get_all_parameter_ranges, get_range_constraint, get_source_enums, get_source_parameter_types, get_str_parameters, get_typed_value_string};
+use crate::soundness::SoundnessImpact;
 use crate::utils::pretty_print::{print_file_functions};
-use crate::utils::function_utils::{get_function_name, get_function_by_name, get_all_function_argument_names};
-use crate::utils::var_utils::{get_min_max_signed_int, get_var_name};
+use crate::utils::function_utils::{get_function_name, get_function_by_name, get_all_function_argument_names, strip_function_hash};
+use crate::utils::var_utils::{get_min_max_signed_int_of_width, get_var_name};
 
 
 // pub const MAIN_FUNCTION_NAMESPACE: &str = "wombat_symx_";
@@ -24,10 +36,156 @@ pub const MAIN_FUNCTION_NAMESPACE: &str = "";
 pub const COMMON_END_NODE: &str = "common_end_node";
 pub const PANIC_VAR_NAME: &str = "is_panic";
 pub const MAIN_FUNCTION_RETURN_REGISTER: &str = "wombat_symx_return_register";
+pub const ENTRY_PROBE_PREFIX: &str = "wombat_symx_entry_probe_";
+pub const EXIT_PROBE_PREFIX: &str = "wombat_symx_exit_probe_";
+
+pub const EXIT_CODE_SAFE: i32 = 0;
+pub const EXIT_CODE_UNSAFE: i32 = 1;
+pub const EXIT_CODE_UNKNOWN: i32 = 2;
+pub const EXIT_CODE_ERROR: i32 = 3;
+
+
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptions {
+    /// Solver timeout in milliseconds
+    pub timeout: Option<u64>,
+    /// Solver resource limit (a deterministic alternative to the timeout)
+    pub rlimit: Option<u32>,
+    /// Maximum memory used by the solver in megabytes
+    pub memory: Option<u32>,
+    /// Refuse to give a verdict if any construct had to be skipped or approximated
+    pub strict: bool,
+    /// How calls to functions outside of the module are encoded
+    pub external_calls: ExternalCallPolicy,
+    /// Rust file with user-written stubs replacing called functions
+    pub stub_file: Option<String>,
+    /// Postconditions checked in addition to the `#[wombat::ensures(..)]` attributes of the function
+    pub ensures: Vec<String>,
+    /// Report undefined behaviour of unsafe code like panics (ex: null pointer dereferences, reachable `unreachable_unchecked`)
+    pub check_undefined_behaviour: bool,
+    /// Target triple the file is compiled for (ex: `thumbv7em-none-eabihf`), the host if None
+    pub target: Option<String>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalCallPolicy {
+    /// The return value is unconstrained within its type's domain and panics of the callee are ignored
+    Havoc,
+    /// As havoc, but the user assumes the callee never panics
+    AssumeNoPanic,
+    /// As havoc, but the callee may panic on any call
+    AssumeMayPanic,
+}
+
+impl Default for ExternalCallPolicy {
+    fn default() -> Self {
+        return ExternalCallPolicy::Havoc;
+    }
+}
+
+impl std::str::FromStr for ExternalCallPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "havoc" => Ok(ExternalCallPolicy::Havoc),
+            "no-panic" => Ok(ExternalCallPolicy::AssumeNoPanic),
+            "may-panic" => Ok(ExternalCallPolicy::AssumeMayPanic),
+            _ => Err(format!("Unknown external call policy {:?} (expected havoc, no-panic or may-panic)", s)),
+        };
+    }
+}
+
+impl std::fmt::Display for ExternalCallPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalCallPolicy::Havoc => write!(f, "havoc"),
+            ExternalCallPolicy::AssumeNoPanic => write!(f, "no-panic"),
+            ExternalCallPolicy::AssumeMayPanic => write!(f, "may-panic"),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnknownReason {
+    Timeout,
+    ResourceLimit,
+    MemoryLimit,
+    /// The solver gave up on the formula (ex: non-linear integer arithmetic)
+    IncompleteTheory(String),
+    /// The successors of an instruction could not be encoded, so no proof covers them (ex: an unsupported terminator)
+    UnsupportedInstruction(String),
+    Other(String),
+}
+
+impl UnknownReason {
+    pub fn from_solver_reason(reason: &str) -> UnknownReason {
+        if reason.contains("timeout") || reason.contains("canceled") {
+            UnknownReason::Timeout
+        } else if reason.contains("resource limit") || reason.contains("rlimit") {
+            UnknownReason::ResourceLimit
+        } else if reason.contains("memory") {
+            UnknownReason::MemoryLimit
+        } else if reason.contains("incomplete") {
+            UnknownReason::IncompleteTheory(String::from(reason))
+        } else {
+            UnknownReason::Other(String::from(reason))
+        }
+    }
+}
+
+impl std::fmt::Display for UnknownReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnknownReason::Timeout => write!(f, "timeout"),
+            UnknownReason::ResourceLimit => write!(f, "resource limit exceeded"),
+            UnknownReason::MemoryLimit => write!(f, "memory limit exceeded"),
+            UnknownReason::IncompleteTheory(reason) => write!(f, "incomplete theory: {}", reason),
+            UnknownReason::UnsupportedInstruction(instruction) => write!(f, "unsupported instruction: {}", instruction),
+            UnknownReason::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// No panic is reachable, unless it is hidden by one of the unsound constructs
+    Safe { unsound_constructs: usize },
+    Unsafe,
+    Unknown(UnknownReason),
+}
+
+impl Verdict {
+    pub fn is_safe(&self) -> bool {
+        return matches!(self, Verdict::Safe { .. });
+    }
+
+    pub fn get_exit_code(&self) -> i32 {
+        return match self {
+            Verdict::Safe { .. } => EXIT_CODE_SAFE,
+            Verdict::Unsafe => EXIT_CODE_UNSAFE,
+            Verdict::Unknown(..) => EXIT_CODE_UNKNOWN,
+        };
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Safe { unsound_constructs: 0 } => write!(f, "safe"),
+            Verdict::Safe { unsound_constructs } => write!(f, "safe (unsound: {} unsupported constructs)", unsound_constructs),
+            Verdict::Unsafe => write!(f, "unsafe"),
+            Verdict::Unknown(reason) => write!(f, "unknown ({})", reason),
+        }
+    }
+}
 
 
-struct FileDropper<'a> {
-    file_name: &'a String,
+pub struct FileDropper<'a> {
+    pub file_name: &'a String,
 }
 
 impl Drop for FileDropper<'_> {
@@ -37,7 +195,7 @@ impl Drop for FileDropper<'_> {
 }
 
 
-fn get_inkwell_module<'a>(context: &'a InkwellContext, file_name: &String) -> Option<InkwellModule<'a>> {
+pub fn get_inkwell_module<'a>(context: &'a InkwellContext, file_name: &String) -> Option<InkwellModule<'a>> {
     let path = Path::new(&file_name);
     if !path.is_file() {
         error!("{:?} is an invalid file. Please provide a valid file.", file_name);
@@ -66,7 +224,8 @@ pub fn get_module_name_from_file_name(file_name: &String) -> String {
 }
 
 
-fn convert_to_dsa<'a>(module: &InkwellModule) -> () {
+pub fn convert_to_dsa<'a>(module: &InkwellModule, analysis: &AnalysisContext) -> () {
+    //! Promotes stack slots to registers, the names given to the instructions before are dropped as they may be freed
     let pass_manager_builder = PassManagerBuilder::create();
     let pass_manager = PassManager::create(module);
     pass_manager.add_promote_memory_to_register_pass();
@@ -77,45 +236,241 @@ fn convert_to_dsa<'a>(module: &InkwellModule) -> () {
         pass_manager.run_on(&current_function);
         next_function = current_function.get_next_function();
     }
+    analysis.symbols.forget_instructions();
 }
 
 
-pub fn symbolic_execution(file_name: &String, function_name: &String) -> Option<bool> {
-    let context = InkwellContext::create();
+pub fn get_temp_file_name(file_name: &String) -> String {
+    //! Name of a temporary file next to the given file
+    let mut temp_file_path_base_end_index = 0;
+    if file_name.rfind('/').is_some() {
+        temp_file_path_base_end_index = file_name.rfind('/').unwrap() + 1;
+    }
+    return format!("{}temp_wombat_symx_{}", &file_name[0..temp_file_path_base_end_index], &file_name[temp_file_path_base_end_index..file_name.len()]);
+}
 
-    let bytecode_file_name = format!("{}.bc", &file_name[0..file_name.rfind('.').unwrap_or(file_name.len())]);
 
-    Command::new("rustc")
+pub fn get_bytecode_file_name(file_name: &String) -> String {
+    return format!("{}.bc", &file_name[0..file_name.rfind('.').unwrap_or(file_name.len())]);
+}
+
+
+pub fn compile_to_bytecode(file_name: &String, bytecode_file_name: &String, target: Option<&str>, extra_args: &[&str]) -> bool {
+    //! Bytecode is not linked, so it can be emitted for any target whose standard library is installed
+    //! Returns false if rustc fails (ex: a compile error or a target whose standard library is missing)
+    let target_args = match target {
+        Some(target) => vec!["--target", target],
+        None => Vec::new(),
+    };
+    let status = Command::new("rustc")
         .args(["--emit=llvm-bc", &file_name, "-o", &bytecode_file_name])
+        .args(target_args)
+        .args(extra_args)
         .status()
         .expect("Failed to generate bytecode file!");
+    if !status.success() {
+        error!("Failed to compile {:?} to bytecode", file_name);
+        return false;
+    }
+    return true;
+}
+
+
+pub fn get_solver_config(options: &AnalysisOptions) -> Config {
+    //! Creates the Z3 config, passing through the resource limits of the analysis
+    let mut cfg = Config::new();
+    if let Some(timeout) = options.timeout {
+        cfg.set_timeout_msec(timeout);
+    }
+    if let Some(rlimit) = options.rlimit {
+        cfg.set_param_value("rlimit", &rlimit.to_string());
+    }
+    return cfg;
+}
+
+
+pub fn get_solver<'ctx>(ctx: &'ctx Z3Context, options: &AnalysisOptions) -> Solver<'ctx> {
+    //! Creates the solver, with the memory limit of the analysis set on the solver rather than process-wide
+    let solver = Solver::new(ctx);
+    if let Some(memory) = options.memory {
+        let mut params = Params::new(ctx);
+        params.set_u32("max_memory", memory);
+        solver.set_params(&params);
+    }
+    return solver;
+}
+
+
+pub fn is_refused_by_strict_mode(analysis: &AnalysisContext) -> bool {
+    if !analysis.options.strict || analysis.ledger.count_skipped() == 0 {
+        return false;
+    }
+    for event in analysis.ledger.get_events() {
+        if event.impact != SoundnessImpact::Assumption {
+            error!("Strict mode: {} ({})", event.description, event.impact);
+        }
+    }
+    return true;
+}
+
+
+pub fn print_soundness_report(analysis: &AnalysisContext) -> () {
+    let events = analysis.ledger.get_events();
+    if events.len() == 0 {
+        return;
+    }
+    println!("\nSkipped, approximated or assumed constructs:");
+    for event in events {
+        println!("\t[{}] {}", event.impact, event.description);
+    }
+}
+
+
+pub fn debug_symbol_table(analysis: &AnalysisContext) -> () {
+    //! Prints the LLVM values the Z3 names of the model stand for
+    debug!("\nSymbols:");
+    for (name, symbol) in analysis.symbols.get_symbols() {
+        debug!("\t{} ({:?}): {}", name, symbol.kind, symbol.description.trim());
+    }
+}
+
+
+pub fn get_panic_var_name(panic_target_node: &str) -> String {
+    //! Name of the variable telling whether the given panic target node was reached by a panic
+    if panic_target_node == COMMON_END_NODE {
+        return String::from(PANIC_VAR_NAME);
+    }
+    return format!("{}.{}", panic_target_node, PANIC_VAR_NAME);
+}
 
-    let _temp_bc_file_dropper = FileDropper {
-        file_name: &bytecode_file_name,
+
+pub fn get_verdict(solver: &Solver, analysis: &AnalysisContext, satisfiability: SatResult) -> Verdict {
+    return match satisfiability {
+        // A proof says nothing about the code after an unsupported instruction
+        SatResult::Unsat => match analysis.unsupported_instructions.borrow().first() {
+            Some(instruction) => Verdict::Unknown(UnknownReason::UnsupportedInstruction(instruction.clone())),
+            None => Verdict::Safe { unsound_constructs: analysis.ledger.count(SoundnessImpact::Unsound) },
+        },
+        SatResult::Sat => Verdict::Unsafe,
+        SatResult::Unknown => {
+            let reason = solver.get_reason_unknown().unwrap_or(String::from("unknown"));
+            Verdict::Unknown(UnknownReason::from_solver_reason(&reason))
+        }
     };
+}
+
+
+pub fn get_implementation_verdicts(solver: &Solver, analysis: &AnalysisContext) -> Vec<(String, Verdict)> {
+    //! Safety of the function for each implementation called through trait objects, checked one implementation at a time
+    let virtual_calls = analysis.virtual_calls.borrow();
+    let mut implementations = Vec::<String>::new();
+    for virtual_call in virtual_calls.iter() {
+        for (implementation, _) in &virtual_call.implementations {
+            if !implementations.contains(implementation) {
+                implementations.push(implementation.clone());
+            }
+        }
+    }
+
+    let mut implementation_verdicts = Vec::new();
+    for implementation in implementations {
+        solver.push();
+        for virtual_call in virtual_calls.iter() {
+            // Calls on trait objects of other traits are left unconstrained
+            if let Some((_, vtable_address_var_name)) = virtual_call.implementations.iter().find(|(other, _)| *other == implementation) {
+                let vtable_var = Int::new_const(solver.get_context(), virtual_call.vtable_var_name.as_str());
+                let vtable_address_var = Int::new_const(solver.get_context(), vtable_address_var_name.as_str());
+                solver.assert(&vtable_var._eq(&vtable_address_var));
+            }
+        }
+        let verdict = get_verdict(solver, analysis, solver.check());
+        solver.pop(1);
+        implementation_verdicts.push((implementation, verdict));
+    }
+    return implementation_verdicts;
+}
+
+
+pub fn print_implementation_verdicts(implementation_verdicts: &Vec<(String, Verdict)>) -> () {
+    if implementation_verdicts.is_empty() {
+        return;
+    }
+    println!("\nSafety per implementation:");
+    for (implementation, verdict) in implementation_verdicts {
+        println!("\t{}: {}", implementation, verdict);
+    }
+}
 
-    let module_result = get_inkwell_module(&context, &bytecode_file_name);
-    if module_result.is_none() {
+
+pub fn constrain_function_parameters(module: &InkwellModule, function: &FunctionValue, solver: &Solver, analysis: &AnalysisContext, namespace: &str, function_name: &String) -> () {
+    // Enums, `char` and `NonZero*` only take the values of their `!range` metadata
+    let parameter_ranges = analysis.parameter_ranges.get(&get_function_name(&function.as_global_value().as_pointer_value()));
+    // constrain int inputs
+    for (parameter_index, input) in function.get_params().iter().enumerate() {
+        // TODO: Support other input types
+        if input.get_type().to_string().eq("\"i1\"") {
+            continue;
+        } else if input.get_type().is_int_type() {
+            let arg_name = get_var_name(input, &solver, analysis, namespace);
+            let arg = Int::new_const(&solver.get_context(), arg_name.as_str());
+            let (min_int_val, max_int_val) = get_min_max_signed_int_of_width(input.get_type().into_int_type().get_bit_width());
+            let min_int = Int::from_i64(solver.get_context(), min_int_val);
+            let max_int = Int::from_i64(solver.get_context(), max_int_val);
+            solver.assert(&Bool::and(solver.get_context(), &[&arg.ge(&min_int), &arg.le(&max_int)]));
+            if let Some(Some(ranges)) = parameter_ranges.and_then(|parameter_ranges| parameter_ranges.get(parameter_index)) {
+                solver.assert(&get_range_constraint(solver, &arg_name, input.get_type().into_int_type().get_bit_width(), ranges));
+                analysis.ledger.record(SoundnessImpact::Assumption, format!("Parameter {} of {} is assumed to be a valid value of its type (!range {:?})", arg_name, function_name, ranges));
+            }
+        } else if let Some(length) = get_str_length_parameter(function, parameter_index as u32, &analysis.str_parameters) {
+            // The length is constrained with the other integers, and bounded by the string
+            constrain_str_parameter(solver, analysis, &get_var_name(input, &solver, analysis, namespace), &get_var_name(&length, &solver, analysis, namespace));
+            if analysis.options.check_undefined_behaviour {
+                constrain_pointer_parameter(module, function, parameter_index as u32, solver, analysis, namespace);
+            }
+        } else if input.get_type().is_pointer_type() && analysis.options.check_undefined_behaviour {
+            // Only the address of pointers is modelled, the memory they point to is unconstrained
+            constrain_pointer_parameter(module, function, parameter_index as u32, solver, analysis, namespace);
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Memory pointed to by input parameter {} to {} is unconstrained", get_var_name(input, &solver, analysis, namespace), function_name));
+        } else {
+            analysis.ledger.record(SoundnessImpact::OverApproximation, format!("Currently unsupported type {:?} for input parameter to {}", input.get_type().to_string(), function_name));
+        }
+    }
+}
+
+
+pub fn symbolic_execution(file_name: &String, function_name: &String, options: &AnalysisOptions) -> Option<Verdict> {
+    let context = InkwellContext::create();
+
+    let loaded_module_option = load_module(&context, file_name, function_name, options, &[]);
+    if loaded_module_option.is_none() {
         return None;
     }
+    let LoadedModule { module, module_name, contract, source, stubs } = loaded_module_option.unwrap();
+
+    let mut analysis = AnalysisContext::new(options);
+    analysis.stubs = stubs;
 
-    let module = module_result.unwrap();
-    let module_name = get_module_name_from_file_name(&bytecode_file_name);
     let target_function_name_prefix = format!("{}::{}", module_name, function_name);
 
     // Initialize the Z3 and Builder objects
-    let cfg = Config::new();
+    let cfg = get_solver_config(options);
     let ctx = Z3Context::new(&cfg);
-    let solver = Solver::new(&ctx);
+    let solver = get_solver(&ctx, options);
 
     // Save function argument names before removing store/alloca instructions
-    let all_func_arg_names = get_all_function_argument_names(&module, &solver, MAIN_FUNCTION_NAMESPACE);
+    let all_func_arg_names = get_all_function_argument_names(&module, &solver, &analysis, MAIN_FUNCTION_NAMESPACE);
 
     // Convert to dynamic single assignment form (DSA)
 
     print_file_functions(&module);
 
-    convert_to_dsa(&module);
+    // Parameter ranges are read from loads which are removed when converting to DSA
+    analysis.parameter_ranges = get_all_parameter_ranges(&module);
+    // `&str` parameters are told apart from byte slices by their source type
+    let source_function_name = strip_function_hash(function_name).rsplit("::").next().unwrap();
+    let parameter_types = get_source_parameter_types(&source, source_function_name);
+    analysis.str_parameters = get_str_parameters(&parameter_types);
+    convert_to_dsa(&module, &analysis);
 
     let function_option = get_function_by_name(&module, &target_function_name_prefix);
     if function_option.is_none() {
@@ -130,27 +485,36 @@ pub fn symbolic_execution(file_name: &String, function_name: &String) -> Option<
     let func_arg_names = func_arg_names_option.unwrap();
 
     let call_stack = function.get_name().to_str().unwrap();
-    codegen_function(&module, &function, &solver, MAIN_FUNCTION_NAMESPACE, call_stack, COMMON_END_NODE, MAIN_FUNCTION_RETURN_REGISTER);
+    codegen_function(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, call_stack, COMMON_END_NODE, COMMON_END_NODE, MAIN_FUNCTION_RETURN_REGISTER);
 
-    // constrain int inputs
-    for input in function.get_params() {
-        // TODO: Support other input types
-        if input.get_type().to_string().eq("\"i1\"") {
-            continue;
-        } else if input.get_type().is_int_type() {
-            let arg = Int::new_const(&solver.get_context(), get_var_name(&input, &solver, MAIN_FUNCTION_NAMESPACE));
-            let (min_int_val, max_int_val) = get_min_max_signed_int(&input.get_type().to_string().as_str().replace("\"", "")[1..]);
-            let min_int = Int::from_i64(solver.get_context(), min_int_val);
-            let max_int = Int::from_i64(solver.get_context(), max_int_val);
-            solver.assert(&Bool::and(solver.get_context(), &[&arg.ge(&min_int), &arg.le(&max_int)]));
-        } else {
-            warn!("Currently unsupported type {:?} for input parameter to {}", input.get_type().to_string(), function_name);
-        }
+    constrain_function_parameters(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, function_name);
+
+    // Preconditions are assumed on the arguments and postconditions are checked whenever the function returns
+    let contract_variables = get_expression_variables(&function, func_arg_names, &source, function_name);
+    let precondition_option = encode_contract_conditions(solver.get_context(), &contract.requires, &contract_variables);
+    let mut ensures = contract.ensures.clone();
+    ensures.extend(options.ensures.iter().cloned());
+    let postcondition_option = encode_contract_conditions(solver.get_context(), &ensures, &contract_variables);
+    if precondition_option.is_none() || postcondition_option.is_none() {
+        return None;
+    }
+    solver.assert(&precondition_option.unwrap());
+    let postcondition = postcondition_option.unwrap();
+
+    if is_refused_by_strict_mode(&analysis) {
+        return None;
+    }
+
+    // Reachability probes are only falsified by the dead code analysis
+    for bb in function.get_basic_blocks() {
+        let node = format!("{}{}", MAIN_FUNCTION_NAMESPACE, bb.get_name().to_str().unwrap());
+        solver.assert(&Bool::new_const(solver.get_context(), get_entry_probe_name(&node)));
+        solver.assert(&Bool::new_const(solver.get_context(), get_exit_probe_name(&node)));
     }
 
     let common_end_node_var = Bool::new_const(solver.get_context(), String::from(COMMON_END_NODE));
     let panic_var = Bool::new_const(solver.get_context(), String::from(PANIC_VAR_NAME));
-    solver.assert(&common_end_node_var._eq(&panic_var.not()));
+    solver.assert(&common_end_node_var._eq(&Bool::and(solver.get_context(), &[&panic_var.not(), &postcondition])));
 
     let start_node = function.get_first_basic_block().unwrap();
     let start_node_var_name = format!("{}{}", MAIN_FUNCTION_NAMESPACE, start_node.get_name().to_str().unwrap());
@@ -159,25 +523,50 @@ pub fn symbolic_execution(file_name: &String, function_name: &String) -> Option<
 
     debug!("{}", format!("\nSolver:\n{:?}", solver));
 
+    // Implementations are checked first, as the model of the whole function is needed below
+    let implementation_verdicts = get_implementation_verdicts(&solver, &analysis);
+
     // Attempt resolving the model (and obtaining the respective arg values if panic found)
     let satisfiability = solver.check();
 
-    let is_confirmed_safe = satisfiability == SatResult::Unsat;
-    let is_confirmed_unsafe = satisfiability == SatResult::Sat;
-    println!("\nFunction safety: {}", if is_confirmed_safe {"safe"} else if is_confirmed_unsafe {"unsafe"} else {"unknown"});
+    let verdict = get_verdict(&solver, &analysis, satisfiability);
+    println!("\nFunction safety: {}", verdict);
+    print_implementation_verdicts(&implementation_verdicts);
+    print_soundness_report(&analysis);
 
     // Exhibit a pathological input if the function is unsafe
-    if is_confirmed_unsafe {
+    if verdict == Verdict::Unsafe {
         let model = solver.get_model().unwrap();
         debug!("\n{:?}", model);
+        debug_symbol_table(&analysis);
         println!("\nUnsafe values:");
         let mut argument_values = Vec::<String>::new();
+        // Values of enums, `char` and `NonZero*` are printed as Rust expressions of their source type (ex: `Color::Green`)
+        let source_enums = get_source_enums(&source);
         for (arg_name, z3_name, var_type) in func_arg_names {
             // TODO: Support non-int params
             let arg_name_without_namespace = &arg_name[MAIN_FUNCTION_NAMESPACE.len()..];
             let arg_name_without_namespace_and_percent = arg_name_without_namespace.replace("%", "");
+            let str_parameter_name = arg_name_without_namespace_and_percent.rsplit_once('.')
+                .map(|(name, _)| name.to_string())
+                .filter(|name| analysis.str_parameters.contains(name));
             let value_string;
-            if var_type.to_string().eq("\"i1\"") {
+            if str_parameter_name.is_some() && var_type.is_int_type() {
+                // NO-OP: the length of `&str` parameters is printed with their bytes
+            } else if let Some(str_parameter_name) = str_parameter_name {
+                // `&str` parameters are printed as an escaped string literal (ex: `"\u{301}"`)
+                let length_z3_name = func_arg_names.iter()
+                    .find(|(other_arg_name, _, other_var_type)| other_arg_name.ends_with(&format!("{}.1", str_parameter_name)) && other_var_type.is_int_type())
+                    .map(|(_, other_z3_name, _)| other_z3_name.clone())
+                    .unwrap_or_default();
+                match get_str_literal(&model, &solver, z3_name, &length_z3_name) {
+                    Some(literal) => {
+                        println!("\t{:?} = {}", &str_parameter_name, literal);
+                        argument_values.push(literal);
+                    }
+                    None => warn!("{} does not hold a valid string!", str_parameter_name),
+                }
+            } else if var_type.to_string().eq("\"i1\"") {
                 let value = Bool::new_const(solver.get_context(), z3_name.as_str());
                 value_string = format!("{:?}", model.eval(&value, true).unwrap());
                 let cleaned_value_string = &value_string.replace("(", "").replace(")", "").replace(" ", "");
@@ -186,40 +575,117 @@ pub fn symbolic_execution(file_name: &String, function_name: &String) -> Option<
             } else if var_type.is_int_type() {
                 let value = Int::new_const(solver.get_context(), z3_name.as_str());
                 value_string = format!("{:?}", model.eval(&value, true).unwrap());
-                let cleaned_value_string = &value_string.replace("(", "").replace(")", "").replace(" ", "");
+                let mut cleaned_value_string = value_string.replace("(", "").replace(")", "").replace(" ", "");
+                let typed_value_string = match (cleaned_value_string.parse::<i64>(), parameter_types.get(&arg_name_without_namespace_and_percent)) {
+                    (Ok(value), Some(source_type)) => get_typed_value_string(value, var_type.into_int_type().get_bit_width(), source_type, &source_enums),
+                    _ => None,
+                };
+                if let Some(typed_value_string) = typed_value_string {
+                    cleaned_value_string = typed_value_string;
+                }
                 println!("\t{:?} = {}", &arg_name_without_namespace_and_percent, cleaned_value_string);
                 argument_values.push(cleaned_value_string.to_string());
+            } else if var_type.is_pointer_type() && analysis.options.check_undefined_behaviour {
+                // Pointers are given by their address (ex: `0 as _` for a null pointer)
+                let address = Int::new_const(solver.get_context(), get_address_var_name(z3_name));
+                let address_string = format!("{:?}", model.eval(&address, true).unwrap());
+                let cleaned_value_string = format!("{} as _", address_string.replace("(", "").replace(")", "").replace(" ", ""));
+                println!("\t{:?} = {}", &arg_name_without_namespace_and_percent, cleaned_value_string);
+                argument_values.push(cleaned_value_string);
             } else {
                 warn!("{} is not a supported parameter type!", var_type);
             }
         };
+
+        // Values read from the environment, in the order they are read in
+        let inputs = analysis.inputs.borrow();
+        if inputs.len() > 0 {
+            println!("\nUnsafe inputs:");
+        }
+        for input in inputs.iter() {
+            let value_string = if input.is_bool {
+                format!("{:?}", model.eval(&Bool::new_const(solver.get_context(), input.var_name.as_str()), true).unwrap())
+            } else {
+                format!("{:?}", model.eval(&Int::new_const(solver.get_context(), input.var_name.as_str()), true).unwrap())
+            };
+            let input_name = match input.field {
+                Some(field) => format!("{}.{}", input.function_path, field),
+                None => input.function_path.clone(),
+            };
+            println!("\t{} = {}", input_name, value_string.replace("(", "").replace(")", "").replace(" ", ""));
+        }
         
-        let mut source_file_content = fs::read_to_string(file_name).unwrap();
+        if model.eval(&panic_var, true).unwrap().as_bool() == Some(false) {
+            println!("\nPostcondition violated with the unsafe values");
+            return Some(verdict);
+        }
+
+        // The panic site is only constrained when the counterexample panics in a panic function
+        let panic_site_var = Int::new_const(solver.get_context(), PANIC_SITE_VAR_NAME);
+        let panic_sites = analysis.panic_sites.borrow();
+        let panic_site_option = model.eval(&panic_site_var, false).and_then(|panic_site| panic_site.as_u64()).and_then(|panic_site| panic_sites.get(panic_site as usize));
+        if let Some(panic_site) = panic_site_option {
+            match &panic_site.message {
+                Some(message) if panic_site.is_undefined_behaviour => println!("\nUndefined behaviour in {}: {}", panic_site.function_path, message),
+                Some(message) => println!("\nPanic message: {}", message),
+                None => println!("\nPanics in: {}", panic_site.function_path),
+            }
+        }
+
+        // The inputs of the entry point come from its environment, so it cannot be called with them
+        if is_entry_point(function_name) {
+            return Some(verdict);
+        }
+
+        // Executables of another target cannot be run on the host
+        if let Some(target) = &options.target {
+            println!("\nThe function is not called with the unsafe values, as it is compiled for {}", target);
+            return Some(verdict);
+        }
+
+        let mut source_file_content = source.clone();
         source_file_content = source_file_content.replace("fn main", "fn _main");
-        source_file_content = format!("{}\nfn main() {{{}(", source_file_content, function_name);
+        source_file_content = format!("{}\nfn main() {{{}(", source_file_content, strip_function_hash(function_name));
         for argument_value in argument_values {
             source_file_content = format!("{}{},", source_file_content, argument_value);
         }
         source_file_content = format!("{});}}", source_file_content);
         debug!("{}", source_file_content);
 
-        let mut temp_file_path_base_end_index = 0;
-        if file_name.rfind('/').is_some() {
-            temp_file_path_base_end_index = file_name.rfind('/').unwrap() + 1;
-        }
-        let temp_source_file_name = format!("{}temp_wombat_symx_{}", &file_name[0..temp_file_path_base_end_index], &file_name[temp_file_path_base_end_index..file_name.len()]);
+        let temp_source_file_name = get_temp_file_name(file_name);
         fs::write(&temp_source_file_name, format!("{}", source_file_content)).expect("Failed to write file!");
 
         let _temp_source_file_dropper = FileDropper {
             file_name: &temp_source_file_name,
         };
 
+        // The companion crate is compiled again, as the one of the analysed module is removed once it is loaded
+        let companion_args_option = get_companion_crate_args(&temp_source_file_name, None);
+        if companion_args_option.is_none() {
+            return Some(verdict);
+        }
+        let companion_args = companion_args_option.unwrap();
+        let companion_rlib_file_name = get_companion_rlib_file_name(&temp_source_file_name);
+        let _companion_rlib_file_dropper = if companion_args.len() > 0 {
+            Some(FileDropper {
+                file_name: &companion_rlib_file_name,
+            })
+        } else {
+            None
+        };
+
         let temp_executable_file_name = &temp_source_file_name[0..temp_source_file_name.rfind('.').unwrap()];
 
-        Command::new("rustc")
+        let compile_status = Command::new("rustc")
             .args([&temp_source_file_name, "-o", &temp_executable_file_name])
+            .args(&companion_args)
             .status()
             .expect("Failed to generate executable file!");
+        if !compile_status.success() {
+            // Ex: parameters passed as several LLVM values, such as `Option<i32>`
+            println!("\nThe function could not be called with the unsafe values");
+            return Some(verdict);
+        }
 
         let _temp_executable_file_dropper = FileDropper {
             file_name: &String::from(temp_executable_file_name),
@@ -229,5 +695,5 @@ pub fn symbolic_execution(file_name: &String, function_name: &String) -> Option<
         println!("\t{}", std::str::from_utf8(&Command::new(format!("./{}", temp_executable_file_name)).output().ok().unwrap().stderr).unwrap().replace("\n", "\n\t"));
     }
 
-    return Some(is_confirmed_safe);
+    return Some(verdict);
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use inkwell::basic_block::BasicBlock;
+use inkwell::module::{Module as InkwellModule};
+use inkwell::values::{AnyValue, InstructionValue};
+
+
+pub type DebugLocations = HashMap<String, (u32, u32)>;
+
+
+fn get_debug_location_field(location_str: &str, field: &str) -> Option<u32> {
+    let field_prefix = format!("{}: ", field);
+    let start_index = location_str.find(&field_prefix)? + field_prefix.len();
+    let end_index = location_str[start_index..].find(|c: char| !c.is_ascii_digit()).unwrap_or(location_str[start_index..].len()) + start_index;
+    return location_str[start_index..end_index].parse::<u32>().ok();
+}
+
+
+pub fn get_debug_locations(module: &InkwellModule) -> DebugLocations {
+    //! Maps the metadata ids of all `DILocation` nodes in the module (ex: `!42`) to their (line, column)
+    let mut debug_locations = DebugLocations::new();
+    let module_llvm_string = module.print_to_string();
+    for line in module_llvm_string.to_str().unwrap().lines() {
+        if !line.starts_with("!") || !line.contains("= !DILocation(") {
+            continue;
+        }
+        let metadata_id = line[0..line.find(" ").unwrap_or(line.len())].to_string();
+        let line_number = get_debug_location_field(line, "line").unwrap_or(0);
+        let column_number = get_debug_location_field(line, "column").unwrap_or(0);
+        debug_locations.insert(metadata_id, (line_number, column_number));
+    }
+    return debug_locations;
+}
+
+
+pub fn get_instruction_debug_location(instruction: &InstructionValue, debug_locations: &DebugLocations) -> Option<(u32, u32)> {
+    let instruction_llvm_string = instruction.print_to_string();
+    let instruction_str = instruction_llvm_string.to_str().unwrap();
+    let start_index = instruction_str.find("!dbg ")? + "!dbg ".len();
+    let end_index = instruction_str[start_index..].find(|c: char| c == ',' || c == ' ').unwrap_or(instruction_str[start_index..].len()) + start_index;
+    return debug_locations.get(&instruction_str[start_index..end_index]).copied();
+}
+
+
+pub fn get_basic_block_debug_location(bb: &BasicBlock, debug_locations: &DebugLocations) -> Option<(u32, u32)> {
+    //! Returns the location of the first instruction in the basic block which has one
+    let mut next_instruction = bb.get_first_instruction();
+    while let Some(current_instruction) = next_instruction {
+        if let Some(location) = get_instruction_debug_location(&current_instruction, debug_locations) {
+            return Some(location);
+        }
+        next_instruction = current_instruction.get_next_instruction();
+    }
+    return None;
+}
@@ -1,15 +1,16 @@
 use std::collections::{HashMap};
 
-use inkwell::types::BasicTypeEnum;
-// use tracing::{debug};
+use inkwell::types::{AnyType, BasicTypeEnum};
+use tracing::{error};
 
 use rustc_demangle::demangle;
 
 use inkwell::module::{Module as InkwellModule};
-use inkwell::values::{FunctionValue, InstructionOpcode, AnyValue, PointerValue};
+use inkwell::values::{FunctionValue, InstructionOpcode, InstructionValue, AnyValue, PointerValue};
 
 use z3::{Solver};
 
+use crate::analysis_context::AnalysisContext;
 use crate::utils::var_utils::get_var_name;
 
 
@@ -36,13 +37,13 @@ impl Named for inkwell::values::BasicValueEnum<'_> {
 }
 
 // Returns a map of source code function argument names to Z3 module variable names
-pub fn get_function_argument_names<'a>(function: FunctionValue<'a>, solver: &Solver, namespace: &str) -> Vec<(String, String, BasicTypeEnum<'a>)> {
+pub fn get_function_argument_names<'a>(function: FunctionValue<'a>, solver: &Solver, analysis: &AnalysisContext, namespace: &str) -> Vec<(String, String, BasicTypeEnum<'a>)> {
     let mut arg_names = Vec::<(String, String, BasicTypeEnum)>::new();
     for param in &function.get_params() {
         // debug!("Func param instr: {:?}", param);
         if param.get_name().len() == 0 {
             // Var name is empty, find in start basic block
-            let alias_name = &get_var_name(&param.as_any_value_enum(), solver, namespace);
+            let alias_name = &get_var_name(&param.as_any_value_enum(), solver, analysis, namespace);
             
             let start_block_option = function.get_first_basic_block();
             if start_block_option.is_none() {
@@ -51,15 +52,14 @@ pub fn get_function_argument_names<'a>(function: FunctionValue<'a>, solver: &Sol
             let start_block =start_block_option.unwrap();
             let mut instr = start_block.get_first_instruction();
             while instr.is_some() {
-                if instr.unwrap().get_opcode() == InstructionOpcode::Store && alias_name.to_string() == get_var_name(&instr.unwrap().as_any_value_enum(), solver, namespace) {
-                    let arg_name = get_var_name(&instr.unwrap().get_operand(1).unwrap().left().unwrap().as_any_value_enum(), solver, namespace);
+                if instr.unwrap().get_opcode() == InstructionOpcode::Store && alias_name.to_string() == get_var_name(&instr.unwrap().get_operand(0).unwrap().left().unwrap().as_any_value_enum(), solver, analysis, namespace) {
+                    let arg_name = get_var_name(&instr.unwrap().get_operand(1).unwrap().left().unwrap().as_any_value_enum(), solver, analysis, namespace);
                     arg_names.push((arg_name.to_string(), alias_name.to_string(), param.get_type().clone()));
                 }
                 instr = instr.unwrap().get_next_instruction();
             }
         } else {
-            let arg_name_string = format!("{}{}{}", namespace, "%", &param.get_name());
-            let arg_name = arg_name_string.to_string();
+            let arg_name = get_var_name(&param.as_any_value_enum(), solver, analysis, namespace);
             arg_names.push((arg_name.to_string(), arg_name.to_string(), param.get_type().clone()));
         }
     }
@@ -69,13 +69,13 @@ pub fn get_function_argument_names<'a>(function: FunctionValue<'a>, solver: &Sol
 }
 
 
-pub fn get_all_function_argument_names<'a>(module: &'a InkwellModule, solver: &'a Solver, namespace: &'a str) -> HashMap<String, Vec<(String, String, BasicTypeEnum<'a>)>> {
+pub fn get_all_function_argument_names<'a>(module: &'a InkwellModule, solver: &'a Solver, analysis: &AnalysisContext, namespace: &'a str) -> HashMap<String, Vec<(String, String, BasicTypeEnum<'a>)>> {
     let mut all_func_arg_names = HashMap::<String, Vec<(String, String, BasicTypeEnum)>>::new();
 
     let mut next_function = module.get_first_function();
     while let Some(current_function) = next_function {
         let current_full_function_name = get_function_name(&current_function.as_global_value().as_pointer_value());
-        let function_argument_names = get_function_argument_names(current_function, solver, namespace);
+        let function_argument_names = get_function_argument_names(current_function, solver, analysis, namespace);
         all_func_arg_names.insert(current_full_function_name, function_argument_names);
         next_function = current_function.get_next_function();
     }
@@ -88,14 +88,110 @@ pub fn get_function_name(function: &PointerValue) -> String {
 }
 
 
-pub fn get_function_by_name<'a>(module: &'a InkwellModule, target_function_name_prefix: &String) -> Option<FunctionValue<'a>> {
+pub fn get_function_path(function: &PointerValue) -> String {
+    //! Demangled name of the function without its hash (ex: `core::cmp::max`)
+    return format!("{:#}", demangle(&function.get_name().to_str().unwrap()));
+}
+
+
+pub fn get_called_function<'ctx>(module: &InkwellModule<'ctx>, instruction: &InstructionValue) -> Option<FunctionValue<'ctx>> {
+    //! Function of the module called directly by the call or invoke, None for calls through a pointer
+    let called_value = instruction.get_operand(instruction.get_num_operands() - 1)?.left()?;
+    if !called_value.is_pointer_value() {
+        return None;
+    }
+    return module.get_function(called_value.into_pointer_value().get_name().to_str().ok()?);
+}
+
+
+pub fn get_call_argument_count(instruction: &InstructionValue) -> u32 {
+    //! Number of arguments of a call, whose last operand is the called function (after the normal and unwind destinations of an invoke)
+    if instruction.get_opcode() == InstructionOpcode::Invoke {
+        return instruction.get_num_operands() - 3;
+    }
+    return instruction.get_num_operands() - 1;
+}
+
+
+pub fn strip_function_hash(function_name: &str) -> &str {
+    //! Function name without the hash selecting a monomorphised instance (ex: `foo::h0123456789abcdef` -> `foo`)
+    if let Some(hash_index) = function_name.rfind("::h") {
+        let hash = &function_name[hash_index + "::h".len()..];
+        if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return &function_name[..hash_index];
+        }
+    }
+    return function_name;
+}
+
+
+pub fn get_function_instances<'a>(module: &'a InkwellModule, target_function_path: &str) -> Vec<FunctionValue<'a>> {
+    //! Functions defined in the module whose path is exactly the target, or whose name with its hash is exactly the target
+    //! Generic functions have one instance per monomorphisation, told apart by their hash (ex: `test::foo::h0123456789abcdef`)
+    let mut instances = Vec::new();
     let mut next_function = module.get_first_function();
     while let Some(current_function) = next_function {
-        let current_full_function_name = get_function_name(&current_function.as_global_value().as_pointer_value());
-        if current_full_function_name.find(target_function_name_prefix).is_some() {
-            return Some(current_function);
+        let function_pointer = current_function.as_global_value().as_pointer_value();
+        let is_match = get_function_path(&function_pointer) == target_function_path || get_function_name(&function_pointer) == target_function_path;
+        if is_match && current_function.count_basic_blocks() > 0 {
+            instances.push(current_function);
         }
         next_function = current_function.get_next_function();
     }
+    return instances;
+}
+
+
+pub fn get_function_by_name<'a>(module: &'a InkwellModule, target_function_path: &String) -> Option<FunctionValue<'a>> {
+    //! Function with exactly the target path, None if it is not defined or has several instances to choose from
+    let instances = get_function_instances(module, target_function_path);
+    if instances.is_empty() {
+        error!("Function {:?} is not defined, use --list-functions to see the defined functions", target_function_path);
+        return None;
+    }
+    if instances.len() > 1 {
+        error!("Function {:?} has {} instances, select one by its hash or use --all-instances:", target_function_path, instances.len());
+        for instance in instances {
+            error!("\t{}: {}", get_function_name(&instance.as_global_value().as_pointer_value()), get_function_signature(&instance));
+        }
+        return None;
+    }
+    return instances.first().copied();
+}
+
+
+pub fn get_function_signature(function: &FunctionValue) -> String {
+    //! LLVM signature of the function (ex: `i32 (i32, i1)`)
+    return function.get_type().print_to_string().to_string();
+}
+
+
+pub fn get_defined_function_name(line: &str) -> Option<String> {
+    //! Name of the function defined on the given source line, if any
+    let fn_index = line.find("fn ")?;
+    if fn_index > 0 && !line[..fn_index].ends_with(' ') {
+        return None;
+    }
+    let name_start_index = fn_index + "fn ".len();
+    let name_end_index = line[name_start_index..].find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(line[name_start_index..].len()) + name_start_index;
+    return Some(String::from(&line[name_start_index..name_end_index]));
+}
+
+pub fn get_source_signature(source: &str, function_name: &str) -> Option<String> {
+    //! Rust signature of the function as written in the source (ex: `fn scale<const N: i32>(x: i8) -> i32`), None if it is not found
+    //! Functions are found by the last segment of their path, so the first method with that name is used
+    let name = strip_function_hash(function_name).rsplit("::").next()?;
+    let lines: Vec<&str> = source.lines().map(|line| line.trim()).collect();
+    let start_index = lines.iter().position(|line| get_defined_function_name(line).as_deref() == Some(name))?;
+    let mut signature_parts = Vec::<&str>::new();
+    for (index, line) in lines[start_index..].iter().enumerate() {
+        // The signature may span several lines (ex: one parameter per line), up to the body or the end of the declaration
+        let part = if index == 0 { &line[line.find("fn ")?..] } else { *line };
+        if let Some(end_index) = part.find(|c: char| c == '{' || c == ';') {
+            signature_parts.push(part[..end_index].trim());
+            return Some(signature_parts.join(" ").replace("( ", "(").replace(", )", ")").replace(" )", ")"));
+        }
+        signature_parts.push(part);
+    }
     return None;
 }
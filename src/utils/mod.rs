@@ -1,3 +1,4 @@
 pub mod function_utils;
 pub mod var_utils;
 pub mod pretty_print;
+pub mod debug_info;
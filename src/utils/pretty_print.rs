@@ -54,7 +54,7 @@ pub fn pretty_print_function(function: &FunctionValue, namespace: &str) -> () {
     let first_basic_block = function.get_first_basic_block().unwrap();
     let first_basic_block_name = format!("{}{}", namespace, first_basic_block.get_name().to_str().unwrap());
     debug!("Start node: {:?}", first_basic_block.get_name().to_str().unwrap());
-    let forward_edges = get_forward_edges(function, namespace, COMMON_END_NODE);
+    let forward_edges = get_forward_edges(function, namespace, COMMON_END_NODE, COMMON_END_NODE);
     let successors = forward_edges.get(&first_basic_block_name).unwrap();
     for successor in successors {
         debug!("\tSuccessor to start node: {:?}", successor);
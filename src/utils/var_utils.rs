@@ -1,49 +1,77 @@
+use inkwell::types::AnyTypeEnum;
 use inkwell::values::{AnyValue};
 
 use z3::ast::{Ast, Bool, Int};
-use z3::Solver;
-
-
-pub const CONST_NAMESPACE: &str = "const_";
-
-
-pub fn get_var_name<'a>(value: &dyn AnyValue, solver: &'a Solver<'_>, namespace: &str) -> String {
-    let value_llvm_str = value.print_to_string();
-    let value_str = value_llvm_str.to_str().unwrap();
-    let name = if !value_str.contains("%") {
-        // handle const literals
-        let const_value_str = value_str.split_whitespace().nth(1).unwrap();
-        let var_name_string = format!("{}{}", CONST_NAMESPACE, const_value_str);
-        let var_name = var_name_string.as_str();
-        if const_value_str.eq("true") {
-            let true_const = Bool::new_const(solver.get_context(), var_name);
-            solver.assert(&true_const._eq(&Bool::from_bool(solver.get_context(), true)));
-        } else if const_value_str.eq("false") {
-            let false_const = Bool::new_const(solver.get_context(), var_name);
-            solver.assert(&false_const._eq(&Bool::from_bool(solver.get_context(), false)));
-        } else {
-            let parsed_num = const_value_str.parse::<i64>().unwrap();
-            let num_const = Int::new_const(solver.get_context(), var_name);
-            solver.assert(&num_const._eq(&Int::from_i64(solver.get_context(), parsed_num.into())));
-        }
-        String::from(var_name)
-    } else {
-        let start_index = value_str.find("%").unwrap();
-        let end_index = value_str[start_index..].find(|c: char| c == '"' || c == ' ' || c == ',').unwrap_or(value_str[start_index..].len()) + start_index;
-        let var_name = String::from(&value_str[start_index..end_index]);
-        String::from(format!("{}{}", namespace, var_name))
-    };
-    return name;
+use z3::{Context as Z3Context, Solver};
+
+use crate::analysis_context::AnalysisContext;
+
+
+pub fn get_lane_name(var_name: &str, lane: u32) -> String {
+    //! Lanes of vectors are encoded as separate variables (ex: `%v.0` and `%v.1` for `<2 x i32> %v`)
+    return format!("{}.{}", var_name, lane);
 }
 
-pub fn get_min_max_signed_int(size: &str) -> (i64, i64) {
-    match size {
-        "8" => (i8::MIN as i64, i8::MAX as i64),
-        "16" => (i8::MIN as i64, i8::MAX as i64),
-        "32" => (i32::MIN as i64, i32::MAX as i64),
-        "64" => (i64::MIN, i64::MAX),
-        "size" => (isize::MIN as i64, isize::MAX as i64), // isize depends on devices architecture (32 bits or 64 bits)
-        "128" => panic!("i128 are unsupported due to Z3 allowing up to 64 bit integers"),
+
+pub fn get_var_name<'a>(value: &dyn AnyValue, solver: &'a Solver<'_>, analysis: &AnalysisContext, namespace: &str) -> String {
+    //! Z3 name of the value, see `SymbolTable::get_name`
+    return analysis.symbols.get_name(value, solver, analysis, namespace);
+}
+
+
+pub fn get_min_max_signed_int_of_width(bit_width: u32) -> (i64, i64) {
+    //! Minimum and maximum values of a signed integer of the given bit width (ex: the pointer width for `isize`), for widths up to 64 bits
+    match bit_width {
+        8 => (i8::MIN as i64, i8::MAX as i64),
+        16 => (i16::MIN as i64, i16::MAX as i64),
+        32 => (i32::MIN as i64, i32::MAX as i64),
+        64 => (i64::MIN, i64::MAX),
+        128 => panic!("i128 are unsupported due to Z3 allowing up to 64 bit integers"),
         _ => panic!("Unsupported signed integer type")
     }
 }
+
+
+pub fn get_unsigned_modulus<'a>(ctx: &'a Z3Context, bit_width: u32) -> Int<'a> {
+    //! `2^bit_width`, which is added to negative values to read them as unsigned (built from factors that fit in a `u64` for `i128`)
+    let mut modulus = Int::from_u64(ctx, 1);
+    let mut remaining_bit_width = bit_width;
+    while remaining_bit_width > 0 {
+        let factor_bit_width = remaining_bit_width.min(63);
+        modulus = &modulus * &Int::from_u64(ctx, 1 << factor_bit_width);
+        remaining_bit_width -= factor_bit_width;
+    }
+    return modulus;
+}
+
+
+pub fn get_unsigned_value<'a>(ctx: &'a Z3Context, value: &Int<'a>, bit_width: u32) -> Int<'a> {
+    //! Unsigned value of an integer of the given bit width, which is encoded by the signed value of its two's complement bits
+    return value.lt(&Int::from_i64(ctx, 0)).ite(&(value + &get_unsigned_modulus(ctx, bit_width)), value);
+}
+
+
+pub fn get_domain_constraint<'a>(solver: &'a Solver<'_>, var_name: &String, var_type: &AnyTypeEnum) -> Option<Bool<'a>> {
+    //! Constrains a variable to the values representable by its (signed) LLVM type, None if the type is unsupported
+    if !var_type.is_int_type() {
+        return None;
+    }
+    let bit_width = var_type.into_int_type().get_bit_width();
+    return match bit_width {
+        1 => Some(Bool::from_bool(solver.get_context(), true)),
+        8 | 16 | 32 | 64 => {
+            let (min_int_val, max_int_val) = get_min_max_signed_int_of_width(bit_width);
+            let var = Int::new_const(solver.get_context(), var_name.as_str());
+            let min_int = Int::from_i64(solver.get_context(), min_int_val);
+            let max_int = Int::from_i64(solver.get_context(), max_int_val);
+            Some(Bool::and(solver.get_context(), &[&var.ge(&min_int), &var.le(&max_int)]))
+        }
+        _ => None
+    };
+}
+
+
+pub fn is_function_pointer_type(var_type: &AnyTypeEnum) -> bool {
+    //! Function pointers are encoded as integers, the addresses given to the candidate targets of indirect calls
+    return var_type.is_pointer_type() && var_type.into_pointer_type().get_element_type().is_function_type();
+}
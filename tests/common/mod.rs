@@ -1,9 +1,29 @@
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 
+use inkwell::context::Context as InkwellContext;
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::module::Module as InkwellModule;
 use tracing_core::Level;
 use tracing_subscriber::FmtSubscriber;
 
+use z3::ast::{Ast, Bool};
+use z3::Context as Z3Context;
+
+use wombat_symx::analysis_context::AnalysisContext;
+use wombat_symx::codegen::codegen_basic_block::{get_entry_probe_name, get_exit_probe_name};
+use wombat_symx::codegen::codegen_function::codegen_function;
+use wombat_symx::contracts::{encode_contract_conditions, get_expression_variables};
+use wombat_symx::equivalence::{differential_checking, equivalence_checking};
+use wombat_symx::instances::{all_instances_analysis, get_function_instance_names};
+use wombat_symx::reachability::reachability_analysis;
+use wombat_symx::symbolic_execution::{
+    constrain_function_parameters, get_solver, get_solver_config, get_verdict, symbolic_execution, AnalysisOptions, Verdict,
+    COMMON_END_NODE, MAIN_FUNCTION_NAMESPACE, MAIN_FUNCTION_RETURN_REGISTER, PANIC_VAR_NAME
+};
+use wombat_symx::utils::function_utils::get_all_function_argument_names;
+
 struct FileDropper<'a> {
     file_name: &'a String,
 }
@@ -14,7 +34,7 @@ impl Drop for FileDropper<'_> {
     }
 }
 
-pub fn test(test_name: &str, function_name: &str, source_code: &str, expected_safe: bool) -> () {
+fn setup_tracing() -> tracing::subscriber::DefaultGuard {
     // Needs to be manually changed due to the test harness not handling cmd args
     let debug = false;
 
@@ -25,25 +45,185 @@ pub fn test(test_name: &str, function_name: &str, source_code: &str, expected_sa
         FmtSubscriber::builder().with_max_level(Level::WARN).finish()
     };
 
-    // _guard resets the current default dispatcher to the prior default when dropped
-    let _guard = tracing::subscriber::set_default(subscriber);
+    // The guard resets the current default dispatcher to the prior default when dropped
+    return tracing::subscriber::set_default(subscriber);
+}
+
+/// Analysis run by a test
+#[derive(Clone, Copy)]
+pub enum Analysis<'a> {
+    /// Checks the function for panics (and undefined behaviour if enabled in the options)
+    Panics,
+    /// Checks the function for panics, with the functions of the stub source replacing the ones they stub
+    PanicsWithStubs(&'a str),
+    /// Checks that the function is equivalent to the other function of the source
+    EquivalentTo(&'a str),
+    /// Looks for inputs on which the debug build panics while the release build returns
+    Differential,
+    /// Checks every instance of the generic function, which has the given number of instances
+    AllInstances(usize),
+}
+
+/// Verdict expected from the analysis
+pub enum Expected {
+    /// Whether the verdict is safe, whatever the number of unsound constructs
+    Safe(bool),
+    /// Exact verdict, None if the analysis fails
+    Verdict(Option<Verdict>),
+}
 
+fn write_test_source(test_name: &str, function_name: &str, source_code: &str) -> String {
     let source_file_name = format!("tests_temp/zzz_temp_test_{}.rs", test_name);
     if !Path::exists(Path::new("tests_temp")) {
         // Ensure temp test directory exists, otherwise src file fails to be created
         fs::create_dir("tests_temp").expect("Unable to create test directory: \"tests_temp\"");
     }
 
-    let _file_dropper = FileDropper {
-        file_name: &source_file_name,
-    };
+    // Programs analysed from their own main are written as is
+    if function_name == "main" {
+        fs::write(&source_file_name, source_code.replace("            ", "")).expect("Failed to write temp test file!");
+        return source_file_name;
+    }
 
     // Prevent compiler from optimizing away unused function
     let main = format!("fn main() {{println!(\"{{:p}}\", {} as *const ())}}", function_name);
 
     fs::write(&source_file_name, format!("{}\n{}", source_code.replace("            ", ""), main)).expect("Failed to write temp test file!");
+    return source_file_name;
+}
+
+pub fn test(test_name: &str, function_name: &str, source_code: &str, expected_safe: bool) -> () {
+    test_analysis(test_name, function_name, source_code, Analysis::Panics, AnalysisOptions::default(), Expected::Safe(expected_safe));
+}
+
+pub fn test_analysis(test_name: &str, function_name: &str, source_code: &str, analysis: Analysis, options: AnalysisOptions, expected: Expected) -> () {
+    let _guard = setup_tracing();
+
+    // Both functions are referenced from main so that neither is optimized away
+    let referenced_functions = match analysis {
+        Analysis::EquivalentTo(other_function_name) => format!("{} as *const (), {}", other_function_name, function_name),
+        // Generic functions cannot be referenced by main without their arguments, the test function instantiates them
+        Analysis::AllInstances(..) => String::from(test_name),
+        _ => String::from(function_name),
+    };
+    let source_file_name = write_test_source(test_name, &referenced_functions, source_code);
+    let _file_dropper = FileDropper {
+        file_name: &source_file_name,
+    };
+
+    let stub_file_name = format!("tests_temp/zzz_temp_stubs_{}.rs", test_name);
+    let mut options = options;
+    let _stub_file_dropper = if let Analysis::PanicsWithStubs(stub_source_code) = analysis {
+        fs::write(&stub_file_name, stub_source_code.replace("            ", "")).expect("Failed to write temp stub file!");
+        options.stub_file = Some(stub_file_name.clone());
+        Some(FileDropper {
+            file_name: &stub_file_name,
+        })
+    } else {
+        None
+    };
+
+    let function_name = String::from(function_name);
+    let actual_verdict = match analysis {
+        Analysis::Panics | Analysis::PanicsWithStubs(..) => symbolic_execution(&source_file_name, &function_name, &options),
+        Analysis::EquivalentTo(other_function_name) => {
+            equivalence_checking(&source_file_name, &function_name, &source_file_name, &String::from(other_function_name), &options)
+        }
+        Analysis::Differential => differential_checking(&source_file_name, &function_name, &options),
+        Analysis::AllInstances(expected_instance_count) => {
+            let instance_names = get_function_instance_names(&source_file_name, &function_name, &options).unwrap();
+            assert!(instance_names.len() == expected_instance_count);
+            all_instances_analysis(&source_file_name, &function_name, &options)
+        }
+    };
 
-    let actual_safe = wombat_symx::symbolic_execution::symbolic_execution(&source_file_name, &String::from(function_name));
+    assert_verdict(expected, actual_verdict);
+}
+
+fn assert_verdict(expected: Expected, actual_verdict: Option<Verdict>) -> () {
+    match expected {
+        Expected::Safe(expected_safe) => assert!(expected_safe == actual_verdict.unwrap().is_safe()),
+        Expected::Verdict(expected_verdict) => assert!(expected_verdict == actual_verdict),
+    }
+}
+
+pub fn test_ir(ir: &str, function_name: &str, options: AnalysisOptions, expected: Expected) -> () {
+    // Shapes of optimised IR (ex: vectors, `freeze`, `poison`) are written by hand, as rustc only emits them for some sources
+    // The function is checked for panics and for the postconditions of the options, whose variables are signed
+    let _guard = setup_tracing();
+    let context = InkwellContext::create();
+    let module = load_ir_module(&context, ir);
+    let function = module.get_function(function_name).expect("Missing test IR function!");
+    let z3_context = Z3Context::new(&get_solver_config(&options));
+    let solver = get_solver(&z3_context, &options);
+    let analysis = AnalysisContext::new(&options);
+
+    let all_func_arg_names = get_all_function_argument_names(&module, &solver, &analysis, MAIN_FUNCTION_NAMESPACE);
+    codegen_function(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, function_name, COMMON_END_NODE, COMMON_END_NODE, MAIN_FUNCTION_RETURN_REGISTER);
+    constrain_function_parameters(&module, &function, &solver, &analysis, MAIN_FUNCTION_NAMESPACE, &String::from(function_name));
+    let variables = get_expression_variables(&function, all_func_arg_names.get(function_name).unwrap(), "", function_name);
+    let postcondition = encode_contract_conditions(solver.get_context(), &options.ensures, &variables).unwrap();
+
+    for bb in function.get_basic_blocks() {
+        let node = format!("{}{}", MAIN_FUNCTION_NAMESPACE, bb.get_name().to_str().unwrap());
+        solver.assert(&Bool::new_const(solver.get_context(), get_entry_probe_name(&node)));
+        solver.assert(&Bool::new_const(solver.get_context(), get_exit_probe_name(&node)));
+    }
+    let common_end_node_var = Bool::new_const(solver.get_context(), COMMON_END_NODE);
+    let panic_var = Bool::new_const(solver.get_context(), PANIC_VAR_NAME);
+    solver.assert(&common_end_node_var._eq(&Bool::and(solver.get_context(), &[&panic_var.not(), &postcondition])));
+    let start_node_name = format!("{}{}", MAIN_FUNCTION_NAMESPACE, function.get_first_basic_block().unwrap().get_name().to_str().unwrap());
+    solver.assert(&Bool::new_const(solver.get_context(), start_node_name).not());
+
+    assert_verdict(expected, Some(get_verdict(&solver, &analysis, solver.check())));
+}
+
+pub fn test_unreachable_code(test_name: &str, function_name: &str, source_code: &str, options: AnalysisOptions, expected_unreachable_count: usize) -> () {
+    // The dead code analysis reports the unreachable blocks and branches rather than a verdict
+    let _guard = setup_tracing();
+    let source_file_name = write_test_source(test_name, function_name, source_code);
+    let _file_dropper = FileDropper {
+        file_name: &source_file_name,
+    };
 
-    assert!(expected_safe == actual_safe.unwrap());
-}
\ No newline at end of file
+    // Counts are only exact when the solver decided every block and branch
+    let report = reachability_analysis(&source_file_name, &String::from(function_name), &options).unwrap();
+    assert!(report.undecided_code.len() == 0);
+    assert!(expected_unreachable_count == report.unreachable_code.len());
+}
+
+pub fn test_output(test_name: &str, function_name: &str, source_code: &str, args: &[&str], expected_lines: &[&str]) -> () {
+    // The counterexample is printed by the command line tool, so it is run on the test source
+    let source_file_name = write_test_source(test_name, function_name, source_code);
+    let _file_dropper = FileDropper {
+        file_name: &source_file_name,
+    };
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wombat_symx"))
+        .args(args)
+        .arg(&source_file_name)
+        .arg(function_name)
+        .output()
+        .expect("Failed to run wombat_symx!");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    for expected_line in expected_lines {
+        assert!(stdout.lines().any(|line| line.contains(expected_line)), "Missing {:?} in the output:\n{}", expected_line, stdout);
+    }
+}
+
+pub fn is_target_installed(target: &str) -> bool {
+    // Tests for other targets are skipped when the standard library of the target is not installed (ex: `rustup target add i686-unknown-linux-gnu`)
+    let output = Command::new("rustc")
+        .args(["--print", "target-libdir", "--target", target])
+        .output()
+        .expect("Failed to run rustc!");
+    let target_libdir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    return output.status.success() && Path::new(&target_libdir).is_dir();
+}
+
+pub fn load_ir_module<'a>(context: &'a InkwellContext, ir: &str) -> InkwellModule<'a> {
+    // Textual IR allows values whose names cannot be written in Rust (ex: quoted or unnamed)
+    let buffer = MemoryBuffer::create_from_memory_range_copy(ir.replace("            ", "").as_bytes(), "test_ir");
+    return context.create_module_from_ir(buffer).expect("Failed to parse test IR!");
+}
@@ -1,5 +1,16 @@
+use inkwell::context::Context as InkwellContext;
+use inkwell::values::AnyValue;
+use z3::{Config, Context as Z3Context, SatResult, Solver};
+
+use wombat_symx::analysis_context::AnalysisContext;
+use wombat_symx::symbol_table::SymbolKind;
+use wombat_symx::symbolic_execution::{get_verdict, AnalysisOptions, ExternalCallPolicy, UnknownReason, Verdict};
+use wombat_symx::utils::var_utils::get_var_name;
+
 mod common;
 
+use common::{Analysis, Expected};
+
 #[test]
 fn test_unsafe_abs() {
     common::test(
@@ -133,6 +144,80 @@ fn test_safe_neg_abs_i16() {
     );
 }
 
+#[test]
+fn test_unsafe_i16_parameter_range() {
+    common::test(
+        "test_unsafe_i16_parameter_range",
+        "test_unsafe_i16_parameter_range",
+        "
+            fn test_unsafe_i16_parameter_range(x: i16) -> i16 {
+                assert!(x < 1000);
+                x
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_u32_widening_add() {
+    common::test(
+        "test_safe_u32_widening_add",
+        "test_safe_u32_widening_add",
+        "
+            fn test_safe_u32_widening_add(x: u32) -> u64 {
+                x as u64 + 1
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_u32_widening_max() {
+    common::test(
+        "test_unsafe_u32_widening_max",
+        "test_unsafe_u32_widening_max",
+        "
+            fn test_unsafe_u32_widening_max(x: u32) -> u64 {
+                assert!((x as u64) < 4294967295);
+                x as u64
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_u32_compare_above_i32_max() {
+    common::test(
+        "test_unsafe_u32_compare_above_i32_max",
+        "test_unsafe_u32_compare_above_i32_max",
+        "
+            fn test_unsafe_u32_compare_above_i32_max(x: u32) -> u32 {
+                assert!(x <= 2147483647);
+                x
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_u32_compare_max() {
+    common::test(
+        "test_safe_u32_compare_max",
+        "test_safe_u32_compare_max",
+        "
+            fn test_safe_u32_compare_max(x: u32) -> u32 {
+                assert!(x >= 0 && x <= 4294967295);
+                x
+            }
+        ",
+        true,
+    );
+}
+
 #[test]
 fn test_safe_neg_abs_i64() {
     common::test(
@@ -566,3 +651,1911 @@ fn test_unsafe_float_params() {
         false,
     );
 }
+
+#[test]
+fn test_dead_code_none() {
+    common::test_unreachable_code(
+        "test_dead_code_none",
+        "test_dead_code_none",
+        "
+            fn test_dead_code_none(c1: bool) -> i32 {
+                if c1 {
+                    1
+                } else {
+                    0
+                }
+            }
+        ",
+        AnalysisOptions::default(),
+        0,
+    );
+}
+
+#[test]
+fn test_dead_code_redundant_check() {
+    common::test_unreachable_code(
+        "test_dead_code_redundant_check",
+        "test_dead_code_redundant_check",
+        "
+            fn test_dead_code_redundant_check(x: i32) -> i32 {
+                if x > 10 {
+                    if x > 5 {
+                        return 1;
+                    }
+                    return 2;
+                }
+                0
+            }
+        ",
+        AnalysisOptions::default(),
+        2,
+    );
+}
+
+#[test]
+fn test_dead_code_requires() {
+    common::test_unreachable_code(
+        "test_dead_code_requires",
+        "test_dead_code_requires",
+        "
+            #[wombat::requires(x > 10)]
+            fn test_dead_code_requires(x: i32) -> i32 {
+                if x > 5 {
+                    return 1;
+                }
+                0
+            }
+        ",
+        AnalysisOptions::default(),
+        2,
+    );
+}
+
+#[test]
+fn test_unknown_resource_limit() {
+    common::test_analysis(
+        "test_unknown_resource_limit",
+        "test_unknown_resource_limit",
+        "
+            fn test_unknown_resource_limit(x: i32, y: i32) -> i32 {
+                let r = x * y;
+                assert!(r != 7);
+                r
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            rlimit: Some(1),
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unknown(UnknownReason::ResourceLimit))),
+    );
+}
+
+#[test]
+fn test_unknown_dead_code_resource_limit() {
+    common::test_output(
+        "test_unknown_dead_code_resource_limit",
+        "test_unknown_dead_code_resource_limit",
+        "
+            fn test_unknown_dead_code_resource_limit(x: i32, y: i32) -> i32 {
+                if x * y == 7 {
+                    return 1;
+                }
+                0
+            }
+        ",
+        &["--dead-code", "--rlimit", "1"],
+        &["Undecided code in test_unknown_dead_code_resource_limit", "resource limit exceeded"],
+    );
+}
+
+#[test]
+fn test_safe_memory_limit() {
+    common::test_analysis(
+        "test_safe_memory_limit",
+        "test_safe_memory_limit",
+        "
+            fn test_safe_memory_limit(x: i32) -> i32 {
+                x / 2
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            memory: Some(1024),
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unknown_unsupported_instruction() {
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+    assert!(get_verdict(&solver, &analysis, SatResult::Unsat) == Verdict::Safe { unsound_constructs: 0 });
+
+    // A proof does not cover the successors of an unsupported terminator
+    analysis.unsupported_instructions.borrow_mut().push(String::from("catchswitch within none [label %handler] unwind to caller"));
+    let verdict = get_verdict(&solver, &analysis, SatResult::Unsat);
+    assert!(matches!(verdict, Verdict::Unknown(UnknownReason::UnsupportedInstruction(..))));
+}
+
+#[test]
+fn test_unsound_safe_float_params() {
+    common::test_analysis(
+        "test_unsound_safe_float_params",
+        "test_unsound_safe_float_params",
+        "
+            fn test_unsound_safe_float_params(y: f32) -> f32 {
+                y * 2.0
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_strict_float_params() {
+    common::test_analysis(
+        "test_strict_float_params",
+        "test_strict_float_params",
+        "
+            fn test_strict_float_params(y: f32) -> f32 {
+                y * 2.0
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            strict: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(None),
+    );
+}
+
+#[test]
+fn test_safe_external_call_no_panic() {
+    common::test_analysis(
+        "test_safe_external_call_no_panic",
+        "test_safe_external_call_no_panic",
+        "
+            fn test_safe_external_call_no_panic(x: u32) -> u32 {
+                let id = std::process::id();
+                if id > x {
+                    return id;
+                }
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            external_calls: ExternalCallPolicy::AssumeNoPanic,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_external_call_may_panic() {
+    common::test_analysis(
+        "test_unsafe_external_call_may_panic",
+        "test_unsafe_external_call_may_panic",
+        "
+            fn test_unsafe_external_call_may_panic(x: u32) -> u32 {
+                let id = std::process::id();
+                if id > x {
+                    return id;
+                }
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            external_calls: ExternalCallPolicy::AssumeMayPanic,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_safe_stubbed_call() {
+    common::test_analysis(
+        "test_safe_stubbed_call",
+        "test_safe_stubbed_call",
+        "
+            fn test_safe_stubbed_call(x: u32) -> u32 {
+                let id = std::process::id();
+                assert!(id == 5);
+                x
+            }
+        ",
+        Analysis::PanicsWithStubs(
+            "
+                #[wombat::stub(\"std::process::id\")]
+                pub fn process_id_stub() -> u32 {
+                    5
+                }
+            "
+        ),
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_stubbed_call() {
+    common::test_analysis(
+        "test_unsafe_stubbed_call",
+        "test_unsafe_stubbed_call",
+        "
+            fn test_unsafe_stubbed_call(x: u32) -> u32 {
+                let id = std::process::id();
+                if id > x {
+                    panic!();
+                }
+                x
+            }
+        ",
+        Analysis::PanicsWithStubs(
+            "
+                #[wombat::stub(\"std::process::id\")]
+                pub fn process_id_stub() -> u32 {
+                    5
+                }
+            "
+        ),
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_safe_unsigned_add() {
+    common::test(
+        "test_safe_unsigned_add",
+        "test_safe_unsigned_add",
+        "
+            fn test_safe_unsigned_add(x: u8) -> u8 {
+                if x < 255 {
+                    return x + 1;
+                }
+                x
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_unsigned_add() {
+    common::test(
+        "test_unsafe_unsigned_add",
+        "test_unsafe_unsigned_add",
+        "
+            fn test_unsafe_unsigned_add(x: u8) -> u8 {
+                x + 1
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_count_ones() {
+    common::test(
+        "test_safe_count_ones",
+        "test_safe_count_ones",
+        "
+            fn test_safe_count_ones(x: u32) -> u32 {
+                let ones = x.count_ones();
+                assert!(ones <= 32);
+                ones
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_safe_requires() {
+    common::test_analysis(
+        "test_safe_requires",
+        "test_safe_requires",
+        "
+            #[wombat::requires(x > 0)]
+            fn test_safe_requires(x: i32) -> i32 {
+                assert!(x != 0);
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_safe_ensures() {
+    common::test_analysis(
+        "test_safe_ensures",
+        "test_safe_ensures",
+        "
+            #[wombat::requires(x > -1000 && x < 1000)]
+            #[wombat::ensures(ret >= 0 && (x >= 0 ==> ret == x))]
+            fn test_safe_ensures(x: i32) -> i32 {
+                if x < 0 {
+                    return 0 - x;
+                }
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_ensures() {
+    common::test_analysis(
+        "test_unsafe_ensures",
+        "test_unsafe_ensures",
+        "
+            #[wombat::requires(x > -1000 && x < 1000)]
+            #[wombat::ensures(ret > 0)]
+            fn test_unsafe_ensures(x: i32) -> i32 {
+                if x < 0 {
+                    return 0 - x;
+                }
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_unsafe_unsigned_requires() {
+    common::test(
+        "test_unsafe_unsigned_requires",
+        "test_unsafe_unsigned_requires",
+        "
+            #[wombat::requires(x > 0)]
+            fn test_unsafe_unsigned_requires(x: u32) -> u32 {
+                assert!(x != 3000000000);
+                x
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_unsigned_ensures() {
+    common::test_analysis(
+        "test_safe_unsigned_ensures",
+        "test_safe_unsigned_ensures",
+        "
+            #[wombat::ensures(ret >= 1 && ret >= x)]
+            fn test_safe_unsigned_ensures(x: u32) -> u32 {
+                if x == 0 {
+                    return 1;
+                }
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_safe_companion_harness() {
+    common::test(
+        "test_safe_companion_harness",
+        "test_safe_companion_harness",
+        "
+            fn test_safe_companion_harness() -> () {
+                let x: i32 = wombat::any();
+                wombat::assume(x > 0 && x < 100);
+                wombat::assert(x * 2 > x);
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_companion_harness() {
+    common::test(
+        "test_unsafe_companion_harness",
+        "test_unsafe_companion_harness",
+        "
+            fn test_unsafe_companion_harness() -> () {
+                let x: i32 = wombat::any();
+                wombat::assume(x > 0);
+                wombat::assert(x != 7);
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_cli_ensures() {
+    common::test_analysis(
+        "test_safe_cli_ensures",
+        "test_safe_cli_ensures",
+        "
+            fn test_safe_cli_ensures(x: bool) -> i32 {
+                if x {
+                    return 1;
+                }
+                2
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            ensures: vec![String::from("ret > 0"), String::from("x ==> ret == 1")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_cli_ensures() {
+    common::test_analysis(
+        "test_unsafe_cli_ensures",
+        "test_unsafe_cli_ensures",
+        "
+            fn test_unsafe_cli_ensures(x: bool) -> i32 {
+                if x {
+                    return 1;
+                }
+                2
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            ensures: vec![String::from("ret == 1")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_unsafe_cli_ensures_unsigned_ret() {
+    common::test_analysis(
+        "test_unsafe_cli_ensures_unsigned_ret",
+        "test_unsafe_cli_ensures_unsigned_ret",
+        "
+            fn test_unsafe_cli_ensures_unsigned_ret(x: u32) -> u32 {
+                x
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            ensures: vec![String::from("ret < 4000000000")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_equivalent_functions() {
+    common::test_analysis(
+        "test_equivalent_functions",
+        "test_equivalent_functions_lhs",
+        "
+            fn test_equivalent_functions_lhs(x: i32) -> i32 {
+                if x > 100 {
+                    panic!();
+                }
+                x * 2
+            }
+
+            fn test_equivalent_functions_rhs(x: i32) -> i32 {
+                if x >= 101 {
+                    panic!();
+                }
+                x + x
+            }
+        ",
+        Analysis::EquivalentTo("test_equivalent_functions_rhs"),
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_non_equivalent_functions() {
+    common::test_analysis(
+        "test_non_equivalent_functions",
+        "test_non_equivalent_functions_lhs",
+        "
+            fn test_non_equivalent_functions_lhs(x: i32) -> i32 {
+                if x > 100 {
+                    return 100;
+                }
+                x
+            }
+
+            fn test_non_equivalent_functions_rhs(x: i32) -> i32 {
+                if x > 100 {
+                    return 100;
+                }
+                if x == 42 {
+                    return 0;
+                }
+                x
+            }
+        ",
+        Analysis::EquivalentTo("test_non_equivalent_functions_rhs"),
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_equivalent_external_calls() {
+    common::test_analysis(
+        "test_equivalent_external_calls",
+        "test_equivalent_external_calls_lhs",
+        "
+            fn test_equivalent_external_calls_lhs(x: u32) -> u32 {
+                let id = std::process::id();
+                if id > 10 {
+                    return 0;
+                }
+                x
+            }
+
+            fn test_equivalent_external_calls_rhs(x: u32) -> u32 {
+                let id = std::process::id();
+                if id >= 11 {
+                    return 0;
+                }
+                x
+            }
+        ",
+        Analysis::EquivalentTo("test_equivalent_external_calls_rhs"),
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 2 })),
+    );
+}
+
+#[test]
+fn test_safe_differential() {
+    common::test_analysis(
+        "test_safe_differential",
+        "test_safe_differential",
+        "
+            fn test_safe_differential(x: i32) -> i32 {
+                if x > 1000 || x < -1000 {
+                    return 0;
+                }
+                x * 2
+            }
+        ",
+        Analysis::Differential,
+        AnalysisOptions::default(),
+        Expected::Safe(true),
+    );
+}
+
+#[test]
+fn test_unsafe_differential() {
+    common::test_analysis(
+        "test_unsafe_differential",
+        "test_unsafe_differential",
+        "
+            fn test_unsafe_differential(x: i32) -> i32 {
+                x * 2 + 1
+            }
+        ",
+        Analysis::Differential,
+        AnalysisOptions::default(),
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_safe_differential_clamp() {
+    common::test_analysis(
+        "test_safe_differential_clamp",
+        "test_safe_differential_clamp",
+        "
+            fn test_safe_differential_clamp(x: i32, negate: bool) -> i32 {
+                let clamped = if x > 100 { 100 } else if x < -100 { -100 } else { x };
+                if negate {
+                    return -clamped * 3;
+                }
+                clamped * 3 + 1
+            }
+        ",
+        Analysis::Differential,
+        AnalysisOptions::default(),
+        Expected::Safe(true),
+    );
+}
+
+#[test]
+fn test_safe_repeated_void_calls() {
+    common::test(
+        "test_safe_repeated_void_calls",
+        "test_safe_repeated_void_calls",
+        "
+            fn check_not_five(x: i32) -> () {
+                if x == 5 {
+                    panic!();
+                }
+            }
+
+            fn test_safe_repeated_void_calls(x: i32) -> () {
+                if x != 5 {
+                    check_not_five(x);
+                    check_not_five(x);
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_repeated_void_calls() {
+    common::test(
+        "test_unsafe_repeated_void_calls",
+        "test_unsafe_repeated_void_calls",
+        "
+            fn check_not_five(x: i32) -> () {
+                if x == 5 {
+                    panic!();
+                }
+            }
+
+            fn test_unsafe_repeated_void_calls(x: i32) -> () {
+                check_not_five(x + 1);
+                check_not_five(x);
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_static_table() {
+    common::test(
+        "test_safe_static_table",
+        "test_safe_static_table",
+        "
+            static TABLE: [i32; 4] = [1, 2, 3, 4];
+
+            fn test_safe_static_table(x: bool) -> i32 {
+                let index = if x { 1 } else { 3 };
+                if TABLE[index] == 3 {
+                    panic!();
+                }
+                TABLE[index]
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_static_table() {
+    common::test(
+        "test_unsafe_static_table",
+        "test_unsafe_static_table",
+        "
+            static TABLE: [i32; 4] = [1, 2, 3, 4];
+
+            fn test_unsafe_static_table(x: bool) -> i32 {
+                let index = if x { 1 } else { 3 };
+                if TABLE[index] == 4 {
+                    panic!();
+                }
+                TABLE[index]
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_static_mut() {
+    // Known over-approximation: the value of a `static mut` on entry is unconstrained, although nothing writes COUNTER
+    common::test(
+        "test_unsafe_static_mut",
+        "test_unsafe_static_mut",
+        "
+            static mut COUNTER: i32 = 0;
+
+            fn test_unsafe_static_mut() -> () {
+                if unsafe { COUNTER } != 0 {
+                    panic!();
+                }
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_static_mut_store() {
+    common::test(
+        "test_safe_static_mut_store",
+        "test_safe_static_mut_store",
+        "
+            static mut COUNTER: i32 = 0;
+
+            fn test_safe_static_mut_store(x: i32) -> () {
+                unsafe { COUNTER = x; }
+                if unsafe { COUNTER } != x {
+                    panic!();
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_safe_main_harness() {
+    common::test_analysis(
+        "test_safe_main_harness",
+        "main",
+        "
+            fn check_small(x: i32) -> () {
+                if x > 10 {
+                    panic!();
+                }
+            }
+
+            fn main() {
+                check_small(3);
+                println!(\"done\");
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Safe(true),
+    );
+}
+
+#[test]
+fn test_unsafe_main_harness() {
+    common::test_analysis(
+        "test_unsafe_main_harness",
+        "main",
+        "
+            fn main() {
+                if std::env::args().len() == 4 {
+                    panic!();
+                }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_strict_main_harness_out_pointer() {
+    // The `Option<OsString>` is returned through an out pointer, whose contents are unconstrained
+    common::test_analysis(
+        "test_strict_main_harness_out_pointer",
+        "main",
+        "
+            fn main() {
+                if std::env::var_os(\"WOMBAT_SYMX_TEST\").is_some() {
+                    panic!();
+                }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            strict: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(None),
+    );
+}
+
+#[test]
+fn test_main_harness_args_output() {
+    common::test_output(
+        "test_main_harness_args_output",
+        "main",
+        "
+            fn main() {
+                if std::env::args().len() == 4 {
+                    panic!();
+                }
+            }
+        ",
+        &[],
+        &["Unsafe inputs:", "std::env::Args as core::iter::traits::exact_size::ExactSizeIterator>::len = 4"],
+    );
+}
+
+#[test]
+fn test_main_harness_stdin_output() {
+    common::test_output(
+        "test_main_harness_stdin_output",
+        "main",
+        "
+            fn main() {
+                let mut line = String::new();
+                let read = std::io::stdin().read_line(&mut line).unwrap();
+                if read == 7 {
+                    panic!();
+                }
+            }
+        ",
+        &[],
+        &["Unsafe inputs:", "std::io::stdio::Stdin::read_line.1 = 7"],
+    );
+}
+
+#[test]
+fn test_unsound_safe_stdin_line() {
+    // The line written into the `String` is not symbolic, so the length check is not proven safe
+    common::test_analysis(
+        "test_unsound_safe_stdin_line",
+        "main",
+        "
+            fn main() {
+                let mut line = String::new();
+                if std::io::stdin().read_line(&mut line).is_ok() && line.len() > 7 {
+                    panic!();
+                }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions::default(),
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 1 })),
+    );
+}
+
+#[test]
+fn test_safe_invoke_cleanup() {
+    common::test(
+        "test_safe_invoke_cleanup",
+        "test_safe_invoke_cleanup",
+        "
+            struct Guard(i32);
+
+            impl Drop for Guard {
+                fn drop(&mut self) {}
+            }
+
+            fn check_not_seven(x: i32) -> () {
+                if x == 7 {
+                    panic!();
+                }
+            }
+
+            fn test_safe_invoke_cleanup(x: i32) -> () {
+                let _guard = Guard(x);
+                if x != 7 {
+                    check_not_seven(x);
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_invoke_cleanup() {
+    common::test(
+        "test_unsafe_invoke_cleanup",
+        "test_unsafe_invoke_cleanup",
+        "
+            struct Guard(i32);
+
+            impl Drop for Guard {
+                fn drop(&mut self) {}
+            }
+
+            fn check_not_seven(x: i32) -> () {
+                if x == 7 {
+                    panic!();
+                }
+            }
+
+            fn test_unsafe_invoke_cleanup(x: i32) -> () {
+                let _guard = Guard(x);
+                check_not_seven(x);
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_function_pointer_call() {
+    common::test(
+        "test_safe_function_pointer_call",
+        "test_safe_function_pointer_call",
+        "
+            fn halve(x: i32) -> i32 {
+                x / 2
+            }
+
+            fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+                f(x)
+            }
+
+            fn test_safe_function_pointer_call(x: i32) -> i32 {
+                apply(halve, x)
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_function_pointer_call() {
+    common::test(
+        "test_unsafe_function_pointer_call",
+        "test_unsafe_function_pointer_call",
+        "
+            fn halve(x: i32) -> i32 {
+                x / 2
+            }
+
+            fn increment(x: i32) -> i32 {
+                x + 1
+            }
+
+            fn test_unsafe_function_pointer_call(x: i32, is_halved: bool) -> i32 {
+                let f: fn(i32) -> i32 = if is_halved { halve } else { increment };
+                f(x)
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_unknown_function_pointer() {
+    common::test(
+        "test_unsafe_unknown_function_pointer",
+        "test_unsafe_unknown_function_pointer",
+        "
+            fn double(x: i32) -> i32 {
+                x.wrapping_mul(2)
+            }
+
+            fn test_unsafe_unknown_function_pointer(f: fn(i32) -> i32, x: i32) -> i32 {
+                let y = if x > 0 { double(x) } else { f(x) };
+                assert!(y % 2 == 0);
+                y
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_dyn_dispatch() {
+    common::test(
+        "test_safe_dyn_dispatch",
+        "test_safe_dyn_dispatch",
+        "
+            trait Shape {
+                fn sides(&self) -> i32;
+            }
+
+            struct Triangle;
+
+            struct Square;
+
+            impl Shape for Triangle {
+                fn sides(&self) -> i32 {
+                    3
+                }
+            }
+
+            impl Shape for Square {
+                fn sides(&self) -> i32 {
+                    4
+                }
+            }
+
+            fn test_safe_dyn_dispatch(is_triangle: bool) -> i32 {
+                let shape: &dyn Shape = if is_triangle { &Triangle } else { &Square };
+                12 / (shape.sides() - 2)
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_dyn_dispatch() {
+    common::test(
+        "test_unsafe_dyn_dispatch",
+        "test_unsafe_dyn_dispatch",
+        "
+            trait Shape {
+                fn sides(&self) -> i32;
+            }
+
+            struct Triangle;
+
+            struct Square;
+
+            impl Shape for Triangle {
+                fn sides(&self) -> i32 {
+                    3
+                }
+            }
+
+            impl Shape for Square {
+                fn sides(&self) -> i32 {
+                    4
+                }
+            }
+
+            fn test_unsafe_dyn_dispatch(is_triangle: bool) -> i32 {
+                let shape: &dyn Shape = if is_triangle { &Triangle } else { &Square };
+                12 / (shape.sides() - 3)
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_exact_function_name() {
+    common::test(
+        "test_safe_exact_function_name",
+        "test_safe_exact_function_name",
+        "
+            fn test_safe_exact_function_name_unchecked(x: i32) -> i32 {
+                100 / x
+            }
+
+            fn test_safe_exact_function_name(x: i32) -> i32 {
+                if x > 0 {
+                    test_safe_exact_function_name_unchecked(x)
+                } else {
+                    0
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_safe_all_instances() {
+    common::test_analysis(
+        "test_safe_all_instances",
+        "scale",
+        "
+            fn scale<const N: i32>(x: i8) -> i32 {
+                x as i32 * N
+            }
+
+            fn test_safe_all_instances(x: i8) -> i32 {
+                scale::<10>(x) - scale::<20>(x)
+            }
+        ",
+        Analysis::AllInstances(2),
+        AnalysisOptions::default(),
+        Expected::Safe(true),
+    );
+}
+
+#[test]
+fn test_unsafe_all_instances() {
+    common::test_analysis(
+        "test_unsafe_all_instances",
+        "scale",
+        "
+            fn scale<const N: i32>(x: i8) -> i32 {
+                x as i32 * N
+            }
+
+            fn test_unsafe_all_instances(x: i8) -> i32 {
+                scale::<10>(x) - scale::<100000000>(x)
+            }
+        ",
+        Analysis::AllInstances(2),
+        AnalysisOptions::default(),
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_unsafe_all_instances_strict() {
+    // The f32 instance is refused by the strict mode, the i32 instance is still analysed
+    common::test_analysis(
+        "test_unsafe_all_instances_strict",
+        "check",
+        "
+            fn check<T: Copy>(x: T, y: i32) -> T {
+                assert!(y != 7);
+                x
+            }
+
+            fn test_unsafe_all_instances_strict(x: i32, y: f32, z: i32) -> i32 {
+                let _ = check(y, z);
+                check(x, z)
+            }
+        ",
+        Analysis::AllInstances(2),
+        AnalysisOptions {
+            strict: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_list_functions_output() {
+    common::test_output(
+        "test_list_functions_output",
+        "test_list_functions_output",
+        "
+            fn test_list_functions_output(
+                x: i8,
+                factor: i8,
+            ) -> i16 {
+                x as i16 * factor as i16
+            }
+        ",
+        &["--list-functions"],
+        &["test_list_functions_output: fn test_list_functions_output(x: i8, factor: i8) -> i16"],
+    );
+}
+
+#[test]
+fn test_safe_option_match() {
+    common::test(
+        "test_safe_option_match",
+        "test_safe_option_match",
+        "
+            fn test_safe_option_match(x: Option<i32>) -> i32 {
+                match x {
+                    Some(value) => value / 2,
+                    None => 0,
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_option_expect() {
+    common::test_output(
+        "test_unsafe_option_expect",
+        "test_unsafe_option_expect",
+        "
+            fn test_unsafe_option_expect(x: Option<i32>) -> i32 {
+                x.expect(\"x is required\")
+            }
+        ",
+        &[],
+        &["Panic message: x is required"],
+    );
+}
+
+#[test]
+fn test_unsafe_unreachable_unchecked_without_ub() {
+    // Without --ub, reaching a lone `unreachable` is reported like a panic rather than discarded
+    common::test(
+        "test_unsafe_unreachable_unchecked_without_ub",
+        "test_unsafe_unreachable_unchecked_without_ub",
+        "
+            fn test_unsafe_unreachable_unchecked_without_ub(x: i32) -> i32 {
+                if x > 10 {
+                    unsafe { std::hint::unreachable_unchecked() }
+                }
+                x
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_result_unwrap() {
+    common::test(
+        "test_unsafe_result_unwrap",
+        "test_unsafe_result_unwrap",
+        "
+            fn test_unsafe_result_unwrap(x: Result<i32, i32>) -> i32 {
+                x.unwrap()
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_enum_param() {
+    common::test(
+        "test_safe_enum_param",
+        "test_safe_enum_param",
+        "
+            enum Color {
+                Red,
+                Green,
+                Blue,
+            }
+
+            fn test_safe_enum_param(color: Color) -> i32 {
+                10 / (color as i32 + 1)
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_enum_param() {
+    common::test(
+        "test_unsafe_enum_param",
+        "test_unsafe_enum_param",
+        "
+            enum Color {
+                Red,
+                Green,
+                Blue,
+            }
+
+            fn test_unsafe_enum_param(color: Color) -> i32 {
+                10 / (color as i32 - 1)
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_safe_char_param() {
+    common::test(
+        "test_safe_char_param",
+        "test_safe_char_param",
+        "
+            fn test_safe_char_param(c: char) -> i32 {
+                // Surrogates are not valid chars
+                1000 / (c as i32 - 0xD900)
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_unreachable_unchecked() {
+    common::test_analysis(
+        "test_unsafe_unreachable_unchecked",
+        "test_unsafe_unreachable_unchecked",
+        "
+            fn test_unsafe_unreachable_unchecked(x: u8) -> u8 {
+                if x < 200 {
+                    x
+                } else {
+                    unsafe { std::hint::unreachable_unchecked() }
+                }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_safe_null_checked_pointer() {
+    common::test_analysis(
+        "test_safe_null_checked_pointer",
+        "test_safe_null_checked_pointer",
+        "
+            fn test_safe_null_checked_pointer(p: *const i32) -> i32 {
+                if p as usize == 0 || p as usize % 4 != 0 {
+                    return 0;
+                }
+                unsafe { *p }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Safe(true),
+    );
+}
+
+#[test]
+fn test_unsafe_null_pointer() {
+    common::test_analysis(
+        "test_unsafe_null_pointer",
+        "test_unsafe_null_pointer",
+        "
+            fn test_unsafe_null_pointer(p: *const i32) -> i32 {
+                if p as usize % 4 != 0 {
+                    return 0;
+                }
+                unsafe { *p }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_unsafe_misaligned_load() {
+    common::test_analysis(
+        "test_unsafe_misaligned_load",
+        "test_unsafe_misaligned_load",
+        "
+            fn test_unsafe_misaligned_load(bytes: &[u32; 2], i: usize) -> u32 {
+                if i > 4 {
+                    return 0;
+                }
+                unsafe { *((bytes as *const [u32; 2] as usize + i) as *const u32) }
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_safe_vec_index() {
+    common::test(
+        "test_safe_vec_index",
+        "test_safe_vec_index",
+        "
+            fn test_safe_vec_index(n: u32) -> i32 {
+                let mut v = Vec::new();
+                v.push(1);
+                v.push(2);
+                if n < 2 {
+                    v[n as usize]
+                } else {
+                    0
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_vec_index() {
+    common::test(
+        "test_unsafe_vec_index",
+        "test_unsafe_vec_index",
+        "
+            fn test_unsafe_vec_index(n: u32) -> i32 {
+                let mut v = Vec::new();
+                v.push(1);
+                v.push(2);
+                if n < 3 {
+                    v[n as usize]
+                } else {
+                    0
+                }
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_double_free() {
+    common::test_analysis(
+        "test_unsafe_double_free",
+        "test_unsafe_double_free",
+        "
+            fn test_unsafe_double_free(twice: bool) -> i32 {
+                let layout = std::alloc::Layout::new::<u64>();
+                unsafe {
+                    let pointer = std::alloc::alloc(layout);
+                    std::alloc::dealloc(pointer, layout);
+                    if twice {
+                        std::alloc::dealloc(pointer, layout);
+                    }
+                }
+                0
+            }
+        ",
+        Analysis::Panics,
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Safe(false),
+    );
+}
+
+#[test]
+fn test_safe_str_slice() {
+    common::test(
+        "test_safe_str_slice",
+        "test_safe_str_slice",
+        "
+            fn test_safe_str_slice(s: &str) -> usize {
+                if s.len() > 1 && s.is_char_boundary(1) {
+                    s[1..].len()
+                } else {
+                    0
+                }
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_str_slice() {
+    common::test(
+        "test_unsafe_str_slice",
+        "test_unsafe_str_slice",
+        "
+            fn test_unsafe_str_slice(s: &str) -> usize {
+                if s.len() > 1 {
+                    s[1..].len()
+                } else {
+                    0
+                }
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_str_slice_to() {
+    common::test(
+        "test_unsafe_str_slice_to",
+        "test_unsafe_str_slice_to",
+        "
+            fn test_unsafe_str_slice_to(s: &str) -> usize {
+                if s.len() > 1 {
+                    s[..1].len()
+                } else {
+                    0
+                }
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_str_bytes() {
+    common::test(
+        "test_unsafe_str_bytes",
+        "test_unsafe_str_bytes",
+        "
+            fn test_unsafe_str_bytes(s: &str) -> i32 {
+                let bytes = s.as_bytes();
+                if bytes.len() == 2 && bytes[0] == b'o' && bytes[1] == b'k' {
+                    panic!(\"found\");
+                }
+                0
+            }
+        ",
+        false,
+    );
+}
+
+#[test]
+fn test_unsafe_str_literal_output() {
+    common::test_output(
+        "test_unsafe_str_literal_output",
+        "test_unsafe_str_literal_output",
+        "
+            fn test_unsafe_str_literal_output(s: &str) -> i32 {
+                let bytes = s.as_bytes();
+                if bytes.len() == 2 && bytes[0] == b'o' && bytes[1] == b'k' {
+                    panic!(\"found\");
+                }
+                0
+            }
+        ",
+        &[],
+        &["Unsafe values:", "\"s\" = \"ok\""],
+    );
+}
+
+#[test]
+fn test_safe_usize_host() {
+    common::test(
+        "test_safe_usize_host",
+        "test_safe_usize_host",
+        "
+            fn test_safe_usize_host(x: u32) -> usize {
+                x as usize + 1
+            }
+        ",
+        true,
+    );
+}
+
+#[test]
+fn test_unsafe_usize_i686() {
+    if !common::is_target_installed("i686-unknown-linux-gnu") {
+        eprintln!("Skipping test_unsafe_usize_i686: the i686-unknown-linux-gnu standard library is not installed");
+        return;
+    }
+    // `usize` is 32 bits wide on i686, so only the largest `u32` overflows
+    common::test_output(
+        "test_unsafe_usize_i686",
+        "test_unsafe_usize_i686",
+        "
+            fn test_unsafe_usize_i686(x: u32) -> usize {
+                x as usize + 1
+            }
+        ",
+        &["--target", "i686-unknown-linux-gnu"],
+        &["Unsafe values:", "\"x\" = 4294967295"],
+    );
+}
+
+#[test]
+fn test_symbol_table_quoted_names() {
+    let context = InkwellContext::create();
+    let module = common::load_ir_module(&context, "
+            @\"a b\" = global i32 0
+
+            define i32 @f(i32 %\"x y\") {
+            start:
+              %\"s.t\" = load i32, i32* @\"a b\"
+              ret i32 %\"s.t\"
+            }
+        ");
+    let function = module.get_function("f").unwrap();
+    let load = function.get_first_basic_block().unwrap().get_first_instruction().unwrap();
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+
+    // Names are taken unquoted from LLVM, the printout keeps the quotes
+    let parameter_name = get_var_name(&function.get_nth_param(0).unwrap(), &solver, &analysis, "");
+    assert!(parameter_name == "%x y");
+    assert!(get_var_name(&load, &solver, &analysis, "") == "%s.t");
+    let global_name = get_var_name(&load.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "");
+    assert!(global_name == "@a b");
+    assert!(analysis.symbols.get_symbol(&global_name).unwrap().description.contains("@\"a b\""));
+}
+
+#[test]
+fn test_symbol_table_globals() {
+    let context = InkwellContext::create();
+    let module = common::load_ir_module(&context, "
+            @g = global i32 0
+
+            define i32 @f(i32 %x) {
+            start:
+              %y = load i32, i32* @g
+              ret i32 %y
+            }
+        ");
+    let function = module.get_function("f").unwrap();
+    let global = module.get_global("g").unwrap().as_pointer_value();
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+
+    // Globals and functions are shared by all namespaces, registers are not
+    assert!(get_var_name(&global, &solver, &analysis, "lhs.") == "@g");
+    assert!(get_var_name(&global, &solver, &analysis, "rhs.") == "@g");
+    assert!(get_var_name(&function.as_global_value().as_pointer_value(), &solver, &analysis, "lhs.") == "@f");
+    assert!(get_var_name(&function.get_nth_param(0).unwrap(), &solver, &analysis, "lhs.") == "lhs.%x");
+    assert!(get_var_name(&function.get_nth_param(0).unwrap(), &solver, &analysis, "rhs.") == "rhs.%x");
+    assert!(analysis.symbols.get_symbol("@g").unwrap().kind == SymbolKind::Global);
+}
+
+#[test]
+fn test_symbol_table_unnamed_temporaries() {
+    let context = InkwellContext::create();
+    let module = common::load_ir_module(&context, "
+            define i32 @f(i32 %0) {
+            start:
+              %1 = add i32 %0, 1
+              %2 = mul i32 %1, 2
+              ret i32 %2
+            }
+        ");
+    let function = module.get_function("f").unwrap();
+    let add = function.get_first_basic_block().unwrap().get_first_instruction().unwrap();
+    let mul = add.get_next_instruction().unwrap();
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+
+    // Unnamed values get fresh names, which are kept for later uses
+    let parameter_name = get_var_name(&function.get_nth_param(0).unwrap(), &solver, &analysis, "");
+    let add_name = get_var_name(&add, &solver, &analysis, "");
+    let mul_name = get_var_name(&mul, &solver, &analysis, "");
+    assert!(parameter_name != add_name && add_name != mul_name && parameter_name != mul_name);
+    assert!(get_var_name(&add, &solver, &analysis, "") == add_name);
+    assert!(get_var_name(&mul.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "") == add_name);
+    assert!(get_var_name(&add, &solver, &analysis, "callee.") != add_name);
+
+    // Names of instructions are dropped when the module is rewritten, those of arguments are kept
+    analysis.symbols.forget_instructions();
+    assert!(analysis.symbols.get_symbol(&add_name).is_none());
+    assert!(get_var_name(&function.get_nth_param(0).unwrap(), &solver, &analysis, "") == parameter_name);
+}
+
+#[test]
+fn test_symbol_table_reverse_map() {
+    let context = InkwellContext::create();
+    let module = common::load_ir_module(&context, "
+            define <2 x i32> @f(i32 %x) {
+            start:
+              %y = add i32 %x, 5
+              %v = insertelement <2 x i32> <i32 1, i32 2>, i32 %y, i32 0
+              ret <2 x i32> %v
+            }
+        ");
+    let function = module.get_function("f").unwrap();
+    let add = function.get_first_basic_block().unwrap().get_first_instruction().unwrap();
+    let insert = add.get_next_instruction().unwrap();
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+
+    // The counterexample printout maps the Z3 names back to the values, lanes by the name of their vector
+    let add_name = get_var_name(&add, &solver, &analysis, "");
+    let literal_name = get_var_name(&add.get_operand(1).unwrap().left().unwrap(), &solver, &analysis, "");
+    let vector_literal_name = get_var_name(&insert.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "");
+    let insert_name = get_var_name(&insert, &solver, &analysis, "");
+    assert!(literal_name == "const_5");
+    assert!(vector_literal_name == "const_<1,2>");
+    let add_symbol = analysis.symbols.get_symbol(&add_name).unwrap();
+    assert!(add_symbol.kind == SymbolKind::Register && add_symbol.description == add.print_to_string().to_string());
+    assert!(analysis.symbols.get_symbol(&literal_name).unwrap().kind == SymbolKind::Literal);
+    assert!(analysis.symbols.get_symbol(&format!("{}.1", vector_literal_name)).unwrap().kind == SymbolKind::Literal);
+    assert!(analysis.symbols.get_symbol(&format!("{}.0", insert_name)).unwrap().kind == SymbolKind::Register);
+    assert!(analysis.symbols.get_symbol("%unknown").is_none());
+    assert!(analysis.symbols.get_symbols().iter().any(|(name, _)| name == &add_name));
+}
+
+#[test]
+fn test_safe_ir_freeze() {
+    common::test_ir("
+            define i32 @f(i32 %x) {
+            start:
+              %frozen = freeze i32 %x
+              %r = sub i32 %frozen, %x
+              ret i32 %r
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("ret == 0")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_ir_freeze_undef() {
+    common::test_ir("
+            define i32 @f() {
+            start:
+              %frozen = freeze i32 undef
+              ret i32 %frozen
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("ret == 0")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_safe_ir_vector_lanes() {
+    common::test_ir("
+            define i32 @f(i32 %x, i32 %y) {
+            start:
+              %v0 = insertelement <2 x i32> undef, i32 %x, i32 0
+              %v1 = insertelement <2 x i32> %v0, i32 %y, i32 1
+              %sum = add <2 x i32> %v1, <i32 1, i32 2>
+              %a = extractelement <2 x i32> %sum, i32 0
+              %b = extractelement <2 x i32> %sum, i32 1
+              %r = sub i32 %b, %a
+              ret i32 %r
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("x > -1000 && x < 1000 && y > -1000 && y < 1000 ==> ret == y - x + 1")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_ir_vector_lanes() {
+    // The lanes are added to their own constant, so swapping them is caught
+    common::test_ir("
+            define i32 @f(i32 %x, i32 %y) {
+            start:
+              %v0 = insertelement <2 x i32> undef, i32 %x, i32 0
+              %v1 = insertelement <2 x i32> %v0, i32 %y, i32 1
+              %sum = add <2 x i32> %v1, <i32 1, i32 2>
+              %a = extractelement <2 x i32> %sum, i32 0
+              %b = extractelement <2 x i32> %sum, i32 1
+              %r = sub i32 %b, %a
+              ret i32 %r
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("x > -1000 && x < 1000 && y > -1000 && y < 1000 ==> ret == y - x - 1")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_unsafe_ir_undef_and_poison_are_fresh() {
+    // Every use of `undef` or `poison` may take a different value
+    common::test_ir("
+            define i32 @f() {
+            start:
+              %a = add i32 undef, 0
+              %b = add i32 poison, 0
+              %r = sub i32 %a, %b
+              ret i32 %r
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("ret == 0")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+    let context = InkwellContext::create();
+    let module = common::load_ir_module(&context, "
+            define i32 @f() {
+            start:
+              %a = add i32 undef, 0
+              %b = add i32 poison, 0
+              ret i32 %a
+            }
+        ");
+    let a = module.get_function("f").unwrap().get_first_basic_block().unwrap().get_first_instruction().unwrap();
+    let b = a.get_next_instruction().unwrap();
+    let z3_context = Z3Context::new(&Config::new());
+    let solver = Solver::new(&z3_context);
+    let analysis = AnalysisContext::new(&AnalysisOptions::default());
+    let undef_name = get_var_name(&a.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "");
+    let other_undef_name = get_var_name(&a.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "");
+    let poison_name = get_var_name(&b.get_operand(0).unwrap().left().unwrap(), &solver, &analysis, "");
+    assert!(undef_name != other_undef_name && undef_name != poison_name && other_undef_name != poison_name);
+}
+
+#[test]
+fn test_safe_ir_select_chain() {
+    common::test_ir("
+            define i32 @f(i32 %x) {
+            start:
+              %is_negative = icmp slt i32 %x, 0
+              %low = select i1 %is_negative, i32 -1, i32 %x
+              %is_large = icmp sgt i32 %low, 10
+              %clamped = select i1 %is_large, i32 10, i32 %low
+              ret i32 %clamped
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("ret >= -1 && ret <= 10"), String::from("x >= 0 && x <= 10 ==> ret == x")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })),
+    );
+}
+
+#[test]
+fn test_unsafe_ir_select_chain() {
+    common::test_ir("
+            define i32 @f(i32 %x) {
+            start:
+              %is_negative = icmp slt i32 %x, 0
+              %low = select i1 %is_negative, i32 -1, i32 %x
+              %is_large = icmp sgt i32 %low, 10
+              %clamped = select i1 %is_large, i32 10, i32 %low
+              ret i32 %clamped
+            }
+        ",
+        "f",
+        AnalysisOptions {
+            ensures: vec![String::from("ret >= 0")],
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_ir_nsw_overflow_only_in_ub_mode() {
+    // The overflow makes the result poison, which is only reported by the UB checker
+    let ir = "
+            define i32 @f(i32 %x) {
+            start:
+              %r = add nsw i32 %x, 1
+              ret i32 %r
+            }
+        ";
+    common::test_ir(ir, "f", AnalysisOptions::default(), Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })));
+    common::test_ir(
+        ir,
+        "f",
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
+
+#[test]
+fn test_ir_nuw_overflow_only_in_ub_mode() {
+    let ir = "
+            define i32 @f(i32 %x) {
+            start:
+              %r = add nuw i32 %x, 1
+              ret i32 %r
+            }
+        ";
+    common::test_ir(ir, "f", AnalysisOptions::default(), Expected::Verdict(Some(Verdict::Safe { unsound_constructs: 0 })));
+    common::test_ir(
+        ir,
+        "f",
+        AnalysisOptions {
+            check_undefined_behaviour: true,
+            ..AnalysisOptions::default()
+        },
+        Expected::Verdict(Some(Verdict::Unsafe)),
+    );
+}
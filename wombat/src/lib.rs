@@ -0,0 +1,23 @@
+//! Companion crate of Wombat SymX for writing verification harnesses
+//!
+//! Under the analysis, `any` returns an unconstrained value, `assume` discards the executions where its condition
+//! does not hold and `assert` reports the executions where its condition does not hold.
+//! When run natively, `any` returns the default value of its type, `assume` does nothing and `assert` panics.
+
+/// Value of type `T` that is unconstrained under the analysis (only integers and booleans are supported)
+#[inline(never)]
+pub fn any<T: Default>() -> T {
+    T::default()
+}
+
+/// Restricts the analysis to the executions where the condition holds
+#[inline(never)]
+pub fn assume(condition: bool) {
+    let _ = condition;
+}
+
+/// Reports the executions where the condition does not hold as unsafe
+#[inline(never)]
+pub fn assert(condition: bool) {
+    assert!(condition, "wombat::assert failed");
+}